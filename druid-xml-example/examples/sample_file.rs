@@ -0,0 +1,23 @@
+#[macro_use]
+extern crate druid_xml_macro;
+
+use druid::{AppLauncher, WindowDesc};
+
+//path is relative to the Cargo.toml of this crate
+druid_xml_file!(
+    "ui/sample_file.xml",
+    "#ok" => {
+        widget.on_click( |_,_,_| {
+            println!("Clicked OK");
+        })
+    }
+);
+
+pub fn main() {
+    let window = WindowDesc::new(build_main())
+        .window_size((400., 200.))
+        .title("xml file sample");
+    AppLauncher::with_window(window)
+        .launch( () )
+        .expect("launch failed");
+}
@@ -2,84 +2,242 @@ extern crate proc_macro;
 
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 use proc_macro::{TokenStream};
 use syn::{parse_macro_input, Result, Token};
 use syn::parse::{Parse, ParseStream};
-use quote::quote;
+use quote::{quote, quote_spanned};
 
-#[proc_macro]
-pub fn druid_xml( input:TokenStream ) -> TokenStream {
-	
-	struct DruidXML {
-		xml_src : syn::LitStr,
-		sep2 : Option<Token![,]>,
-		maps : Option<Vec<WidgetWrapper>>,
+/// `"xml or path", "selector" => { ... }, ...`
+struct DruidXML {
+	xml_src : syn::LitStr,
+	sep2 : Option<Token![,]>,
+	maps : Option<Vec<WidgetWrapper>>,
+}
+
+//like syn::Arm
+// old style : but we want avoid type annotation
+//
+//druid_xml!( r#"
+//",
+//"button" => |btn:Button::<()> | {
+///   println!("On clicked");
+//});
+// struct WidgetWrapper {
+// 	query : syn::LitStr,
+// 	sep : Token![=>],
+// 	bindfn : syn::ExprClosure,
+// 	sepe : Option<Token![,]>,
+// }
+
+//
+struct WidgetWrapper {
+	query : syn::LitStr,
+	sep : Token![=>],
+	bindfn : syn::ExprBlock,
+	sepe : Option<Token![,]>,
+}
+
+impl Parse for WidgetWrapper {
+	fn parse(input: ParseStream) -> Result<Self> {
+		Ok( WidgetWrapper {
+			query : input.parse()?,
+			sep : input.parse()?,
+			bindfn : input.parse()?,
+			sepe : input.parse()?
+		})
 	}
+}
 
-	//like syn::Arm
-	// old style : but we want avoid type annotation
-	//
-	//druid_xml!( r#"
-	//",
-	//"button" => |btn:Button::<()> | {
-	///   println!("On clicked");
-	//});
-	// struct WidgetWrapper {
-	// 	query : syn::LitStr,
-	// 	sep : Token![=>],
-	// 	bindfn : syn::ExprClosure,
-	// 	sepe : Option<Token![,]>,
-	// }
-
-	//
-	struct WidgetWrapper {
-		query : syn::LitStr,
-		sep : Token![=>],
-		bindfn : syn::ExprBlock,
-		sepe : Option<Token![,]>,
+impl Parse for DruidXML {
+	fn parse(input: ParseStream) -> Result<Self> {
+		let xml_src:syn::LitStr = input.parse()?;
+		let sep2 = input.parse()?;
+		let mut maps:Vec<WidgetWrapper> = vec![];
+		loop {
+			if let Ok(ev) = input.parse::<WidgetWrapper>() {
+				maps.push( ev );
+			} else {
+				break
+			}
+		}
+		let maps = if maps.len() > 0 {
+			Some(maps)
+		} else {
+			None
+		};
+		Ok( DruidXML { xml_src, sep2, maps } )
 	}
+}
 
-	impl Parse for WidgetWrapper {
-		fn parse(input: ParseStream) -> Result<Self> {
-			Ok( WidgetWrapper {
-				query : input.parse()?,
-				sep : input.parse()?,
-				bindfn : input.parse()?,
-				sepe : input.parse()?
-			})
+impl DruidXML {
+	fn wrapper_maps(&self) -> HashMap<String,String> {
+		let mut wrapper_maps = HashMap::new();
+		if let Some(wrappers) = self.maps.as_ref() {
+			wrappers.iter().for_each( |e| {
+				let w = &e.bindfn;
+				wrapper_maps.insert(e.query.value(), quote!(#w).to_string() );
+			});
 		}
+		wrapper_maps
 	}
+}
 
-	impl Parse for DruidXML {
-		fn parse(input: ParseStream) -> Result<Self> {
-			let xml_src:syn::LitStr = input.parse()?;
-			let sep2 = input.parse()?;
-			let mut maps:Vec<WidgetWrapper> = vec![];
-			loop {
-				if let Ok(ev) = input.parse::<WidgetWrapper>() {
-					maps.push( ev );
-				} else {
-					break
-				}
-			}
-			let maps = if maps.len() > 0 {
-				Some(maps)
-			} else {
-				None
-			};
-			Ok( DruidXML { xml_src, sep2, maps } )
+fn parse_generated(lit:&syn::LitStr, ui_code:&str) -> proc_macro2::TokenStream {
+	match ui_code.parse::<proc_macro2::TokenStream>() {
+		Ok(tokens) => tokens,
+		Err(e) => {
+			let msg = format!("druid_xml: generated code is invalid : {e}");
+			let span = lit.span();
+			quote_spanned!(span=> compile_error!(#msg);)
 		}
 	}
+}
 
+#[proc_macro]
+pub fn druid_xml( input:TokenStream ) -> TokenStream {
+	let druid_xml = parse_macro_input!(input as DruidXML);
+	let wrapper_maps = druid_xml.wrapper_maps();
+	//`<import src>` is relative to the Cargo.toml of the calling crate
+	let dir = PathBuf::from( std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default() );
+	match druid_xml::compile_with_dir(&druid_xml.xml_src.value(), &dir, &wrapper_maps) {
+		Ok(compiled) => {
+			let trackers = track(&compiled.imports);
+			let handlers = check_handlers(&compiled.handlers, |span| literal_span(&druid_xml.xml_src, span.start, span.end));
+			let ui = parse_generated(&druid_xml.xml_src, &compiled.code);
+			quote!( #trackers #handlers #ui ).into()
+		},
+		Err(diagnostic) => compile_error(&druid_xml.xml_src, &diagnostic)
+	}
+}
+
+/// Same as `druid_xml!` but load xml from the file.
+/// The path is relative to `CARGO_MANIFEST_DIR` and editing the file triggers rebuild.
+///
+/// ```ignore
+/// druid_xml_file!("ui/login.xml",
+///     "#ok" => {
+///         widget.on_click( |_,_,_| println!("OK") )
+///     }
+/// );
+/// ```
+#[proc_macro]
+pub fn druid_xml_file( input:TokenStream ) -> TokenStream {
 	let druid_xml = parse_macro_input!(input as DruidXML);
-	let mut wrapper_maps = HashMap::new();
-	if let Some(wrappers) = druid_xml.maps.as_ref() {
-		wrappers.iter().for_each( |e| {
-			let w = &e.bindfn;
-			wrapper_maps.insert(e.query.value(), quote!(#w).to_string() );
-		});
+	let wrapper_maps = druid_xml.wrapper_maps();
+	let rel_path = druid_xml.xml_src.value();
+	let path = match std::env::var("CARGO_MANIFEST_DIR") {
+		Ok(dir) => PathBuf::from(dir).join(&rel_path),
+		Err(_) => PathBuf::from(&rel_path)
+	};
+	let span = druid_xml.xml_src.span();
+
+	let xml = match std::fs::read_to_string(&path) {
+		Ok(xml) => xml,
+		Err(e) => {
+			let msg = format!("druid_xml: can't read \"{}\" : {e}", path.display());
+			return quote_spanned!(span=> compile_error!(#msg);).into()
+		}
+	};
+
+	let tracker = track( std::slice::from_ref(&path) );
+
+	let dir = path.parent().map( |e| e.to_path_buf() ).unwrap_or_default();
+	let ui = match druid_xml::compile_with_dir(&xml, &dir, &wrapper_maps) {
+		Ok(compiled) => {
+			let trackers = track(&compiled.imports);
+			//no source span of the file
+			let handlers = check_handlers(&compiled.handlers, |_| span);
+			let ui = parse_generated(&druid_xml.xml_src, &compiled.code);
+			quote!( #trackers #handlers #ui )
+		},
+		Err(diagnostic) => {
+			let msg = format!("druid_xml: {}:{}:{}: {}\n{}", rel_path, diagnostic.line, diagnostic.column, diagnostic.message, diagnostic.snippet);
+			quote_spanned!(span=> compile_error!(#msg);)
+		}
+	};
+	quote!( #tracker #ui ).into()
+}
+
+/// `include_bytes!` makes cargo track the file(editing it triggers rebuild)
+fn track(paths:&[PathBuf]) -> proc_macro2::TokenStream {
+	let paths = paths.iter().map( |e| e.to_string_lossy().to_string() );
+	quote!( #( const _ : &[u8] = include_bytes!(#paths); )* )
+}
+
+/// Typed binding of every handler function with the span of the attribute.
+/// Unknown function(``cannot find value `login` in this scope``) and the wrong signature are reported at the attribute
+fn check_handlers(handlers:&[(String, druid_xml::Span)], span_of:impl Fn(&druid_xml::Span) -> proc_macro2::Span) -> proc_macro2::TokenStream {
+	let checks = handlers.iter().filter_map( |(path, at)| {
+		let span = span_of(at);
+		//invalid path is the error of the compile
+		let path = path.parse::<proc_macro2::TokenStream>().ok()?.into_iter().map( |mut e| { e.set_span(span); e } ).collect::<proc_macro2::TokenStream>();
+		Some( quote_spanned!(span=> let _ : druid_xml::widget::Handler<_> = #path;) )
+	});
+	quote!( const _ : () = { #( #checks )* }; )
+}
+
+/// `compile_error!` pointing into the xml string literal.
+fn compile_error(lit:&syn::LitStr, diagnostic:&druid_xml::Diagnostic) -> TokenStream {
+	let msg = format!("druid_xml: {}", diagnostic);
+	let span = literal_span(lit, diagnostic.start, diagnostic.end);
+	quote_spanned!(span=> compile_error!(#msg);).into()
+}
+
+/// Span of the byte range of the string value.
+/// If the compiler can't make a sub span(stable rust), the whole literal is pointed.
+fn literal_span(lit:&syn::LitStr, start:usize, end:usize) -> proc_macro2::Span {
+	let token = lit.token();
+	match ( source_offset(&token.to_string(), start), source_offset(&token.to_string(), end) ) {
+		(Some(start), Some(end)) => token.subspan(start .. end.max(start+1)).unwrap_or( lit.span() ),
+		_ => lit.span()
+	}
+}
+
+/// Convert byte offset of the string value to byte offset of the literal source.
+///
+/// `r#"<flex>"#` : value offset 1 is source offset 4
+/// `"<label>\"A\"</label>"` : escapes are counted as the source length
+fn source_offset(lit_src:&str, value_offset:usize) -> Option<usize> {
+	if lit_src.starts_with('r') {
+		//raw string has no escape
+		let hashes = lit_src[1..].chars().take_while( |c| *c == '#' ).count();
+		return Some( 1 + hashes + 1 + value_offset )
+	}
+	if !lit_src.starts_with('"') {
+		return None
+	}
+
+	let bytes = lit_src.as_bytes();
+	let mut src = 1;
+	let mut value = 0;
+	while value < value_offset {
+		if src >= bytes.len() - 1 {
+			return None
+		}
+		if bytes[src] == b'\\' {
+			match bytes.get(src+1)? {
+				b'x' => { src += 4; value += 1; },
+				b'u' => {
+					let close = lit_src[src..].find('}')?;
+					let ch = u32::from_str_radix( &lit_src[src+3 .. src+close], 16 ).ok().and_then( char::from_u32 )?;
+					src += close + 1;
+					value += ch.len_utf8();
+				},
+				b'\n' | b'\r' => {
+					//line continuation skips following whitespace
+					src += 1;
+					while src < bytes.len() && (bytes[src] as char).is_whitespace() {
+						src += 1;
+					}
+				},
+				_ => { src += 2; value += 1; }
+			}
+		} else {
+			src += 1;
+			value += 1;
+		}
 	}
-	let ui_code = druid_xml::compile(&druid_xml.xml_src.value(), &wrapper_maps).unwrap();
-	ui_code.parse().unwrap()
+	Some(src)
 }
@@ -0,0 +1,193 @@
+//! Data-bound text of label and button.
+//!
+//! `<label>Count: {AppState::count}</label>`, `<label>{price:.2}</label>` or `<label text-lens="AppState::status"/>`
+//!
+//! `{Type::field}` is the lens path(`crate::lens`) and `{field}` is the field of the template `lens` type.
+//! After `:` is the format spec of `format!`(`[[fill]align][width][.precision]`). `{{` and `}}` are the literal braces.
+
+use std::fmt::Write;
+
+use crate::Error;
+use crate::lens::LensPath;
+
+#[derive(Debug,PartialEq)]
+pub(crate) enum Part<'a> {
+    Text(String),
+    Bind(Binding<'a>),
+}
+
+#[derive(Debug,PartialEq)]
+pub(crate) struct Binding<'a> {
+    /// `Type::field` or `field`
+    pub path : &'a str,
+    /// format spec without `:`
+    pub spec : Option<&'a str>,
+}
+
+impl <'a> Binding<'a> {
+    /// `AppState::price:.2` => (`AppState::price`, `.2`)
+    pub fn parse(v:&'a str) -> Option<Self> {
+        let v = v.trim();
+        let bytes = v.as_bytes();
+        //first `:` which is not the path separator `::`
+        let mut split = None;
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b':' {
+                if bytes.get(i+1) == Some(&b':') {
+                    i += 2;
+                    continue
+                }
+                split = Some(i);
+                break
+            }
+            i += 1;
+        }
+        let (path, spec) = match split {
+            Some(at) => (v[..at].trim(), Some(&v[at+1..])),
+            None => (v, None)
+        };
+        if LensPath::parse(path).is_some() {
+            Some( Self { path, spec } )
+        } else {
+            None
+        }
+    }
+
+    /// Lens expression. `field` is the field of the `data_type`
+    pub fn lens(&self, data_type:Option<&str>, pos:usize) -> Result<String, Error> {
+        LensPath::parse(self.path).ok_or( Error::InvalidAttributeValue((pos, "lens")) )?.to_rust(data_type, pos)
+    }
+}
+
+/// Format spec of the binding which is available in both backends. Other flags of `format!`(`+`, `#`, `0` ...) are not
+#[derive(Debug,Clone,PartialEq)]
+pub(crate) struct FormatSpec {
+    pub fill : char,
+    /// `<`, `^` or `>`. Default is the left of the text and the right of the number
+    pub align : Option<char>,
+    pub width : Option<usize>,
+    pub precision : Option<usize>,
+}
+
+impl FormatSpec {
+    /// `None` if it's not `[[fill]align][width][.precision]`
+    pub fn parse(v:&str) -> Option<Self> {
+        let is_align = |c:char| matches!(c, '<' | '^' | '>');
+        let mut chars = v.chars();
+        let (fill, align, rest) = match (chars.next(), chars.next()) {
+            (Some(fill), Some(align)) if is_align(align) => (fill, Some(align), &v[fill.len_utf8()+1..]),
+            (Some(align), _) if is_align(align) => (' ', Some(align), &v[1..]),
+            _ => (' ', None, v)
+        };
+        if fill == '{' || fill == '}' {
+            return None
+        }
+        let (width, precision) = match rest.split_once('.') {
+            Some((width, precision)) => (width, Some(precision)),
+            None => (rest, None)
+        };
+        let number = |v:&str| v.bytes().all( |c| c.is_ascii_digit() ).then( || v.parse::<usize>().ok() ).flatten();
+        let width = match width {
+            "" => None,
+            //`0` is the flag of `format!`
+            _ if width.starts_with('0') => return None,
+            _ => Some( number(width)? )
+        };
+        let precision = match precision {
+            Some(v) => Some( number(v)? ),
+            None => None
+        };
+        Some( Self { fill, align, width, precision } )
+    }
+
+    /// Text of the value with the `width`. `precision` is applied already
+    pub fn pad(&self, text:String, numeric:bool) -> String {
+        let len = text.chars().count();
+        let width = match self.width {
+            Some(width) if width > len => width - len,
+            _ => return text
+        };
+        let (left, right) = match self.align.unwrap_or( if numeric { '>' } else { '<' } ) {
+            '<' => (0, width),
+            '^' => (width / 2, width - width / 2),
+            _ => (width, 0)
+        };
+        let fill = |n:usize| std::iter::repeat(self.fill).take(n).collect::<String>();
+        format!("{}{text}{}", fill(left), fill(right))
+    }
+}
+
+/// `None` if there is no binding. Braces which are not the binding(`${`, `{ not path }`) are the text
+pub(crate) fn parse(text:&str) -> Option<Vec<Part>> {
+    let mut parts = vec![];
+    let mut buf = String::new();
+    let mut has_binding = false;
+    let mut rest = text;
+    while let Some(at) = rest.find( |c| c == '{' || c == '}' ) {
+        buf.push_str( &rest[..at] );
+        let tail = &rest[at..];
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            buf.push_str( &tail[..1] );
+            rest = &tail[2..];
+            continue
+        }
+        if tail.starts_with('{') && !buf.ends_with('$') {
+            if let Some(end) = tail.find('}') {
+                if let Some(binding) = Binding::parse(&tail[1..end]) {
+                    if !buf.is_empty() {
+                        parts.push( Part::Text(std::mem::take(&mut buf)) );
+                    }
+                    parts.push( Part::Bind(binding) );
+                    has_binding = true;
+                    rest = &tail[end+1..];
+                    continue
+                }
+            }
+        }
+        buf.push_str( &tail[..1] );
+        rest = &tail[1..];
+    }
+    buf.push_str(rest);
+    if !buf.is_empty() {
+        parts.push( Part::Text(buf) );
+    }
+    if has_binding { Some(parts) } else { None }
+}
+
+/// Text of the parts. The text braces are escaped
+pub(crate) fn to_source(parts:&[Part]) -> String {
+    let mut src = String::new();
+    for part in parts {
+        match part {
+            Part::Text(t) => src.push_str( &t.replace('{', "{{").replace('}', "}}") ),
+            Part::Bind(Binding { path, spec:Some(spec) }) => write!(src, "{{{path}:{spec}}}").unwrap(),
+            Part::Bind(Binding { path, spec:None }) => write!(src, "{{{path}}}").unwrap(),
+        }
+    }
+    src
+}
+
+/// Unescape `{{` and `}}` of the text without binding
+pub(crate) fn unescape(text:&str) -> String {
+    text.replace("{{", "{").replace("}}", "}")
+}
+
+/// `|data, _env| format!(...)` closure
+pub(crate) fn closure(parts:&[Part], data_type:Option<&str>, pos:usize) -> Result<String, Error> {
+    let mut fmt = String::new();
+    let mut args = String::new();
+    for part in parts {
+        match part {
+            Part::Text(t) => fmt.push_str( &t.replace('{', "{{").replace('}', "}}") ),
+            Part::Bind(binding) => {
+                match binding.spec {
+                    Some(spec) => write!(fmt, "{{:{spec}}}").unwrap(),
+                    None => fmt.push_str("{}")
+                }
+                write!(args, ", druid::LensExt::get(&{}, data)", binding.lens(data_type, pos)?).unwrap();
+            }
+        }
+    }
+    Ok( format!("|data, _env| format!({fmt:?}{args})") )
+}
@@ -0,0 +1,188 @@
+use std::fmt;
+
+use quick_xml::events::Event;
+use quick_xml::reader::Reader;
+
+use crate::Error;
+
+/// Human readable form of [`Error`] with the location in the xml source.
+///
+/// ```text
+/// line 12, col 5: split needs exactly two children (found 3)
+///   --> flex[fn=build_main] > split
+///    |
+/// 12 |     <split direction="row" flex="1">
+///    |     ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+/// ```
+#[derive(Debug,Clone)]
+pub struct Diagnostic {
+    /// Start byte offset of the span
+    pub start : usize,
+    /// End byte offset of the span (exclusive)
+    pub end : usize,
+    /// 1-based line number of `start`
+    pub line : usize,
+    /// 1-based column(in characters) of `start`
+    pub column : usize,
+    /// Element path from the root to the offending element. ex) `["flex[fn=build_main]", "split"]`
+    pub path : Vec<String>,
+    /// Error description without location
+    pub message : String,
+    /// Rendered source line with caret
+    pub snippet : String,
+    /// Original error
+    pub error : Error,
+}
+
+impl Diagnostic {
+    pub fn new(src:&str, error:Error) -> Self {
+        let start = error.error_at().min( src.len() );
+        let (start, end) = span_of(src, start, error.attribute_name());
+        let (line, column) = line_column(src, start);
+        let path = element_path(src, start);
+        let message = error.to_string();
+        let snippet = render_snippet(src, start, end, line, column);
+        Self { start, end, line, column, path, message, snippet, error }
+    }
+
+    /// `a > b > c` style element path
+    pub fn path_string(&self) -> String {
+        self.path.join(" > ")
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "line {}, col {}: {}", self.line, self.column, self.message)?;
+        if !self.path.is_empty() {
+            writeln!(f, "  --> {}", self.path_string())?;
+        }
+        write!(f, "{}", self.snippet)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+fn line_column(src:&str, pos:usize) -> (usize, usize) {
+    let before = &src[..pos];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map( |e| e + 1 ).unwrap_or(0);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+/// Expand error position to the whole start tag(or to the attribute when the error has one)
+fn span_of(src:&str, pos:usize, attr:Option<&str>) -> (usize, usize) {
+    let bytes = src.as_bytes();
+    if pos >= bytes.len() {
+        return (pos, pos)
+    }
+
+    if bytes[pos] != b'<' {
+        //text or unknown position : until the end of line
+        let end = src[pos..].find( |c| c == '\n' || c == '<' ).map( |e| pos + e ).unwrap_or(src.len());
+        return (pos, end.max(pos + 1).min(src.len()))
+    }
+
+    //find closing '>' of the tag while skipping quoted attribute values
+    let mut quote = None;
+    let mut end = src.len();
+    for (i, b) in bytes.iter().enumerate().skip(pos) {
+        match (quote, *b) {
+            (None, b'"') | (None, b'\'') => quote = Some(*b),
+            (Some(q), c) if q == c => quote = None,
+            (None, b'>') => { end = i + 1; break },
+            _ => ()
+        }
+    }
+
+    if let Some(attr) = attr {
+        let tag = &src[pos..end];
+        let mut from = 0;
+        while let Some(found) = tag[from..].find(attr) {
+            let at = from + found;
+            let prev_ok = at > 0 && tag.as_bytes()[at - 1].is_ascii_whitespace();
+            let rest = tag[at + attr.len()..].trim_start();
+            if prev_ok && rest.starts_with('=') {
+                //include value
+                let value_start = tag.len() - rest.len() + 1;
+                let value = tag[value_start..].trim_start();
+                let value_start = tag.len() - value.len();
+                let value_end = match value.as_bytes().first() {
+                    Some(q @ b'"') | Some(q @ b'\'') => value[1..].find(*q as char).map( |e| value_start + e + 2 ),
+                    _ => value.find( |c:char| c.is_whitespace() || c == '>' || c == '/' ).map( |e| value_start + e )
+                }.unwrap_or(tag.len());
+                return (pos + at, pos + value_end)
+            }
+            from = at + attr.len();
+        }
+    }
+    (pos, end)
+}
+
+/// Element names from the root to the element which starts at(or contains) `pos`
+fn element_path(src:&str, pos:usize) -> Vec<String> {
+    fn describe(e:&quick_xml::events::BytesStart) -> String {
+        let name = String::from_utf8_lossy(e.name().as_ref()).to_string();
+        for attr in e.attributes().flatten() {
+            let key = attr.key.as_ref();
+            if key == b"fn" || key == b"id" {
+                return format!("{}[{}={}]", name, String::from_utf8_lossy(key), String::from_utf8_lossy(&attr.value))
+            }
+        }
+        name
+    }
+
+    let mut reader = Reader::from_str(src);
+    reader.check_end_names(false);
+    let mut stack = vec![];
+    loop {
+        let event_pos = reader.buffer_position();
+        if event_pos > pos {
+            break
+        }
+        match reader.read_event() {
+            Ok(Event::Start(e)) => {
+                if e.name().as_ref() == b"style" {
+                    if reader.read_to_end(e.name()).is_err() {
+                        break
+                    }
+                    continue
+                }
+                stack.push( describe(&e) );
+                if event_pos == pos {
+                    break
+                }
+            },
+            Ok(Event::Empty(e)) => {
+                if event_pos == pos {
+                    stack.push( describe(&e) );
+                    break
+                }
+            },
+            Ok(Event::End(_)) => {
+                if reader.buffer_position() > pos {
+                    break
+                }
+                stack.pop();
+            },
+            Ok(Event::Eof) | Err(_) => break,
+            _ => ()
+        }
+    }
+    stack
+}
+
+fn render_snippet(src:&str, start:usize, end:usize, line:usize, column:usize) -> String {
+    let line_start = src[..start].rfind('\n').map( |e| e + 1 ).unwrap_or(0);
+    let line_end = src[start..].find('\n').map( |e| start + e ).unwrap_or(src.len());
+    let line_src = src[line_start..line_end].trim_end_matches('\r');
+    let line_no = line.to_string();
+    let gutter = " ".repeat( line_no.len() );
+
+    //tab is rendered as a tab so caret stay aligned
+    let indent:String = line_src.chars().take(column - 1).map( |c| if c == '\t' { '\t' } else { ' ' } ).collect();
+    let width = src[start..end.min(line_end).max(start)].chars().count().max(1);
+
+    format!("{gutter} |\n{line_no} | {line_src}\n{gutter} | {indent}{}\n", "^".repeat(width))
+}
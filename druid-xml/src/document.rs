@@ -0,0 +1,438 @@
+//! Owned xml document model.
+//!
+//! Both backends(code generation and dynamic) build widgets from this model and
+//! external tools can load, edit and save druid-xml files with it.
+//!
+//! ```
+//! use druid_xml::Document;
+//!
+//! let mut doc = Document::parse(r#"<flex fn="build_main" lens="()"><label>Hi</label></flex>"#).unwrap();
+//! doc.template_mut("build_main").unwrap().attributes.set("direction", "column");
+//! assert_eq!( doc.to_xml(), r#"<flex fn="build_main" lens="()" direction="column"><label>Hi</label></flex>"# );
+//! ```
+
+use std::fmt;
+
+use quick_xml::events::{Event, BytesStart};
+use quick_xml::reader::Reader;
+
+use crate::Error;
+
+/// Byte range in the source. Zero for nodes created programmatically.
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq)]
+pub struct Span {
+    pub start : usize,
+    pub end : usize,
+}
+
+impl Span {
+    pub fn new(start:usize, end:usize) -> Self {
+        Self { start, end }
+    }
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct Attribute {
+    pub name : String,
+    /// Unescaped value
+    pub value : String,
+    /// `name="value"`
+    pub span : Span,
+}
+
+/// Attributes in the source order
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct Attributes(Vec<Attribute>);
+
+impl Attributes {
+    pub fn new() -> Self {
+        Self(vec![])
+    }
+
+    pub fn get(&self, name:&str) -> Option<&str> {
+        self.find(name).map( |e| e.value.as_str() )
+    }
+
+    pub fn find(&self, name:&str) -> Option<&Attribute> {
+        self.0.iter().find( |e| e.name == name )
+    }
+
+    pub fn contains(&self, name:&str) -> bool {
+        self.find(name).is_some()
+    }
+
+    /// Replace the value keeping the position, or append at the end
+    pub fn set(&mut self, name:&str, value:&str) {
+        if let Some(attr) = self.0.iter_mut().find( |e| e.name == name ) {
+            attr.value = value.to_owned();
+        } else {
+            self.0.push( Attribute { name:name.to_owned(), value:value.to_owned(), span:Span::default() } );
+        }
+    }
+
+    pub fn remove(&mut self, name:&str) -> Option<Attribute> {
+        let idx = self.0.iter().position( |e| e.name == name )?;
+        Some( self.0.remove(idx) )
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Attribute> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl <'a> IntoIterator for &'a Attributes {
+    type Item = &'a Attribute;
+    type IntoIter = std::slice::Iter<'a, Attribute>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct Text {
+    pub content : String,
+    pub span : Span,
+}
+
+impl Text {
+    pub fn new(content:&str) -> Self {
+        Self { content:content.to_owned(), span:Span::default() }
+    }
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum Node {
+    Element(Element),
+    /// Unescaped text. The content of `<style>` is kept as it is
+    Text(Text),
+    /// `<![CDATA[content]]>`
+    CData(Text),
+    /// `<!--content-->`
+    Comment(Text),
+    /// `<?xml ... ?>`, `<!DOCTYPE ...>` and processing instructions. Written back as it is
+    Raw(Text),
+}
+
+impl Node {
+    pub fn span(&self) -> Span {
+        match self {
+            Node::Element(e) => e.span,
+            Node::Text(t) | Node::CData(t) | Node::Comment(t) | Node::Raw(t) => t.span
+        }
+    }
+
+    pub fn as_element(&self) -> Option<&Element> {
+        match self {
+            Node::Element(e) => Some(e),
+            _ => None
+        }
+    }
+
+    pub fn as_element_mut(&mut self) -> Option<&mut Element> {
+        match self {
+            Node::Element(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct Element {
+    pub name : String,
+    pub attributes : Attributes,
+    pub children : Vec<Node>,
+    /// From `<` of the start tag to `>` of the end tag
+    pub span : Span,
+    /// `<tag/>` form. Ignored when the element has children
+    pub self_closing : bool,
+}
+
+impl Element {
+    pub fn new(name:&str) -> Self {
+        Self { name:name.to_owned(), attributes:Attributes::new(), children:vec![], span:Span::default(), self_closing:true }
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.name
+    }
+
+    /// Child elements without text and comment
+    pub fn elements(&self) -> impl Iterator<Item=&Element> {
+        self.children.iter().filter_map( |e| e.as_element() )
+    }
+
+    pub fn elements_mut(&mut self) -> impl Iterator<Item=&mut Element> {
+        self.children.iter_mut().filter_map( |e| e.as_element_mut() )
+    }
+
+    /// Concatenated text and cdata of the direct children
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for child in self.children.iter() {
+            match child {
+                Node::Text(t) | Node::CData(t) => text.push_str(&t.content),
+                _ => ()
+            }
+        }
+        text
+    }
+
+    /// Find the element at `pos` from this element(inclusive)
+    pub fn element_at(&self, pos:usize) -> Option<&Element> {
+        if pos < self.span.start || pos >= self.span.end {
+            return None
+        }
+        self.elements().find_map( |e| e.element_at(pos) ).or( Some(self) )
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut w = String::new();
+        write_element(&mut w, self);
+        w
+    }
+}
+
+#[derive(Debug,Clone,Default,PartialEq)]
+pub struct Document {
+    pub nodes : Vec<Node>,
+}
+
+impl Document {
+    pub fn parse(xml:&str) -> Result<Self, Error> {
+        let mut reader = Reader::from_str(xml);
+        //close tag is checked ourself to report `InvalidCloseTag`
+        reader.check_end_names(false);
+        let mut nodes = vec![];
+        let mut stack:Vec<Element> = vec![];
+        loop {
+            let pos = reader.buffer_position();
+            let event = reader.read_event().map_err( |e| Error::XMLSyntaxError( (pos, e) ) )?;
+            let end = reader.buffer_position();
+            let span = Span::new(pos, end);
+            let node = match event {
+                Event::Start(e) => {
+                    let mut elem = new_element(xml, span, &e, false)?;
+                    if elem.name == "style" {
+                        //css is not xml. keep it as it is
+                        let css = reader.read_to_end(e.name()).map_err( |_| Error::InvalidCloseTag(end) )?;
+                        let content = xml[css.clone()].to_owned();
+                        elem.children.push( Node::Text( Text { content, span:Span::new(css.start, css.end) } ) );
+                        elem.span.end = reader.buffer_position();
+                        Node::Element(elem)
+                    } else {
+                        stack.push( elem );
+                        continue
+                    }
+                },
+                Event::End(e) => {
+                    let mut elem = stack.pop().ok_or( Error::CloseWithoutStart(pos) )?;
+                    if e.name().as_ref() != elem.name.as_bytes() {
+                        return Err(Error::InvalidCloseTag(pos))
+                    }
+                    elem.span.end = end;
+                    Node::Element(elem)
+                },
+                Event::Empty(e) => Node::Element( new_element(xml, span, &e, true)? ),
+                Event::Text(t) => {
+                    let content = t.unescape().map_err( |e| Error::XMLSyntaxError( (pos, e) ) )?.into_owned();
+                    Node::Text( Text { content, span } )
+                },
+                Event::CData(t) => Node::CData( Text { content:String::from_utf8_lossy(&t).into_owned(), span } ),
+                Event::Comment(t) => Node::Comment( Text { content:String::from_utf8_lossy(&t).into_owned(), span } ),
+                Event::Decl(_) | Event::PI(_) | Event::DocType(_) => Node::Raw( Text { content:xml[pos..end].to_owned(), span } ),
+                Event::Eof => {
+                    if let Some(elem) = stack.last() {
+                        return Err(Error::InvalidCloseTag(elem.span.start))
+                    }
+                    break
+                }
+            };
+            if let Some(parent) = stack.last_mut() {
+                parent.children.push( node );
+            } else {
+                nodes.push( node );
+            }
+        }
+        Ok( Self { nodes } )
+    }
+
+    /// Top level elements
+    pub fn elements(&self) -> impl Iterator<Item=&Element> {
+        self.nodes.iter().filter_map( |e| e.as_element() )
+    }
+
+    /// Top level elements or children of `<druid-xml>` document root
+    fn top_levels(&self) -> impl Iterator<Item=&Element> {
+        self.elements().flat_map( |e| {
+            let (root, single) = if e.name == "druid-xml" { (Some(e), None) } else { (None, Some(e)) };
+            root.into_iter().flat_map( |e| e.elements() ).chain( single )
+        })
+    }
+
+    /// Top level elements or children of `<druid-xml>` document root except `<style>`, `<import>` and `<model>`
+    pub fn templates(&self) -> impl Iterator<Item=&Element> {
+        self.top_levels().filter( |e| e.name != "style" && e.name != "import" && e.name != "model" )
+    }
+
+    /// `<model name="...">` elements
+    pub fn models(&self) -> impl Iterator<Item=&Element> {
+        self.top_levels().filter( |e| e.name == "model" )
+    }
+
+    /// `<import src="..."/>` elements
+    pub fn imports(&self) -> impl Iterator<Item=&Element> {
+        self.top_levels().filter( |e| e.name == "import" )
+    }
+
+    /// Template which has `fn` name
+    pub fn template(&self, fn_name:&str) -> Option<&Element> {
+        self.templates().find( |e| e.attributes.get("fn") == Some(fn_name) )
+    }
+
+    pub fn template_mut(&mut self, fn_name:&str) -> Option<&mut Element> {
+        fn find<'a>(nodes:&'a mut [Node], fn_name:&str) -> Option<&'a mut Element> {
+            for node in nodes.iter_mut() {
+                if let Node::Element(e) = node {
+                    if e.name == "druid-xml" {
+                        if let Some(found) = find(&mut e.children, fn_name) {
+                            return Some(found)
+                        }
+                    } else if e.attributes.get("fn") == Some(fn_name) {
+                        return Some(e)
+                    }
+                }
+            }
+            None
+        }
+        find(&mut self.nodes, fn_name)
+    }
+
+    /// Innermost element at `pos`
+    pub fn element_at(&self, pos:usize) -> Option<&Element> {
+        self.elements().find_map( |e| e.element_at(pos) )
+    }
+
+    pub fn to_xml(&self) -> String {
+        let mut w = String::new();
+        for node in self.nodes.iter() {
+            write_node(&mut w, node, false);
+        }
+        w
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str( &self.to_xml() )
+    }
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str( &self.to_xml() )
+    }
+}
+
+impl std::str::FromStr for Document {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+fn new_element(xml:&str, span:Span, e:&BytesStart, self_closing:bool) -> Result<Element, Error> {
+    let name = String::from_utf8_lossy( e.name().as_ref() ).into_owned();
+    let tag_src = &xml[span.start .. span.end];
+    let mut attributes = Attributes::new();
+    let mut cursor = 1 + name.len();
+    for attr in e.attributes() {
+        let attr = attr.map_err( |e| Error::XMLSyntaxError( (span.start, e.into()) ) )?;
+        let value = attr.unescape_value().map_err( |e| Error::XMLSyntaxError( (span.start, e) ) )?.into_owned();
+        let attr_name = String::from_utf8_lossy( attr.key.as_ref() ).into_owned();
+        let (start, end) = attribute_range(tag_src, cursor, &attr_name);
+        cursor = end;
+        attributes.0.push( Attribute { name:attr_name, value, span:Span::new(span.start + start, span.start + end) } );
+    }
+    Ok( Element { name, attributes, children:vec![], span, self_closing } )
+}
+
+/// `name="value"` range in the start tag source from `from`
+fn attribute_range(tag_src:&str, from:usize, name:&str) -> (usize, usize) {
+    let bytes = tag_src.as_bytes();
+    let mut at = from;
+    while let Some(found) = tag_src.get(at..).and_then( |e| e.find(name) ) {
+        let start = at + found;
+        at = start + name.len();
+        if start == 0 || !bytes[start - 1].is_ascii_whitespace() {
+            continue
+        }
+        let rest = tag_src[at..].trim_start();
+        if !rest.starts_with('=') {
+            continue
+        }
+        let value = rest[1..].trim_start();
+        let value_start = tag_src.len() - value.len();
+        let end = match value.as_bytes().first() {
+            Some(q @ b'"') | Some(q @ b'\'') => value[1..].find(*q as char).map( |e| value_start + e + 2 ),
+            _ => value.find( |c:char| c.is_whitespace() || c == '>' || c == '/' ).map( |e| value_start + e )
+        }.unwrap_or(tag_src.len());
+        return (start, end)
+    }
+    (from, from)
+}
+
+fn escape(w:&mut String, v:&str, attr:bool) {
+    for c in v.chars() {
+        match c {
+            '&' => w.push_str("&amp;"),
+            '<' => w.push_str("&lt;"),
+            '>' if !attr => w.push_str("&gt;"),
+            '"' if attr => w.push_str("&quot;"),
+            _ => w.push(c)
+        }
+    }
+}
+
+fn write_node(w:&mut String, node:&Node, raw_text:bool) {
+    match node {
+        Node::Element(e) => write_element(w, e),
+        Node::Text(t) => if raw_text { w.push_str(&t.content) } else { escape(w, &t.content, false) },
+        Node::CData(t) => { w.push_str("<![CDATA["); w.push_str(&t.content); w.push_str("]]>"); },
+        Node::Comment(t) => { w.push_str("<!--"); w.push_str(&t.content); w.push_str("-->"); },
+        Node::Raw(t) => w.push_str(&t.content),
+    }
+}
+
+fn write_element(w:&mut String, e:&Element) {
+    w.push('<');
+    w.push_str(&e.name);
+    for attr in e.attributes.iter() {
+        w.push(' ');
+        w.push_str(&attr.name);
+        w.push_str("=\"");
+        escape(w, &attr.value, true);
+        w.push('"');
+    }
+    if e.children.is_empty() && e.self_closing {
+        w.push_str("/>");
+        return
+    }
+    w.push('>');
+    let raw_text = e.name == "style";
+    for child in e.children.iter() {
+        write_node(w, child, raw_text);
+    }
+    w.push_str("</");
+    w.push_str(&e.name);
+    w.push('>');
+}
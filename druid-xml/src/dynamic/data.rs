@@ -0,0 +1,192 @@
+//! JSON data binding of the dynamic widgets.
+//!
+//! The `lens` attribute is the JSON path(`crate::lens::LensPath`) and the type name of the root is ignored.
+//! `App::user.name` and `user.name` are `data["user"]["name"]`.
+
+use std::sync::Arc;
+
+use druid::{Data, Lens};
+use serde_json::Value;
+
+use crate::bind::{FormatSpec, Part};
+use crate::lens::LensPath;
+use crate::qwidget::value::JSValue;
+
+/// Widget data from/to the JSON value
+pub(crate) trait JSData : Data {
+    fn from_json(v:&Value) -> Option<Self>;
+    fn to_json(&self) -> Value;
+}
+
+impl JSData for bool {
+    fn from_json(v:&Value) -> Option<Self> {
+        v.as_bool()
+    }
+
+    fn to_json(&self) -> Value {
+        Value::Bool(*self)
+    }
+}
+
+impl JSData for f64 {
+    fn from_json(v:&Value) -> Option<Self> {
+        v.as_f64().or_else( || v.as_str().and_then( |s| s.trim().parse().ok() ) )
+    }
+
+    /// Integral value is the integer(`3` not `3.0`)
+    fn to_json(&self) -> Value {
+        if self.fract() == 0. && self.abs() < i64::MAX as f64 {
+            Value::from(*self as i64)
+        } else {
+            Value::from(*self)
+        }
+    }
+}
+
+impl JSData for String {
+    fn from_json(v:&Value) -> Option<Self> {
+        match v {
+            Value::String(s) => Some(s.clone()),
+            Value::Null => None,
+            v => Some(v.to_string())
+        }
+    }
+
+    /// Text of the number or bool field is parsed by `LensPath::set_json`
+    fn to_json(&self) -> Value {
+        Value::String(self.clone())
+    }
+}
+
+impl JSData for JSValue {
+    fn from_json(v:&Value) -> Option<Self> {
+        Some( JSValue::new(v.clone()) )
+    }
+
+    fn to_json(&self) -> Value {
+        (**self).clone()
+    }
+}
+
+/// Items of the `list`
+impl JSData for Arc<Vec<JSValue>> {
+    fn from_json(v:&Value) -> Option<Self> {
+        v.as_array().map( |items| Arc::new( items.iter().cloned().map( JSValue::new ).collect() ) )
+    }
+
+    fn to_json(&self) -> Value {
+        Value::Array( self.iter().map( |e| (**e).clone() ).collect() )
+    }
+}
+
+/// Lens of the JSON path. Without the path, the data itself.
+/// Missing or mismatched value is the `fallback` and writing it creates the path
+pub(crate) struct JSLens<U> {
+    path : Option<LensPath>,
+    fallback : U,
+}
+
+impl <U:JSData> JSLens<U> {
+    pub fn new(path:Option<LensPath>, fallback:U) -> Self {
+        Self { path, fallback }
+    }
+
+    fn get(&self, data:&JSValue) -> U {
+        let v = match self.path.as_ref() {
+            Some(path) => path.get_json(data),
+            None => Some( (**data).clone() )
+        };
+        v.and_then( |v| U::from_json(&v) ).unwrap_or_else( || self.fallback.clone() )
+    }
+}
+
+impl <U:JSData> Lens<JSValue, U> for JSLens<U> {
+    fn with<V, F: FnOnce(&U) -> V>(&self, data: &JSValue, f: F) -> V {
+        f( &self.get(data) )
+    }
+
+    fn with_mut<V, F: FnOnce(&mut U) -> V>(&self, data: &mut JSValue, f: F) -> V {
+        let old = self.get(data);
+        let mut v = old.clone();
+        let r = f(&mut v);
+        if !old.same(&v) {
+            match self.path.as_ref() {
+                Some(path) => { path.set_json(data, v.to_json()); },
+                None => **data = v.to_json()
+            }
+        }
+        r
+    }
+}
+
+/// Condition of the `if`
+pub(crate) fn truthy(v:&Value) -> bool {
+    match v {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().map( |n| n != 0. ).unwrap_or(false),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+/// Owned [`Part`] for the `Label::dynamic` closure
+pub(crate) enum TextPart {
+    Text(String),
+    /// (path, format spec, source text)
+    Bind(LensPath, Option<FormatSpec>, String),
+}
+
+pub(crate) fn text_parts(parts:&[Part]) -> Vec<TextPart> {
+    parts.iter().map( |part| match part {
+        Part::Text(t) => TextPart::Text(t.clone()),
+        Part::Bind(b) => match LensPath::parse(b.path) {
+            Some(path) => {
+                let source = match b.spec {
+                    Some(spec) => format!("{{{}:{spec}}}", b.path),
+                    None => format!("{{{}}}", b.path)
+                };
+                TextPart::Bind(path, b.spec.and_then( FormatSpec::parse ), source)
+            },
+            None => TextPart::Text(b.path.to_owned())
+        }
+    }).collect()
+}
+
+/// Text of the data. Missing value is the source text(`{App::count}`) if `preview`, otherwise empty.
+/// Format spec is the same as `format!`. The precision is the digits of the float and the max length of the text
+pub(crate) fn format(parts:&[TextPart], data:&JSValue, preview:bool) -> String {
+    let mut text = String::new();
+    for part in parts {
+        match part {
+            TextPart::Text(t) => text.push_str(t),
+            TextPart::Bind(path, spec, source) => {
+                let precision = spec.as_ref().and_then( |e| e.precision );
+                let (value, numeric) = match path.get_json(data) {
+                    Some(Value::Number(n)) => match (precision, n.is_f64()) {
+                        (Some(precision), true) => (format!("{:.precision$}", n.as_f64().unwrap_or(0.)), true),
+                        _ => (n.to_string(), true)
+                    },
+                    Some(Value::Null) | None => {
+                        if preview {
+                            text.push_str(source);
+                        }
+                        continue
+                    },
+                    Some(Value::String(s)) => (s, false),
+                    Some(v) => (v.to_string(), false)
+                };
+                let value = match precision {
+                    Some(precision) if !numeric => value.chars().take(precision).collect(),
+                    _ => value
+                };
+                match spec {
+                    Some(spec) => text.push_str( &spec.pad(value, numeric) ),
+                    None => text.push_str(&value)
+                }
+            }
+        }
+    }
+    text
+}
@@ -0,0 +1,167 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use druid::widget::{Label, LineBreaking};
+use druid::{BoxConstraints, Color, Data, Env, Event, EventCtx, FontDescriptor, FontFamily, LayoutCtx, LifeCycle, LifeCycleCtx,
+    PaintCtx, Point, Rect, RenderContext, Size, TimerToken, UpdateCtx, Widget, WidgetPod};
+
+use super::{JSValue, StateHandle, WidgetRegistry};
+
+/// Default polling interval of the modified time
+const INTERVAL:Duration = Duration::from_millis(500);
+
+/// Rebuild the widget when the xml file or the imported file is modified.
+/// The last good widget is kept on error and the error is shown at the bottom.
+/// `<import src="..."/>` is relative to the file. The data of the parent is ignored
+///
+/// ```ignore
+/// let window = WindowDesc::new( HotReload::new("ui/main.xml") );
+/// ```
+pub struct HotReload {
+    path : PathBuf,
+    interval : Duration,
+    registry : WidgetRegistry<JSValue>,
+    timer : TimerToken,
+    /// The file and the imported files with the modified time. The imports of the last good widget on error
+    watched : Vec<(PathBuf, Option<SystemTime>)>,
+    child : Option<WidgetPod<(), Box<dyn Widget<()>>>>,
+    values : Option<StateHandle>,
+    error : Option<WidgetPod<(), Label<()>>>,
+}
+
+impl HotReload {
+    pub fn new(path:impl Into<PathBuf>) -> Self {
+        Self { path:path.into(), interval:INTERVAL, registry:WidgetRegistry::<JSValue>::new(), timer:TimerToken::INVALID, watched:vec![], child:None, values:None, error:None }
+    }
+
+    /// Polling interval of the modified time
+    pub fn interval(mut self, interval:Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Native widgets of the xml
+    pub fn registry(mut self, registry:WidgetRegistry<JSValue>) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// Input values of the current widget(`generate_widget`)
+    pub fn values(&self) -> Option<&StateHandle> {
+        self.values.as_ref()
+    }
+
+    fn reload(&mut self, ctx:&mut EventCtx) {
+        match load(&self.path, &self.registry) {
+            Ok( (widget, values, imports) ) => {
+                self.child = Some( WidgetPod::new(widget) );
+                self.values = Some(values);
+                self.error = None;
+                self.watched = std::iter::once(self.path.clone()).chain(imports).map( |e| { let modified = modified(&e); (e, modified) } ).collect();
+            },
+            Err(e) => {
+                let label = Label::new(e)
+                    .with_font( FontDescriptor::new(FontFamily::MONOSPACE) )
+                    .with_text_color( Color::rgb8(0xff, 0x6b, 0x6b) )
+                    .with_line_break_mode( LineBreaking::WordWrap );
+                self.error = Some( WidgetPod::new(label) );
+            }
+        }
+        if self.watched.is_empty() {
+            self.watched.push( (self.path.clone(), modified(&self.path)) );
+        }
+        ctx.children_changed();
+    }
+
+    /// Any of the watched files is modified. The modified time is updated
+    fn poll(&mut self) -> bool {
+        let mut changed = false;
+        for (path, time) in self.watched.iter_mut() {
+            let modified = modified(path);
+            if modified != *time {
+                *time = modified;
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// Build the widget of the file with the resolved paths of the imported files. The error is the text with the path
+pub(crate) fn load(path:&Path, registry:&WidgetRegistry<JSValue>) -> Result<(Box<dyn Widget<()>>, StateHandle, Vec<PathBuf>), String> {
+    let src = std::fs::read_to_string(path).map_err( |e| format!("{}: {e}", path.display()) )?;
+    let dir = path.parent().unwrap_or( Path::new(".") );
+    registry.generate_widget_with_imports(&src, dir).map_err( |e| format!("{}: {e}", path.display()) )
+}
+
+fn modified(path:&Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then( |e| e.modified() ).ok()
+}
+
+impl <T:Data> Widget<T> for HotReload {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, _data: &mut T, env: &Env) {
+        match event {
+            Event::WindowConnected => {
+                self.reload(ctx);
+                self.timer = ctx.request_timer(self.interval);
+            },
+            Event::Timer(id) if *id == self.timer => {
+                if self.poll() {
+                    self.reload(ctx);
+                }
+                self.timer = ctx.request_timer(self.interval);
+                return
+            },
+            _ => ()
+        }
+        if let Some(child) = self.child.as_mut() {
+            child.event(ctx, event, &mut (), env);
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, _data: &T, env: &Env) {
+        if let Some(child) = self.child.as_mut() {
+            child.lifecycle(ctx, event, &(), env);
+        }
+        if let Some(error) = self.error.as_mut() {
+            error.lifecycle(ctx, event, &(), env);
+        }
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, _data: &T, env: &Env) {
+        if let Some(child) = self.child.as_mut() {
+            child.update(ctx, &(), env);
+        }
+        if let Some(error) = self.error.as_mut() {
+            error.update(ctx, &(), env);
+        }
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, _data: &T, env: &Env) -> Size {
+        let size = match self.child.as_mut() {
+            Some(child) => {
+                let size = child.layout(ctx, bc, &(), env);
+                child.set_origin(ctx, Point::ORIGIN);
+                size
+            },
+            None => bc.constrain( (100.0, 100.0) )
+        };
+        if let Some(error) = self.error.as_mut() {
+            let error_bc = BoxConstraints::new( Size::ZERO, Size::new(size.width, f64::INFINITY) );
+            let error_size = error.layout(ctx, &error_bc, &(), env);
+            error.set_origin(ctx, Point::new(0., (size.height - error_size.height).max(0.)));
+        }
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, _data: &T, env: &Env) {
+        if let Some(child) = self.child.as_mut() {
+            child.paint(ctx, &(), env);
+        }
+        if let Some(error) = self.error.as_mut() {
+            let rect = error.layout_rect();
+            ctx.fill( Rect::new(0., rect.y0, ctx.size().width, rect.y1), &Color::rgba8(0, 0, 0, 0xd0) );
+            error.paint(ctx, &(), env);
+        }
+    }
+}
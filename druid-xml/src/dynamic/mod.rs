@@ -1,18 +1,37 @@
-use std::borrow::Cow;
-use std::collections::HashMap;
-
-use druid::kurbo::Line;
-use druid::{Widget,WidgetExt,TextAlignment,Color};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use druid::{Widget,WidgetExt,FontWeight,FontStyle};
+use druid::text::{RichText, Attribute};
 use druid::widget::*;
-use quick_xml::{Reader, events::Event};
-use simplecss::{StyleSheet, Declaration, DeclarationTokenizer};
+use simplecss::StyleSheet;
+
+use crate::rich_text::{RichSpans, InlineStyle};
+use crate::{Error, Diagnostic, Document, Templates, schema, import, model};
+use crate::template::Template;
+use crate::bind;
+use crate::lens::LensPath;
+use crate::resource::{ResourceResolver, FileResolver};
+use crate::qwidget::drawable::DrawableStack;
+use crate::spec::{Context, FlexChild, Input, Kind, LabelText, StyleCache, Target, WidgetSpec};
+use crate::widget::{EventHandler, InvalidState, NumberFormatter, LocalState, LocalStateHandle};
+
+pub use crate::qwidget::value::JSValue;
+use data::{JSData, JSLens};
+
+/// Item count of the `list` preview
+const PREVIEW_LIST_ITEMS:usize = 3;
 
-use crate::simple_style::{AnimationState, Pseudo, BorderStyle};
-use crate::writer::{ElementQueryWrap, PseudoOrderTrapQueryWrap};
-use crate::{Element, Error, AttributeGetter, DummyLens, AttributesWrapper};
+pub(crate) mod color;
+pub(crate) mod data;
+pub(crate) mod hot_reload;
+pub(crate) mod registry;
 
-mod color;
-pub(crate) mod ex_custom_widget;
+pub use hot_reload::HotReload;
+pub use registry::{WidgetRegistry, WidgetFactory, CommandFactory};
 
 struct LazyWrapperWidget<W:Widget<D>,D> {
     child : Option<W>,
@@ -59,59 +78,136 @@ impl <D, W:Widget<D>> Widget<D> for LazyWrapperWidget<W,D> {
     }
 }
 
-pub fn generate_widget(xml:&str) -> Result< Box<dyn Widget<()>>, Error > {
-	let mut style = StyleSheet::new();
-	let mut reader = Reader::from_str(xml);
-    let mut elem_map = HashMap::new();
-    let mut expected_main_widget = None;
-    let mut last_widget = None;
-	loop {
-		let pos = reader.buffer_position();
-		match reader.read_event() {
-			Ok(Event::Start(e)) => {
-				match e.name().as_ref() {
-					b"style" => {
-						let start_pos = reader.buffer_position();
-						match reader.read_to_end(e.name()) {
-							Ok(span) => {
-								let css_impl = &xml[span];
-								style.parse_more( css_impl );
-							},
-							_ => return Err(Error::InvalidCloseTag(start_pos))
-						}
-					},
-					_ => {						
-						if let Some(elem) = crate::parse_element(pos, Some(Event::Start(e)), &mut reader )? {
-                            let fnname = elem.attributes( None ).get_as_result::<String>("fn")?;
-                            last_widget = Some(fnname.clone());
-                            if fnname.find("main").is_some() {
-                                expected_main_widget = Some(fnname);
-                            }
-                            elem_map.insert( elem.attributes( None ).get_as_result::<String>("fn")?, elem);
-						} else {
-							break
-						}
-					}
-				}
-			},
-
-			Err(e) => return Err(Error::XMLSyntaxError( (reader.buffer_position(),e) )),
-			// exits the loop when reaching end of file
-			Ok(Event::Eof) => break,
-			Ok(Event::Comment(_)) => (),
-			Ok(Event::CData(_)) => (),
-			Ok(Event::Empty(_)) => (),
-			Ok(Event::Decl(_)) => (),
-			Ok(Event::PI(_)) => (),
-			Ok(Event::DocType(_)) => (),
-			Ok(Event::Text(_)) => (), //ignore text from root node
-			Ok(Event::End(_)) => (), //return Err(Error::CloseWithoutStart(reader.buffer_position())),
-		}
-	}
-
-    let widget = if let Some(main) = expected_main_widget.and( last_widget ) {
-        if let Some(elem ) = elem_map.get(&main) {
-            build_widget(None, &elem_map, &[], &elem, &style)?
+/// Values of the inputs by the element `id`
+#[derive(Clone,Default)]
+pub struct StateHandle {
+    slots : Rc<RefCell<HashMap<String, Rc<dyn Slot>>>>,
+}
+
+trait Slot {
+    fn get(&self) -> serde_json::Value;
+    fn set(&self, v:serde_json::Value) -> bool;
+}
+
+impl <T:JSData> Slot for LocalStateHandle<T> {
+    fn get(&self) -> serde_json::Value {
+        LocalStateHandle::get(self).to_json()
+    }
+
+    fn set(&self, v:serde_json::Value) -> bool {
+        T::from_json(&v).map( |v| LocalStateHandle::set(self, v) ).is_some()
+    }
+}
+
+impl StateHandle {
+    fn insert(&self, id:String, slot:Rc<dyn Slot>) {
+        self.slots.borrow_mut().insert(id, slot);
+    }
+
+    /// `#pwd` or `pwd`
+    pub fn value(&self, id:&str) -> Option<serde_json::Value> {
+        let slots = self.slots.borrow();
+        slots.get( id.trim_start_matches('#') ).map( |e| e.get() )
+    }
+
+    /// `false` if there is no input or the value is not the type of the input.
+    /// Applied on the next event of the widget(or `druid_xml::widget::local_state::REFRESH` command)
+    pub fn set_value(&self, id:&str, v:serde_json::Value) -> bool {
+        let slots = self.slots.borrow();
+        slots.get( id.trim_start_matches('#') ).map( |e| e.set(v) ).unwrap_or(false)
+    }
+
+    /// Element ids of the inputs
+    pub fn ids(&self) -> Vec<String> {
+        self.slots.borrow().keys().cloned().collect()
+    }
+}
+
+/// Build widget from xml at runtime.
+/// The error is reported as [`Diagnostic`] which has line, column and source snippet.
+/// `<import src="..."/>` is relative to the current directory. The native widgets are [`WidgetRegistry::generate_widget`]
+///
+/// Preview without data. Each input owns its value([`LocalState`]) which is available by the element `id`.
+/// Missing value of the binding is shown as it is(`{App::count}`)
+///
+/// ```ignore
+/// let (widget, ui) = generate_widget(xml)?;
+/// ui.value("#pwd");
+/// ```
+pub fn generate_widget(xml:&str) -> Result< (Box<dyn Widget<()>>, StateHandle), Diagnostic > {
+    generate_widget_with_dir(xml, Path::new("."))
+}
+
+/// Same as [`generate_widget`] but `<import src="..."/>` is relative to `dir`
+pub fn generate_widget_with_dir(xml:&str, dir:&Path) -> Result< (Box<dyn Widget<()>>, StateHandle), Diagnostic > {
+    WidgetRegistry::<JSValue>::new().generate_widget_with_dir(xml, dir)
+}
+
+/// Build widget bound to the JSON data.
+/// Each `lens` is the JSON path(`App::user.name` is `data["user"]["name"]`) and the widgets read and write the data
+pub fn generate_widget_with_data(xml:&str) -> Result< Box<dyn Widget<JSValue>>, Diagnostic > {
+    generate_widget_with_data_dir(xml, Path::new("."))
+}
+
+/// Same as [`generate_widget_with_data`] but `<import src="..."/>` is relative to `dir`
+pub fn generate_widget_with_data_dir(xml:&str, dir:&Path) -> Result< Box<dyn Widget<JSValue>>, Diagnostic > {
+    WidgetRegistry::<JSValue>::new().generate_widget_with_data_dir(xml, dir)
+}
+
+/// Owned templates and styles. The `list` item is built later
+struct Factory {
+    templates : HashMap<String,Template>,
+    /// Filled when the main widget is built. Every template of the `list` item is cascaded then
+    styles : StyleCache,
+    registry : WidgetRegistry<JSValue>,
+    /// Element ids of the `target="#id"` and the templates which have more than one instance
+    targets : HashSet<String>,
+    reused : HashSet<String>,
+    /// `src` of the image
+    resources : Rc<dyn ResourceResolver<Output=Vec<u8>>>,
+    /// No data(`Some`). Every input owns its value and placeholder items of the list
+    preview : Option<StateHandle>,
+}
+
+/// Widget of the `list` item template. Root of the item data.
+/// The style sheet is not parsed again, the styles are cached already
+fn build_item(factory:&Rc<Factory>, name:&str) -> Result<Box<dyn Widget<JSValue>>, Error> {
+    let natives = factory.registry.tags();
+    let ctx = Context { templates:&factory.templates, css:&StyleSheet::new(), natives:Some(natives.as_slice()), cache:Some(&factory.styles) };
+    let spec = WidgetSpec::root(&ctx, &factory.templates[name])?;
+    build_widget(factory, spec)
+}
+
+/// The widget and the resolved paths of the imported files
+fn generate(xml:&str, dir:&Path, registry:&WidgetRegistry<JSValue>, preview:Option<StateHandle>) -> Result< (Box<dyn Widget<JSValue>>, Vec<PathBuf>), Error > {
+    let doc = Document::parse(xml)?;
+    let natives = registry.tags();
+    let (imports, import_paths) = import::load(&doc, dir, Some(natives.as_slice()))?;
+    if let Some(e) = schema::validate(&doc, &imports, Some(natives.as_slice())).into_iter().next() {
+        return Err(e)
+    }
+    let models = model::collect( imports.iter().chain( std::iter::once(&doc) ) )?;
+    model::check(&doc, &models)?;
+    let styles = Templates::styles(&doc, &imports);
+    let mut templates = Templates::collect(&doc, &imports, &styles)?;
+
+    //exported templates are all entry points. prefer "main" and otherwise the first one
+    let resources:Rc<dyn ResourceResolver<Output=Vec<u8>>> = match registry.resources() {
+        Some(resources) => resources,
+        None => Rc::new( FileResolver::new(dir) )
+    };
+    let main = templates.main().or( templates.exports.first().map( |e| e.as_str() ) ).map( |e| e.to_owned() );
+    let mut targets = HashSet::new();
+    for template in templates.elem_map.values() {
+        crate::writer::collect_targets(&template.elem, &mut targets);
+    }
+    let reused = crate::writer::reused_templates(&templates.elem_map);
+    let factory = Rc::new( Factory { templates:std::mem::take(&mut templates.elem_map), styles:StyleCache::default(), registry:registry.clone(), targets, reused, resources, preview } );
+    let widget = if let Some(main) = main {
+        if let Some(template) = factory.templates.get(&main) {
+            let natives = factory.registry.tags();
+            let ctx = Context { templates:&factory.templates, css:&templates.style, natives:Some(natives.as_slice()), cache:Some(&factory.styles) };
+            build_widget(&factory, WidgetSpec::root(&ctx, template)?)?
         } else {
             Label::new(format!("Can't find main widget : {}", main) ).boxed()
         }
@@ -120,826 +216,370 @@ pub fn generate_widget(xml:&str) -> Result< Box<dyn Widget<()>>, Error > {
     };
     
 
-	Ok( widget )
+	Ok( (widget, import_paths) )
 }
 
-fn build_widget<'a>(parameter:Option<&AttributesWrapper<'a>>,parsed_map:&HashMap<String,Element>, parent_stack:&[&Element], elem:&Element, css:&StyleSheet) -> Result<Box<dyn Widget<()>>,Error> {
-    let depth = parent_stack.len();
-    let elem_query = ElementQueryWrap { parent_stack, elem };
-
-    //just simplify ordered iteration without vec allocation (#id query first)
-    //Reference : https://www.w3.org/TR/selectors/#specificity
-    let css_iter = 
-    css.rules.iter()
-
-    //id first
-    .filter( |e| { let spec = e.selector.specificity(); spec[0] > 0 && e.selector.matches(&elem_query) } )
-
-    //class 
-    .chain(
-        css.rules.iter()
-        .filter( |e| { let spec = e.selector.specificity(); spec[0] == 0 && spec[1] > 0 && e.selector.matches(&elem_query) } ) )
-
-    //global types
-    .chain(
-        css.rules.iter()
-        .filter( |e| { let spec = e.selector.specificity(); spec[0] == 0 && spec[1] == 0 && spec[2] > 0 && e.selector.matches(&elem_query) } ) )
-    .map( |e| &e.declarations );
-
-    let tag_qname = elem.tag();
-    let tag = String::from_utf8_lossy(tag_qname.as_ref());
-    let mut tag_wrap:&str = &tag;
-
-    let attrs = elem.attributes( parameter );
-    let elem_style = attrs.get(b"style").unwrap_or( Cow::Borrowed(b"") );
-    let elem_style_str = &String::from_utf8_lossy(&elem_style) as &str;
-    let specific_style:Vec<Declaration> = DeclarationTokenizer::from( elem_style_str ).collect();
-
-    macro_rules! get_style {
-        ($name:tt) => {
-            specific_style.iter().find( |e| e.name == $name ).map( |e| e.value ).or_else( || {
-                for global_style in css_iter.clone() {
-                    let find = global_style.iter().find( |e| e.name == $name ).map( |e| e.value );
-                    if find.is_some() {
-                        return find
-                    }
-                }
-                None
-            })
-        }
-    }
+fn build_widget(factory:&Rc<Factory>, spec:WidgetSpec) -> Result<Box<dyn Widget<JSValue>>,Error> {
+    let pos = spec.elem.span.start;
+    let styled = spec.style.is_some();
+    let lens = spec.lens.is_some();
+    let id = spec.id.as_deref();
 
-    macro_rules! style {
-        ( $widget:ident, "width-height" ) => {
-            if let (Some(mut width), Some(mut height)) = (get_style!("width") , get_style!("height")) {
-                width = &width[ .. width.find("px").unwrap_or(width.len())];
-                height = &height[ .. height.find("px").unwrap_or(height.len())];
-                if let (Ok(width),Ok(height)) = ( width.parse::<f64>(), height.parse::<f64>() ) {
-                    $widget.fix_size(width, height).boxed()
-                } else {
-                    $widget
+    //`:invalid` state of the `ValueTextBox`
+    let mut invalid = None;
+
+    let mut child:Box<dyn Widget<JSValue>> = match spec.kind {
+        Kind::Flex { column, must_fill, cross, main, children } => {
+            let mut flex = if column { Flex::column() } else { Flex::row() };
+            if let Some(v) = must_fill {
+                flex = flex.must_fill_main_axis(v);
+            }
+            if let Some(v) = cross {
+                flex.set_cross_axis_alignment(v);
+            }
+            if let Some(v) = main {
+                flex.set_main_axis_alignment(v);
+            }
+            for child in children {
+                match child {
+                    FlexChild::Spacer(Some(v)) => flex.add_flex_spacer(v),
+                    FlexChild::Spacer(None) => flex.add_default_spacer(),
+                    FlexChild::Child(child, Some(v)) => flex.add_flex_child(build_widget(factory, child)?, v),
+                    FlexChild::Child(child, None) => flex.add_child(build_widget(factory, child)?),
                 }
-            } else {
-                $widget
             }
-        };
-        ( $widget:ident, "width" ) => {
-            if let Some(mut v) = get_style!("width") {
-                if v.ends_with("px") {
-                    v = &v[ .. v.len()-2];
+            flex.boxed()
+        },
+
+        //inline markup : `<label>Hello <b>world</b></label>`
+        Kind::Label { text:LabelText::Rich(rich), button, line_break, align } => {
+            let rich = to_rich_text(&rich);
+            if styled || button {
+                let mut label = crate::widget::DXLabel::rich(rich);
+                if let Some(align) = align {
+                    label.set_text_alignment(align);
+                }
+                if let Some(mode) = line_break {
+                    label.set_line_break_mode(mode);
                 }
-                if let Ok(v) = v.parse::<f64>() {
-                    $widget.fix_width(v).boxed()
+                if button {
+                    crate::widget::DXButton::from_label(label).boxed()
                 } else {
-                    $widget
+                    label.boxed()
                 }
             } else {
-                $widget
-            }
-        };
-        ( $widget:ident, "height" ) => {
-            if let Some(mut v) = get_style!("height") {
-                if v.ends_with("px") {
-                    v = &v[ .. v.len()-2];
+                let mut label = RawLabel::<RichText>::new();
+                if let Some(align) = align {
+                    label.set_text_alignment(align);
                 }
-                if let Ok(v) = v.parse::<f64>() {
-                    $widget.fix_height(v).boxed()
-                } else {
-                    $widget
+                if let Some(mode) = line_break {
+                    label.set_line_break_mode(mode);
                 }
-            } else {
-                $widget
-            }
-        };
-        ( $widget:ident, "background-color" ) => {
-            if let Some(background) = get_style!("background-color") {
-                let col = color::to_color( background, Some(Color::rgb8(255,255,255)) );
-                $widget.background( col ).boxed()
-            } else {
-                $widget
+                label.lens( druid::lens::Constant(rich) ).boxed()
             }
-        };
-        ( $widget:ident, "padding" ) => {
-            if let Some(v) = get_style!("padding") {
-                let mut splits = v.split_whitespace().map( |s| &s[..s.find("px").unwrap_or(s.len())] );
-                let count = splits.clone().count();
-                if count == 1 {
-                    $widget.padding( splits.next().unwrap_or("0").parse::<f64>().unwrap_or(0f64) ).boxed()
-                } else if count == 2 || count == 4 {
-                    $widget.padding( (splits.next().unwrap_or("0").parse::<f64>().unwrap_or(0f64), splits.next().unwrap_or("0").parse::<f64>().unwrap_or(0f64)) ).boxed()
-                } else if count ==4 {
-                    $widget.padding( (splits.next().unwrap_or("0").parse::<f64>().unwrap_or(0f64), splits.next().unwrap_or("0").parse::<f64>().unwrap_or(0f64), splits.next().unwrap_or("0").parse::<f64>().unwrap_or(0f64), splits.next().unwrap_or("0").parse::<f64>().unwrap_or(0f64)) ).boxed()
+        },
+
+        Kind::Label { text, button, line_break, align } => {
+            //`{AppState::count}` of the JSON data
+            let parts = match &text {
+                LabelText::Bind(source) => bind::parse(source).map( |parts| data::text_parts(&parts) ),
+                _ => None
+            };
+            let text = match text {
+                LabelText::Static(text) => text,
+                _ => String::new()
+            };
+            let preview = factory.preview.is_some();
+            if styled {
+                let mut label = match parts {
+                    Some(parts) => crate::widget::DXLabel::dynamic( move |data:&JSValue, _env| data::format(&parts, data, preview) ),
+                    None => crate::widget::DXLabel::new( text )
+                };
+                if let Some(align) = align {
+                    label.set_text_alignment(align);
+                }
+                if let Some(mode) = line_break {
+                    label.set_line_break_mode(mode);
+                }
+                if button {
+                    crate::widget::DXButton::from_label(label).boxed()
                 } else {
-                    return Err(Error::InvalidAttributeValue( (attrs.pos(),"padding") ) )
+                    label.boxed()
                 }
             } else {
-                $widget
-            }
-        };
-        ( $widget:ident, "color" ) => {
-            if let Some(value) = get_style!("color") {
-                $widget.with_text_color( color::to_color(value, Some(Color::rgb8(0,0,0)) ) )
-            } else {
-                $widget
-            }
-        };
-        ( $widget:ident, "font-size" ) => {
-            if let Some(value) = get_style!("font-size") {
-                let tv = value.trim();
-                let font_size = match tv.as_bytes() {
-                    b"xx-small" => 9f64,
-                    b"x-small" => 10f64,
-                    b"small" => 13.333f64,
-                    b"medium" => 16f64,
-                    b"large" => 18f64,
-                    b"x-large" => 24f64,
-                    b"xx-large" => 32f64,
-                    [val @ .. , b'p', b'x'] => String::from_utf8_lossy(val).parse::<f64>().unwrap_or(13.333f64),
-                    [val @ .. , b'e', b'm'] => String::from_utf8_lossy(val).parse::<f64>().map( |v| v / 0.0625).unwrap_or(13.333f64),
-                    [val @ .. , b'p', b't'] => String::from_utf8_lossy(val).parse::<f64>().map( |v| v * 1.333).unwrap_or(13.333f64),
-                    [val @ .. , b'%'] => String::from_utf8_lossy(val).parse::<f64>().map( |v| v / 100f64 / 0.0625 ).unwrap_or(13.333f64),
-                    val @ _ => String::from_utf8_lossy(val).parse::<f64>().unwrap_or(13.333f64)
+                let mut label = match parts {
+                    Some(parts) => Label::dynamic( move |data:&JSValue, _env| data::format(&parts, data, preview) ),
+                    None => Label::new( text )
                 };
-                $widget.with_text_size( font_size )
-            } else {
-                $widget
-            }
-        };
-        ( $widget:ident, "border" ) => {
-            if let Some(border) = get_style!("border") {
-                let v = border.trim();
-                let mut splited = v.split_whitespace();
-                let width = splited.next().map( |v| v[..v.find("px").unwrap_or(v.len())].parse::<f64>().unwrap_or(1f64) ).unwrap_or(1f64);
-                //TODO : support other border style?
-                let _border_style = splited.next().unwrap_or("solid");
-                if _border_style != "solid" {
-                    return Err(Error::InvalidBorderAttributeValue(attrs.pos()))
-                } else {
-                    let color = color::to_color(splited.next().unwrap_or("black"), None);
-                    $widget.border(color, width).boxed()
+                if let Some(align) = align {
+                    label.set_text_alignment(align);
                 }
-            } else {
-                $widget
-            }
-        };
-        ( $widget:ident, "text-align" ) => {
-            if let Some(v) = get_style!("text-align") {
-                match v {
-                    "left" => $widget.with_text_alignment(TextAlignment::Start),
-                    "right" => $widget.with_text_alignment(TextAlignment::End),
-                    "center" => $widget.with_text_alignment(TextAlignment::Center),
-                    "justify" => $widget.with_text_alignment(TextAlignment::Justified),
-                    _ => return Err( Error::InvalidAttributeValue((0,"text-align")) )
+                if let Some(mode) = line_break {
+                    label.set_line_break_mode(mode);
                 }
-            } else {
-                $widget
-            }
-        };
-
-        ( $widget:ident, "text-align" ) => {
-            if let Some(v) = get_style!("text-align") {
-                match v {
-                    "left" => $widget.set_text_align(druid::TextAlignment::Start),
-                    "right" => $widget.set_text_align(druid::TextAlignment::End),
-                    "center" => $widget.set_text_align(druid::TextAlignment::Center),
-                    "justify" => $widget.set_text_align(druid::TextAlignment::Justify),
-                    _ => return Err( Error::InvalidAttributeValue((0,"text-align")) )
+                if button {
+                    Button::from_label(label).boxed()
+                } else {
+                    label.boxed()
                 }
             }
-        };
-    }
+        },
 
-    macro_rules! new_parent_stack {
-        () => {
-            new_parent_stack!( elem )
-        };
-        ( $new:ident ) => { {
-            let mut new_stack = parent_stack.to_owned();
-            new_stack.push( $new );
-            new_stack
-        } }
-    }
+        Kind::Checkbox { text } => bind_value(factory, lens, id, Checkbox::new(text), false),
 
-    let mut text = elem.text.as_ref().map( |e| String::from_utf8_lossy(&e) ).unwrap_or( std::borrow::Cow::Borrowed("") );
-
-    if text.starts_with("${") && text.ends_with('}') {
-        let key = text[2..text.len()-1].trim();
-        if let Some(parameter) = parameter {
-            if let Some(param_value) = parameter.get( key.as_bytes() ) {
-                text = Cow::Owned(String::from_utf8_lossy(&param_value).as_ref().to_owned());
-                //TODO : how to avoid?
-                // text = String::from_utf8_lossy(param_value);
+        Kind::TextBox { multiline, placeholder, value, input, color, font_size, align } => {
+            let mut textbox = if multiline { TextBox::multiline() } else { TextBox::new() };
+            if let Some(color) = color {
+                textbox.set_text_color(color);
             }
-        } 
-    }
-    
-    let input_type_holder = &attrs.get(b"type").unwrap_or(Cow::Borrowed(b""));
-    let input_type = String::from_utf8_lossy( input_type_holder );
-
-    let normal_transition = get_style!("transition");
-    let has_norm_style = 
-            get_style!("padding").is_some() 
-            | get_style!("margin").is_some() 
-            | get_style!("font-size").is_some() 
-            | get_style!("width").is_some() 
-            | get_style!("height").is_some() 
-            | get_style!("color").is_some() 
-            | get_style!("background-color").is_some() 
-            | get_style!("border").is_some()
-            | normal_transition.is_some();
-    let mut has_pseudo_style = false;
-    for rule in css.rules.iter() {
-        let pseudo_trap_hack = PseudoOrderTrapQueryWrap::new( ElementQueryWrap { parent_stack, elem } );           
-        if rule.selector.matches(&pseudo_trap_hack) {
-            let pseudo = pseudo_trap_hack.get_pseudo();
-
-            /// check disabled. simplecss 'disabled' pseudo not support
-            let selector = rule.selector.to_string();
-            let mut check_disabled = simplecss::SelectorTokenizer::from( selector.as_str() );
-            let is_disabled = check_disabled.find( |e| {
-                if let Ok(e) = e {
-                    match e {
-                        simplecss::SelectorToken::PseudoClass(p) => *p == "disabled",
-                        _ => false
+            if let Some(size) = font_size {
+                textbox.set_text_size(size);
+            }
+            if let Some(align) = align {
+                textbox.set_text_alignment(align);
+            }
+            if let Some(placeholder) = placeholder {
+                textbox.set_placeholder(placeholder);
+            }
+            match input {
+                Input::Text => bind_value(factory, lens, id, textbox, value.unwrap_or_default()),
+                Input::Password => bind_value(factory, lens, id, textbox.lens( crate::widget::input::password() ), value.unwrap_or_default()),
+                Input::Number { min, max, step, format } => {
+                    let mut formatter = NumberFormatter::new();
+                    if let Some(v) = min {
+                        formatter = formatter.min(v);
                     }
-                } else {
-                    false
-                }
-            }).is_some();
-            
-            let pseudo = if is_disabled {
-                Some(Pseudo::Disabled)
-            } else {
-                pseudo
-            };
-
-            if pseudo.is_some() {
-                has_pseudo_style = rule.declarations.iter().find( |e| e.name == "padding" ).is_some()
-                | rule.declarations.iter().find( |e| e.name == "margin" ).is_some()
-                | rule.declarations.iter().find( |e| e.name == "font-size" ).is_some()
-                | rule.declarations.iter().find( |e| e.name == "width" ).is_some()
-                | rule.declarations.iter().find( |e| e.name == "height" ).is_some()
-                | rule.declarations.iter().find( |e| e.name == "color" ).is_some()
-                | rule.declarations.iter().find( |e| e.name == "background-color" ).is_some()
-                | rule.declarations.iter().find( |e| e.name == "border" ).is_some();
-                if has_pseudo_style {
-                    break
+                    if let Some(v) = max {
+                        formatter = formatter.max(v);
+                    }
+                    if let Some(v) = step {
+                        formatter = formatter.step(v);
+                    }
+                    if let Some(format) = format {
+                        formatter = formatter.format(&format);
+                    }
+                    let state = InvalidState::default();
+                    invalid = Some(state.clone());
+                    bind_value(factory, lens, id, ValueTextBox::new(textbox, formatter).validate_while_editing(true).delegate(state), value.and_then( |e| e.parse().ok() ).unwrap_or(0f64))
                 }
             }
-        }
-    }
-
-    //TODO : Wrap EnvSetup
-    //TODO : Bind event
-    let mut child:Box<dyn Widget<()>> = 
-    if tag == "flex" {
-        let mut flex = if let Some( Cow::Borrowed(b"column") ) = attrs.get(b"direction") {
-            druid::widget::Flex::column()
-        } else {
-            druid::widget::Flex::row()
-        };
-        if elem.childs.len() < 1 {
-            return Err(Error::InvalidFlexChildNum(elem.src_pos))
-        }
-
-        if let Some(v) = attrs.get(b"must_fill_main_axis") {
-            if "true" == String::from_utf8_lossy(&v) {
-               flex = flex.must_fill_main_axis(true); 
+        },
+
+        Kind::Image { src, fit, rendering } => {
+            let bytes = factory.resources.resolve(&src).ok_or( Error::ResourceNotFound( (pos, src.clone()) ) )?;
+            let image_buf = druid::ImageBuf::from_data(&bytes).map_err( |_| Error::InvalidImage( (pos, src) ) )?;
+            let mut image = Image::new(image_buf);
+            if let Some(fit) = fit {
+                image.set_fill_mode(fit);
             }
-        }
+            if let Some(mode) = rendering {
+                image.set_interpolation_mode(mode);
+            }
+            image.boxed()
+        },
+
+        //no data on preview. `PREVIEW_LIST_ITEMS` items of the template
+        Kind::List { item, template, horizontal, spacing } => {
+            //error of the item template is reported now and the widget is the first item
+            let first = RefCell::new( Some(build_widget(factory, *template)?) );
+            let item_factory = factory.clone();
+            let mut list = List::new(move || match first.borrow_mut().take() {
+                Some(first) => first,
+                None => build_item(&item_factory, &item).unwrap_or_else( |e| Label::new(e.to_string()).boxed() )
+            });
+            if horizontal {
+                list = list.horizontal();
+            }
+            if let Some(spacing) = spacing {
+                list = list.with_spacing(spacing);
+            }
+            let placeholders = if factory.preview.is_some() { PREVIEW_LIST_ITEMS } else { 0 };
+            list.lens( JSLens::new(None, Arc::new(vec![JSValue::default(); placeholders])) ).boxed()
+        },
 
-        if let Some(v) = attrs.get(b"cross_axis_alignment") {
-            let v = match v.as_ref() {
-                b"start" => druid::widget::CrossAxisAlignment::Start,
-                b"center" => druid::widget::CrossAxisAlignment::Center,
-                b"end" => druid::widget::CrossAxisAlignment::End,
-                b"baseline" => druid::widget::CrossAxisAlignment::Baseline,
-                _ => return Err(Error::InvalidAttributeValue((elem.src_pos, "cross_axis_alignment")))
-            };
-            flex.set_cross_axis_alignment(v);
-        }
+        Kind::Scroll { child } => Scroll::new( build_widget(factory, *child)? ).boxed(),
 
-        if let Some(v) = attrs.get(b"axis_alignment") {
-            let v = match v.as_ref() {
-                b"start" => druid::widget::MainAxisAlignment::Start,
-                b"center" => druid::widget::MainAxisAlignment::Center,
-                b"end" => druid::widget::MainAxisAlignment::End,
-                b"spacebetween" => druid::widget::MainAxisAlignment::SpaceBetween,
-                b"spaceevenly" => druid::widget::MainAxisAlignment::SpaceEvenly,
-                b"spacearound" => druid::widget::MainAxisAlignment::SpaceAround,
-                _ => return Err(Error::InvalidAttributeValue((elem.src_pos, "axis_alignment")))
-            };
-            flex.set_main_axis_alignment(v);
-        }
-        
+        Kind::Slider { min, max } => bind_value(factory, lens, id, Slider::new().with_range(min, max), 0f64),
 
-        let new_stack = new_parent_stack!();
-        for child in elem.childs.iter() {
-            if child.tag().as_ref() == b"spacer" {
-                if let Some(v) = child.attributes(None).get(b"flex") {
-                    flex.add_flex_spacer( String::from_utf8_lossy(&v).parse::<f64>().unwrap_or(1f64) );
-                } else {
-                    flex.add_default_spacer( );
-                }
-            } else {
-                let child_widget = build_widget(parameter, parsed_map, &new_stack, child, css)?;
-                if let Some(flex_param) = child.attributes(None).get_as::<f64>(b"flex") {
-                    flex.add_flex_child(child_widget, flex_param );
-                } else {
-                    flex.add_child( child_widget );
-                }
+        Kind::Spinner { color } => {
+            let mut spinner = Spinner::new();
+            if let Some(color) = color {
+                spinner.set_color(color);
             }
-        }
-        flex.boxed()
-    }
-
-    //WARN : checkbox is none-standard
-    else if tag == "label" || tag == "button" {
-        let label_text = if text == "" {
-            tag.as_ref()
-        } else {
-            &text
-        };
-        //let mut label = druid::widget::Label::new( label_text );
-        // label = style!(label, "color");
-        // label = style!(label, "font-size");
-        // label = style!(label, "text-align");
-
-        if has_norm_style || has_pseudo_style {
-            let mut label = crate::widget::DXLabel::new( label_text );
-            if let Some(lbk) = attrs.get(b"line-break") {
-                match lbk.as_ref() {
-                    b"wordwrap" => label.set_line_break_mode( LineBreaking::WordWrap ),
-                    b"clip" => label.set_line_break_mode( LineBreaking::Clip ),
-                    b"overflow" => label.set_line_break_mode( LineBreaking::Overflow ),
-                    _ => ()
-                }
+            spinner.boxed()
+        },
+
+        Kind::Split { column, one, two, split_point, min_size, bar_size, min_bar_area, draggable, solid_bar } => {
+            let one = build_widget(factory, *one)?;
+            let two = build_widget(factory, *two)?;
+            let mut split = if column { Split::columns(one, two) } else { Split::rows(one, two) };
+            if let Some(v) = split_point {
+                split = split.split_point(v);
             }
-            if tag == "button" {
-                crate::widget::DXButton::from_label(label).boxed()
-            } else {
-                label.boxed()
+            if let Some((first, second)) = min_size {
+                split = split.min_size(first, second);
             }
-        } else {
-            let mut label = druid::widget::Label::new( label_text );
-            if let Some(lbk) = attrs.get(b"line-break") {
-                match lbk.as_ref() {
-                    b"wordwrap" => label.set_line_break_mode( LineBreaking::WordWrap ),
-                    b"clip" => label.set_line_break_mode( LineBreaking::Clip ),
-                    b"overflow" => label.set_line_break_mode( LineBreaking::Overflow ),
-                    _ => ()
-                }
+            if let Some(v) = bar_size {
+                split = split.bar_size(v);
             }
-            if tag == "button" {
-                druid::widget::Button::from_label(label).boxed()
-            } else {
-                label.boxed()
+            if let Some(v) = min_bar_area {
+                split = split.min_bar_area(v);
             }
-        }
-    }
-
-    else if tag == "checkbox" || (tag == "input" && input_type == "checkbox") {
-        tag_wrap = "checkbox";
-        let label_text = if text == "" {
-            "Checkbox"
-        } else {
-            &text
-        };
-        Checkbox::new(label_text ).lens( DummyLens::<(),_>::new( false) ).boxed()
-    }
+            if let Some(v) = draggable {
+                split = split.draggable(v);
+            }
+            if let Some(v) = solid_bar {
+                split = split.solid_bar(v);
+            }
+            split.boxed()
+        },
+
+        //no data on preview. the `if` child unless `preview="else"`
+        Kind::If { lens:condition, then, otherwise, preview_else } => {
+            let path = LensPath::parse(&condition).ok_or( Error::InvalidAttributeValue((pos, "lens")) )?;
+            let missing = factory.preview.is_some() && !preview_else;
+            let then = build_widget(factory, *then)?;
+            let otherwise = match otherwise {
+                Some(otherwise) => build_widget(factory, *otherwise)?,
+                None => SizedBox::empty().boxed()
+            };
+            Either::new(move |data:&JSValue, _| path.get_json(data).map( |v| data::truthy(&v) ).unwrap_or(missing), then, otherwise).boxed()
+        },
 
-    //TODO : password type?
-    else if tag == "textbox" || (tag == "input" && input_type == "text") {
-        tag_wrap = "textbox";
-        let mut textbox = if let Some(Cow::Borrowed(b"true")) = attrs.get(b"multiline") {
-            druid::widget::TextBox::multiline()
-        } else {
-            druid::widget::TextBox::new()
-        };
-        textbox = style!(textbox, "color");
-        textbox = style!(textbox, "font-size");
-        textbox = style!(textbox, "text-align");
+        Kind::Stepper { min, max, step, wraparound } => {
+            let stepper = Stepper::new().with_range(min, max).with_step(step).with_wraparound(wraparound);
+            bind_value(factory, lens, id, stepper, 0f64)
+        },
 
-        if let Some(placeholder) = attrs.get_as::<String>(b"placeholder") {
-            textbox.set_placeholder(placeholder);
-        }
+        Kind::Switch => bind_value(factory, lens, id, Switch::new(), false),
 
-        textbox.lens( DummyLens::<(),String>::new(String::new()) ).boxed()
-    }
+        //shapes are drawn in order
+        Kind::Canvas { shapes } => {
+            let drawables = DrawableStack::new( shapes );
+            Painter::new( move |ctx, _:&JSValue, _| drawables.draw(0., ctx) ).boxed()
+        },
 
-    //WARN : "image" is none-standard
-    else if tag == "image" || tag == "img" {
-        tag_wrap = "image";
-        // let file_src_holder = &attrs.get_result("src", 0)?;
-        // let file_src = String::from_utf8_lossy( file_src_holder );
-        // let image_buf = druid::ImageBuf::from_bytes( inclue_bytes!(\"{}\") ).unwrap();\n", &file_src);
-        // let mut image = druid::widget::Image::new(image_buf);
-        // style!(image, "object-fit");
-        // style!(image, "image-rendering");
-        todo!()
-    }
+        Kind::Native { tag, attrs, children } => {
+            let native = factory.registry.get(tag).cloned().ok_or_else( || Error::UnknownTag( (pos, tag.to_owned(), None) ) )?;
+            let children = children.into_iter().map( |child| build_widget(factory, child) ).collect::<Result<Vec<_>,_>>()?;
+            native(&attrs, children)
+        },
 
-    //WARN : list is none-standard
-    else if tag == "list" {
-        todo!()
-        // style!( "let mut list = druid::widget::List::new(", "fn" ,");\n");
-        // if let Some( Cow::Borrowed(b"horizontal") ) = attrs.get(b"direction") {
-        //     src!( "list = list.horizontal();\n");
-        // }
-        // style!( "list.set_spacing(", "spacing", ");\n");
-    }
+        Kind::Template { root } => build_widget(factory, *root)?,
+    };
 
-    else if tag == "scroll" {
-        if elem.childs.len() != 1 {
-            return Err(Error::InvalidScrollChildNum(elem.src_pos))
-        }
-        let new_stack = new_parent_stack!();
-        let child = build_widget(parameter, parsed_map,&new_stack, &elem.childs[0], css)?;
-        Scroll::new(child).boxed()
+    //event handler of the registry. the controller is inside of the lens like the code generation
+    let unregistered = |name:&'static str, path:&str| Error::UnregisteredHandler( (pos, name, path.to_owned()) );
+    let mut controller:Option<EventHandler<JSValue>> = None;
+    let mut on_click = None;
+    for (name, path) in spec.handlers.iter() {
+        let handler = factory.registry.handler(path).ok_or_else( || unregistered(*name, path) )?;
+        if *name == "onclick" {
+            on_click = Some(handler);
+            continue
+        }
+        let handlers = controller.take().unwrap_or_else( EventHandler::new );
+        controller = Some( match *name {
+            "onchange" => handlers.on_change(handler),
+            "onfocus" => handlers.on_focus(handler),
+            "onblur" => handlers.on_blur(handler),
+            _ => handlers.on_dblclick(handler)
+        });
     }
-
-    else if tag == "slider" {
-        let min = attrs.get_as_result::<f64>("min").unwrap_or(0f64);
-        let max = attrs.get_as_result::<f64>("max").unwrap_or(1f64);
-        let mut slider = Slider::new();
-        slider.with_range(min,max).lens( DummyLens::<(),f64>::new(0f64) ).boxed()
+    if let Some(controller) = controller {
+        child = child.controller(controller).boxed();
     }
 
-    else if tag == "spinner" {
-        let mut spinner = Spinner::new();
-        if let Some(v) = attrs.get(b"color") {
-            spinner.set_color( color::to_color(&String::from_utf8_lossy(&v), None) );
-        }
-        spinner.boxed()
+    //rust lens expression(not the path) is not available at runtime
+    if let Some(path) = spec.lens.as_deref().and_then( LensPath::parse ) {
+        child = child.lens( JSLens::new(Some(path), JSValue::default()) ).boxed();
     }
 
-    //TODO : child must be two item
-    else if tag == "split" {
-        if elem.childs.len() != 2 {
-            return Err(Error::InvalidSplitChildNum(elem.src_pos))
-        }
-        let new_stack = new_parent_stack!();
-        let one = build_widget(parameter, parsed_map, &new_stack, &elem.childs[0], css)?;
-        let two = build_widget(parameter, parsed_map, &new_stack, &elem.childs[1], css)?;
-
-        let mut split = if let Some( Cow::Borrowed(b"column") ) = attrs.get(b"direction") {
-            Split::columns(one, two)
-        } else {
-            Split::rows(one, two)
-        };
-        
-        if let Some(v) = attrs.get(b"split_point") {
-            let sp = String::from_utf8_lossy(&v).parse::<f64>().map( |e| if e <= 0f64 {0.1} else {e}).unwrap_or(0.5);
-            split = split.split_point( sp );
-        }
-        if let Some(v) = attrs.get(b"min_size") {
-            let v = String::from_utf8_lossy(&v);
-            let mut splits = v.split(',');
-            split = split.min_size( splits.next().unwrap_or("0.0").parse::<f64>().unwrap_or(0.0), splits.next().unwrap_or("0.0").parse::<f64>().unwrap_or(0.0) );
-        }
-        if let Some(v) = attrs.get(b"bar_size") {
-            split = split.bar_size( String::from_utf8_lossy(&v).parse::<f64>().unwrap_or(6.0) );
-        }
-        if let Some(v) = attrs.get(b"min_bar_area") {
-            split = split.min_bar_area( String::from_utf8_lossy(&v).parse::<f64>().unwrap_or(6.0) );
-        }
-        if let Some(v) = attrs.get(b"draggable") {
-            split = split.draggable( String::from_utf8_lossy(&v).parse::<bool>().unwrap_or(false) );
-        }
-        if let Some(v) = attrs.get(b"solid_bar") {
-            split = split.solid_bar( String::from_utf8_lossy(&v).parse::<bool>().unwrap_or(true) );
-        }
-
-        split.boxed()
+    //`onclick` takes the data of the parent
+    if let Some(handler) = on_click {
+        child = child.on_click(handler).boxed();
     }
 
-    else if tag == "stepper" {
-        let min = attrs.get_as_result::<f64>("min").unwrap_or(std::f64::MIN);
-        let max = attrs.get_as_result::<f64>("max").unwrap_or(std::f64::MAX);
-        let step = attrs.get_as_result::<f64>("step").unwrap_or(std::f64::MAX);
-        let wrap = attrs.get_as_result::<bool>("wraparound").unwrap_or(false);
-        let mut stepper = Stepper::new();
-        stepper = stepper.with_range(min,max);
-        stepper = stepper.with_step(step);
-        stepper = stepper.with_wraparound(wrap);
-        stepper.lens(DummyLens::<(),f64>::new(0f64)).boxed()
+    if let Some(command) = spec.command {
+        let make = factory.registry.command(&command.selector).cloned().ok_or_else( || unregistered("command", &command.selector) )?;
+        let payload = command.payload;
+        let target = command.target;
+        child = child.on_click( move |ctx, _data, _env| {
+            let cmd = make(payload.as_deref());
+            let cmd = match target.as_ref() {
+                Some(Target::Window) => cmd.to(druid::Target::Window(ctx.window_id())),
+                Some(Target::Global) => cmd.to(druid::Target::Global),
+                Some(Target::Widget(id)) => cmd.to(druid::Target::Widget(crate::widget::widget_id(id))),
+                None => cmd
+            };
+            ctx.submit_command(cmd);
+        }).boxed();
     }
 
-    else if tag == "switch" {
-        //TODO : style
-        Switch::new().lens(DummyLens::<(),bool>::new(false)).boxed()
+    //wrap `SizedBox` with optimize
+    match (spec.width, spec.height) {
+        (Some(width), Some(height)) => child = child.fix_size(width, height).boxed(),
+        (Some(width), None) => child = child.fix_width(width).boxed(),
+        (None, Some(height)) => child = child.fix_height(height).boxed(),
+        (None, None) => ()
     }
 
-    //TODO
-    else if tag == "painter" || tag == "canvas" {
-        tag_wrap = "painter";
-        todo!()
+    //wrap `Padding`
+    if styled {
+        child = druid::WidgetExt::padding( child, crate::widget::theme::PADDING ).boxed();
     }
 
-    //WARN : container is none-standard
-    // else if tag == "container" {
-    //     if elem.childs.len() != 1 {
-    //         return Err(Error::InvalidContainerChildNum(elem.src_pos))
-    //     }
-    //     let new_stack = new_parent_stack!();
-    //     let child = build_widget(parameter, parsed_map, &new_stack, &elem.childs[0], css)?;
-    //     Container::new( child ).boxed()
-    // }
-
-    else {
-        if tag == "demo_custom_widget" {
-            ex_custom_widget::CustomWidget{}.boxed()
-        } else {
-            if let Some(elem) = parsed_map.get( tag.as_ref() ) {
-                let new_stack = new_parent_stack!();
-                build_widget(Some(&attrs), parsed_map, &new_stack, elem, css)?
-            } else {
-                return Err(Error::UnknownTag( (elem.src_pos, tag.as_ref().to_owned() )));
-            }
+    //TODO : need optimization for duplicated style(use Rc)
+    if let Some(style) = spec.style {
+        let mut pseudo_styles = [None,None,None,None,None];
+        for (slot, pseudo) in pseudo_styles.iter_mut().zip(style.pseudo) {
+            *slot = Some(pseudo);
         }
-    };
-
-    
-
-
-    //all component
-    //background, padding, 
-    {
-        //wrap Lens
-        //None
-
-        
-
-        //wrap `SizedBox` with optimize
-        if get_style!("width").is_some() && get_style!("height").is_some() {
-            child = style!(child, "width-height");
-        } else {
-            child = style!(child, "width");
-            child = style!(child, "height");
+        let mut styled = crate::widget::SimpleStyleWidget::new(style.normal, pseudo_styles, child );
+        if let Some(invalid) = invalid {
+            styled = styled.with_invalid(invalid);
         }
-
-        //wrap `Padding`
-        if has_norm_style || has_pseudo_style {
-            child = druid::WidgetExt::padding( child, crate::widget::theme::PADDING ).boxed();
-        } else {
-            child = style!(child, "padding").boxed();
-        }
-        
-        //wrap 'Container' 
-        //deprecated 
-        // if tag != "container" {
-        //     child = style!(child, "background-color");
-        //     child = style!(child, "border");
-        // }
+        child = styled.boxed();
     }
 
-    //build styler
-    //TODO : 
-    // - need optimization for duplicated style(use Rc)
-    // - inherit style
-    if has_norm_style || has_pseudo_style {
-        fn parse_time(v:&str) -> u64 {
-            let v = v.to_lowercase();
-            if v.ends_with("s") {
-                u64::from_str_radix( &v[..v.len()-1], 10 ).unwrap_or(0) * 1000_000_000
-            } else if v.ends_with("ms") {
-                u64::from_str_radix( &v[..v.len()-2], 10 ).unwrap_or(0) * 1000_000
-            } else {
-                u64::from_str_radix( v.as_str(), 10 ).unwrap() * 1000_000
-            }
+    //target of the command
+    if let Some(id) = spec.id.as_ref().filter( |id| factory.targets.contains(*id) ) {
+        //every instance would have the same `WidgetId`
+        if spec.parents.iter().copied().chain( std::iter::once(spec.elem) ).any( |e| e.attributes.get("fn").map_or(false, |e| factory.reused.contains(e)) ) {
+            return Err(Error::ReusedTarget( (pos, id.clone()) ))
         }
+        child = child.with_id(crate::widget::widget_id(id)).boxed();
+    }
 
-        fn transition_option(define:Option<&str>, item:&str) -> Option<AnimationState> {
-            let define = if let Some(v) = define {
-                v
-            } else {
-                return None
-            };
-
-            for n in define.split(",") {
-                let mut duration = 0;
-                let mut delay = 0;
-                let mut timing_function = crate::simple_style::TimingFunction::Linear;
-                //[sec] [name] => duration,item
-                //[name] [sec] => duration,item
-                //[sec] [name] [sec] => duration,item,delay
-                let mut wsplited = n.split_whitespace();
-                let expect_duration = wsplited.next().unwrap();
-                let expect_property = if expect_duration.chars().next().unwrap().is_numeric() {
-                    //duration
-                    duration = parse_time(expect_duration);
-                    if duration == 0 {
-                        //ignore
-                        continue;
-                    }
-                    if let Some(s) = wsplited.next() {
-                        s
-                    } else {
-                        continue
-                    }
-                } else {
-                    //pass
-                    expect_duration
-                };
-
-                if expect_property != item {
-                    //not target
-                    continue;
-                }
-
-                let expect_delay = if let Some(s) = wsplited.next() {
-                    s
-                } else {
-                    "0"
-                };
-
-                let expect_tf = if expect_delay.chars().next().unwrap().is_numeric() {
-                    //delay
-                    delay = parse_time(expect_delay);
-                    if let Some(s) = wsplited.next() {
-                        s
-                    } else {
-                        "linear"
-                    }
-                } else {
-                    //timning-funciton
-                    expect_delay
-                };
+    Ok( child )
+}
 
-                timing_function = match expect_tf {
-                    "ease" => crate::simple_style::TimingFunction::Ease,
-                    "ease-in" => crate::simple_style::TimingFunction::EaseIn,
-                    "ease-out" => crate::simple_style::TimingFunction::EaseOut,
-                    "ease-in-out" => crate::simple_style::TimingFunction::EaseInOut,
-                    "linear" => crate::simple_style::TimingFunction::Linear,
-                    "step-start" => crate::simple_style::TimingFunction::Ease,
-                    "step-end" => crate::simple_style::TimingFunction::Ease,
-                    _ => if expect_tf.starts_with("cubic-bezier(") {
-                        let mut params = expect_tf["cubic-bezier(".len() .. expect_tf.rfind(')').unwrap_or(expect_tf.len()-1)].split(',');
-                        crate::simple_style::TimingFunction::CubicBezier{
-                            p1: params.next().unwrap_or("0").parse().unwrap_or(0.),
-                            p2: params.next().unwrap_or("0").parse().unwrap_or(0.),
-                            p3: params.next().unwrap_or("0").parse().unwrap_or(0.),
-                            p4: params.next().unwrap_or("0").parse().unwrap_or(0.)
-                        }
-                    } else if expect_tf.starts_with("steps(") {
-                        let mut params = expect_tf["steps(".len() .. expect_tf.rfind(')').unwrap_or(expect_tf.len()-1)].split(',');
-                        let mut cb = "".to_string();
-                        cb.push_str("n:"); params.next().unwrap_or("0"); cb.push_str("f64, ");
-                        let jumpterm = match params.next().unwrap_or("jump-start") {
-                            "jump-start" => crate::simple_style::JumpTerm::JumpStart,
-                            "jump-end" => crate::simple_style::JumpTerm::JumpEnd,
-                            "jump-none" => crate::simple_style::JumpTerm::JumpNone,
-                            "jump-both" => crate::simple_style::JumpTerm::JumpBoth,
-                            "start" => crate::simple_style::JumpTerm::Start,
-                            "end" => crate::simple_style::JumpTerm::End,
-                            _ => crate::simple_style::JumpTerm::JumpStart
-                        };
-                        crate::simple_style::TimingFunction::Steps{n:params.next().unwrap_or("0").parse().unwrap(), jumpterm}
-                    } else {
-                        crate::simple_style::TimingFunction::Linear
-                    }
-                };
-                return Some(crate::simple_style::AnimationState::from( crate::simple_style::Animation{ delay:delay as _, direction: crate::simple_style::Direction::Normal, duration:duration as _, iteration: 1., name: 1., timing_function, fill_mode: 0. } ));
+/// Value of the input. The data of the `lens`, otherwise([`LocalState`]) the input owns it.
+/// Every input of the preview owns it's value which is available by the element `id`
+fn bind_value<T:JSData, W:Widget<T> + 'static>(factory:&Factory, lens:bool, id:Option<&str>, widget:W, initial:T) -> Box<dyn Widget<JSValue>> {
+    match factory.preview.as_ref() {
+        None if lens => widget.lens( JSLens::new(None, initial) ).boxed(),
+        preview => {
+            let state = LocalState::new(widget, initial);
+            if let (Some(values), Some(id)) = (preview, id) {
+                values.insert(id.to_owned(), Rc::new(state.handle()));
             }
-            return None
-        }
-
-        
-        macro_rules! styler_item {
-            ( color, $caller:expr ) => {
-                $caller.map(|background| {
-                    color::to_color( background, Some(Color::rgb8(255,255,255)) )
-                })
-            };
-            ( insets, $caller:expr ) => {
-                $caller.map( |v| {
-                    let mut splits = v.split_whitespace().map( |s| &s[..s.find("px").unwrap_or(s.len())] );                    
-                    let count = splits.clone().count();
-                    if count == 1 {
-                        druid::Insets::from( splits.next().unwrap_or("0").parse::<f64>().unwrap_or(0f64) )
-                    } else if count == 2 {
-                        druid::Insets::from( (splits.next().unwrap_or("0").parse::<f64>().unwrap_or(0f64), splits.next().unwrap_or("0").parse::<f64>().unwrap_or(0f64)) )
-                    } else {
-                        druid::Insets::from( (splits.next().unwrap_or("0").parse::<f64>().unwrap_or(0f64), splits.next().unwrap_or("0").parse::<f64>().unwrap_or(0f64), splits.next().unwrap_or("0").parse::<f64>().unwrap_or(0f64), splits.next().unwrap_or("0").parse::<f64>().unwrap_or(0f64)) )
-                    }
-                })
-            };
-           
-            ( f64, $caller:expr ) => {
-                $caller.map( |value| {
-                    let tv = value.trim();
-                    let font_size = match tv.as_bytes() {
-                        b"xx-small" => 9f64,
-                        b"x-small" => 10f64,
-                        b"small" => 13.333f64,
-                        b"medium" => 16f64,
-                        b"large" => 18f64,
-                        b"x-large" => 24f64,
-                        b"xx-large" => 32f64,
-                        [val @ .. , b'p', b'x'] => String::from_utf8_lossy(val).parse::<f64>().unwrap_or(13.333f64),
-                        [val @ .. , b'e', b'm'] => String::from_utf8_lossy(val).parse::<f64>().map( |v| v / 0.0625).unwrap_or(13.333f64),
-                        [val @ .. , b'p', b't'] => String::from_utf8_lossy(val).parse::<f64>().map( |v| v * 1.333).unwrap_or(13.333f64),
-                        [val @ .. , b'%'] => String::from_utf8_lossy(val).parse::<f64>().map( |v| v / 100f64 / 0.0625 ).unwrap_or(13.333f64),
-                        val @ _ => String::from_utf8_lossy(val).parse::<f64>().unwrap_or(13.333f64)
-                    };
-                    font_size
-                } )
-            };
-            ( border, $caller:expr, $caller_rad:expr ) => {
-                $caller.map(|border| {
-                    let v = border.trim();
-                    let mut splited = v.split_whitespace();
-                    let width = splited.next().map( |v| v[..v.find("px").unwrap_or(v.len())].parse::<f64>().unwrap_or(1f64) ).unwrap_or(1f64);
-                    //TODO : support other border style?
-                    let _border_style = splited.next().unwrap_or("solid");
-                    let color = color::to_color(splited.next().unwrap_or("black"), None);
-                    let radius = $caller_rad.unwrap_or("0").parse::<f64>().unwrap_or( 0. );
-                    BorderStyle::new(width, radius, color)
-                })
-            };
+            Box::new(state)
         }
+    }
+}
 
-        let normal_transition = get_style!("transition");
-        let normal_style = 
-        crate::simple_style::Styler {
-            padding : ( styler_item!(insets, get_style!("padding")), transition_option(normal_transition, "padding")),
-            margin : ( styler_item!(insets, get_style!("margin")), transition_option(normal_transition, "margin")),
-            font_size : ( styler_item!(f64, get_style!("font-size")), transition_option(normal_transition, "font-size")),
-            width : ( styler_item!(f64, get_style!("width")), transition_option(normal_transition, "width")),
-            height : ( styler_item!(f64, get_style!("height")), transition_option(normal_transition, "height")),
-            text_color : ( styler_item!(color, get_style!("color")), transition_option(normal_transition, "color")),
-            background_color : ( styler_item!(color, get_style!("background-color")), transition_option(normal_transition, "background-color")),
-            border : ( styler_item!(border, get_style!("border"), get_style!("border-radius")), transition_option(normal_transition, "border")),
+fn to_rich_text(rich:&RichSpans) -> RichText {
+    let mut text = RichText::new( rich.text.as_str().into() );
+    for (range, style) in rich.spans.iter() {
+        let attr = match style {
+            InlineStyle::Bold => Attribute::weight(FontWeight::BOLD),
+            InlineStyle::Italic => Attribute::style(FontStyle::Italic),
+            InlineStyle::Underline => Attribute::underline(true),
+            InlineStyle::Color(v) => Attribute::text_color( v.clone() ),
+            InlineStyle::FontSize(v) => Attribute::size( *v ),
         };
-
-        let mut pseudo_styles = [None,None,None,None];
-        let mut temp_styles = vec![];
-        let mut pseudo_count = 0;
-        for rule in css.rules.iter() {
-            let pseudo_trap_hack = PseudoOrderTrapQueryWrap::new( ElementQueryWrap { parent_stack, elem } );
-            
-            if rule.selector.matches(&pseudo_trap_hack) {
-                let pseudo = pseudo_trap_hack.get_pseudo();
-
-                /// check disabled. simplecss 'disabled' pseudo not support
-                let selector = rule.selector.to_string();
-                let mut check_disabled = simplecss::SelectorTokenizer::from( selector.as_str() );
-                let is_disabled = check_disabled.find( |e| {
-                    if let Ok(e) = e {
-                        match e {
-                            simplecss::SelectorToken::PseudoClass(p) => *p == "disabled",
-                            _ => false
-                        }
-                    } else {
-                        false
-                    }
-                }).is_some();
-                
-                let pseudo = if is_disabled {
-                    Some(Pseudo::Disabled)
-                } else {
-                    pseudo
-                };
-
-                macro_rules! get_pseudo_style {
-                    ($name:tt) => {
-                        rule.declarations.iter().find( |e| e.name == $name ).map( |e| e.value )
-                    }
-                }
-
-                if let Some(pseudo) = pseudo {
-                    pseudo_count += 1;
-                    let pseudo_transition = rule.declarations.iter().find( |e| e.name == "transition" ).map( |e| e.value );
-                    let styler = crate::simple_style::Styler {
-                        padding : ( styler_item!(insets, get_pseudo_style!("padding")), transition_option(pseudo_transition, "padding")),
-                        margin : ( styler_item!(insets, get_pseudo_style!("margin")), transition_option(pseudo_transition, "margin")),
-                        font_size : ( styler_item!(f64, get_pseudo_style!("font-size")), transition_option(pseudo_transition, "font-size")),
-                        width : ( styler_item!(f64, get_pseudo_style!("width")), transition_option(pseudo_transition, "width")),
-                        height : ( styler_item!(f64, get_pseudo_style!("height")), transition_option(pseudo_transition, "height")),
-                        text_color : ( styler_item!(color, get_pseudo_style!("color")), transition_option(pseudo_transition, "color")),
-                        background_color : ( styler_item!(color, get_pseudo_style!("background-color")), transition_option(pseudo_transition, "background-color")),
-                        border : ( styler_item!(border, get_pseudo_style!("border"), get_pseudo_style!("border-radius")), transition_option(pseudo_transition, "border")),
-                    };
-                    
-                    let pseudo_styler = match pseudo {
-                        Pseudo::Focus => Some(crate::simple_style::PseudoStyle::focus( styler )),
-                        Pseudo::Hover => Some(crate::simple_style::PseudoStyle::hover( styler )),
-                        Pseudo::Active => Some(crate::simple_style::PseudoStyle::active( styler )),
-                        Pseudo::Disabled => Some(crate::simple_style::PseudoStyle::disabled( styler ))
-                    };
-                    temp_styles.push(pseudo_styler);
-                }
-            }
-        }
-
-        //fill 'None' 
-        for (i,e) in temp_styles.into_iter().enumerate() {
-            pseudo_styles[i] = e;
-        }
-
-        child = crate::widget::SimpleStyleWidget::new(normal_style, pseudo_styles, child ).boxed();
+        text = text.with_attribute(range.clone(), attr);
     }
-
-    Ok( child )
-}
\ No newline at end of file
+    text
+}
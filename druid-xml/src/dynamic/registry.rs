@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use druid::{Command, Data, Widget, WidgetExt};
+
+use crate::{Attributes, Diagnostic};
+use crate::resource::ResourceResolver;
+use crate::widget::Handler;
+use super::{generate, JSValue, StateHandle};
+
+/// Builds the widget of the tag from the resolved attributes(template parameters are interpolated) and the child widgets
+pub type WidgetFactory<T> = dyn Fn(&Attributes, Vec<Box<dyn Widget<T>>>) -> Box<dyn Widget<T>>;
+
+/// `Command` of the `command` attribute from the `command-payload` text. The `target` is applied to the returned command
+pub type CommandFactory = dyn Fn(Option<&str>) -> Command;
+
+/// Native widgets of the application available to the runtime xml.
+/// The registered tags pass the validation and override the templates of the same name.
+///
+/// `T` is the data of the widgets and the handlers. The runtime xml is built by `WidgetRegistry<JSValue>`(the default):
+/// the runtime tree has no rust type to lens into(`lens` is the path of the JSON value, `Type::field` is not available at runtime),
+/// so every widget of the tree including the children is `Widget<JSValue>`. The typed data is the code generation(`druid_xml!`).
+/// A widget of other data type can be `lens(druid::lens::Constant(..))` or [`crate::widget::LocalState`]
+///
+/// ```ignore
+/// let mut registry = WidgetRegistry::new();
+/// registry.register("gauge", |attrs, _children| {
+///     Gauge::new( attrs.get("max").and_then( |e| e.parse().ok() ).unwrap_or(100.) ).boxed()
+/// });
+/// let (widget, ui) = registry.generate_widget(xml)?;
+/// ```
+///
+/// The rust functions of the event handlers(`onclick`, `onchange` ...) and the selectors of the `command` are not available at runtime.
+/// They are registered by the path as written in the xml, otherwise the widget is not built(`Error::UnregisteredHandler`).
+///
+/// ```ignore
+/// registry.register_handler("login", |_ctx, data, _env| println!("{data:?}"));
+/// registry.register_command("crate::cmds::SAVE", |_payload| druid::Command::from(crate::cmds::SAVE));
+/// ```
+#[derive(Clone)]
+pub struct WidgetRegistry<T:Data = JSValue> {
+    factories : HashMap<String, Rc<WidgetFactory<T>>>,
+    handlers : HashMap<String, Handler<T>>,
+    commands : HashMap<String, Rc<CommandFactory>>,
+    /// `None` is the files relative to the `dir`
+    resources : Option<Rc<dyn ResourceResolver<Output=Vec<u8>>>>,
+}
+
+impl <T:Data> WidgetRegistry<T> {
+    pub fn new() -> Self {
+        Self { factories:HashMap::new(), handlers:HashMap::new(), commands:HashMap::new(), resources:None }
+    }
+
+    /// Replace the factory of the same tag
+    pub fn register<F>(&mut self, tag:&str, factory:F) -> &mut Self
+    where F : Fn(&Attributes, Vec<Box<dyn Widget<T>>>) -> Box<dyn Widget<T>> + 'static {
+        self.factories.insert( tag.to_owned(), Rc::new(factory) );
+        self
+    }
+
+    /// Function of the event handler attribute. `path` is the value of the attribute(`handlers::id_changed`)
+    pub fn register_handler(&mut self, path:&str, handler:Handler<T>) -> &mut Self {
+        self.handlers.insert( path.to_owned(), handler );
+        self
+    }
+
+    /// Command of the `command` attribute. `path` is the value of the attribute(`crate::cmds::SAVE`)
+    pub fn register_command<F>(&mut self, path:&str, factory:F) -> &mut Self
+    where F : Fn(Option<&str>) -> Command + 'static {
+        self.commands.insert( path.to_owned(), Rc::new(factory) );
+        self
+    }
+
+    /// Source of the `<image src="...">`. Default is the files relative to the `dir`(`crate::resource::FileResolver`)
+    pub fn set_resources(&mut self, resources:impl ResourceResolver<Output=Vec<u8>> + 'static) -> &mut Self {
+        self.resources = Some( Rc::new(resources) );
+        self
+    }
+
+    pub fn contains(&self, tag:&str) -> bool {
+        self.factories.contains_key(tag)
+    }
+
+    /// Registered tags in no particular order
+    pub fn tags(&self) -> Vec<&str> {
+        self.factories.keys().map( |e| e.as_str() ).collect()
+    }
+
+    pub(crate) fn get(&self, tag:&str) -> Option<&Rc<WidgetFactory<T>>> {
+        self.factories.get(tag)
+    }
+
+    pub(crate) fn handler(&self, path:&str) -> Option<Handler<T>> {
+        self.handlers.get(path).copied()
+    }
+
+    pub(crate) fn command(&self, path:&str) -> Option<&Rc<CommandFactory>> {
+        self.commands.get(path)
+    }
+
+    pub(crate) fn resources(&self) -> Option<Rc<dyn ResourceResolver<Output=Vec<u8>>>> {
+        self.resources.clone()
+    }
+}
+
+impl WidgetRegistry<JSValue> {
+    /// [`super::generate_widget`] with the registered widgets
+    pub fn generate_widget(&self, xml:&str) -> Result< (Box<dyn Widget<()>>, StateHandle), Diagnostic > {
+        self.generate_widget_with_dir(xml, Path::new("."))
+    }
+
+    /// [`super::generate_widget_with_dir`] with the registered widgets
+    pub fn generate_widget_with_dir(&self, xml:&str, dir:&Path) -> Result< (Box<dyn Widget<()>>, StateHandle), Diagnostic > {
+        self.generate_widget_with_imports(xml, dir).map( |(widget, values, _)| (widget, values) )
+    }
+
+    /// [`Self::generate_widget_with_dir`] and the resolved paths of the imported files
+    pub(crate) fn generate_widget_with_imports(&self, xml:&str, dir:&Path) -> Result< (Box<dyn Widget<()>>, StateHandle, Vec<PathBuf>), Diagnostic > {
+        let values = StateHandle::default();
+        let (widget, imports) = generate(xml, dir, self, Some(values.clone())).map_err( |e| Diagnostic::new(xml, e) )?;
+        Ok( (widget.lens( druid::lens::Constant(JSValue::default()) ).boxed(), values, imports) )
+    }
+
+    /// [`super::generate_widget_with_data`] with the registered widgets
+    pub fn generate_widget_with_data(&self, xml:&str) -> Result< Box<dyn Widget<JSValue>>, Diagnostic > {
+        self.generate_widget_with_data_dir(xml, Path::new("."))
+    }
+
+    /// [`super::generate_widget_with_data_dir`] with the registered widgets
+    pub fn generate_widget_with_data_dir(&self, xml:&str, dir:&Path) -> Result< Box<dyn Widget<JSValue>>, Diagnostic > {
+        generate(xml, dir, self, None).map( |(widget, _)| widget ).map_err( |e| Diagnostic::new(xml, e) )
+    }
+}
+
+impl <T:Data> Default for WidgetRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
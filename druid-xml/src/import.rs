@@ -0,0 +1,65 @@
+//! `<import src="common/widgets.xml"/>`
+//!
+//! Templates and styles of the imported document are merged into the importing one.
+//! The path is relative to the importing file.
+
+use std::path::{Path, PathBuf};
+
+use crate::{Document, Diagnostic, Error, Templates, schema};
+
+/// Load the imports of `doc` recursively. Dependencies come first.
+/// `natives` are passed to [`schema::validate`]
+///
+/// The resolved paths of the imported files are returned with the documents in the same order
+pub(crate) fn load(doc:&Document, dir:&Path, natives:Option<&[&str]>) -> Result<(Vec<Document>, Vec<PathBuf>), Error> {
+    let mut loader = Loader { natives, stack:vec![], loaded:vec![], docs:vec![] };
+    loader.load_imports(doc, dir)?;
+    Ok( (loader.docs, loader.loaded) )
+}
+
+struct Loader<'a> {
+    natives : Option<&'a [&'a str]>,
+    /// Importing files for the cycle detection
+    stack : Vec<PathBuf>,
+    /// Same file is imported only once
+    loaded : Vec<PathBuf>,
+    docs : Vec<Document>,
+}
+
+impl <'a> Loader<'a> {
+    fn load_imports(&mut self, doc:&Document, dir:&Path) -> Result<(), Error> {
+        for import in doc.imports() {
+            let pos = import.span.start;
+            let src = import.attributes.get("src").ok_or( Error::AttributeRequired((pos, "src")) )?;
+            let path = dir.join(src).canonicalize().map_err( |_| Error::ImportNotFound((pos, src.to_owned())) )?;
+            if self.stack.contains(&path) {
+                return Err(Error::ImportCycle((pos, src.to_owned())))
+            }
+            if self.loaded.contains(&path) {
+                continue
+            }
+            let xml = std::fs::read_to_string(&path).map_err( |_| Error::ImportNotFound((pos, src.to_owned())) )?;
+
+            self.stack.push( path.clone() );
+            let imported = self.load_document(&xml, path.parent().unwrap_or(dir));
+            self.stack.pop();
+
+            //error in the imported file is located with it's own source
+            let imported = imported.map_err( |e| Error::ImportError((pos, src.to_owned(), Box::new(Diagnostic::new(&xml, e)))) )?;
+            self.loaded.push( path );
+            self.docs.push( imported );
+        }
+        Ok(())
+    }
+
+    fn load_document(&mut self, xml:&str, dir:&Path) -> Result<Document, Error> {
+        let doc = Document::parse(xml)?;
+        self.load_imports(&doc, dir)?;
+        if let Some(e) = schema::validate(&doc, &self.docs, self.natives).into_iter().next() {
+            return Err(e)
+        }
+        //check the templates
+        Templates::collect(&doc, &[], &[])?;
+        Ok( doc )
+    }
+}
@@ -0,0 +1,212 @@
+//! Lens path of the `lens` attribute and the data binding.
+//!
+//! ```text
+//! App::user.address.city      nested field
+//! App::items[2]               index
+//! App::volume | percent       conversion
+//! name                        field of the template `lens` type
+//! ```
+//!
+//! Code generation composes the druid `Lens` of each segment(`field`, `index`, `then`).
+//! Conversion is the built-in(`percent`, `not`, `string`) or the path of the rust `Lens` value.
+//! The dynamic backend resolves the same path against the JSON data.
+
+use druid::{Lens, LensExt};
+use druid::lens::{Field, Map};
+use serde_json::Value;
+
+use crate::Error;
+
+/// Field of the `lens` value. The type of the value is inferred from the `lens`(the derived `Lens` of the struct),
+/// so the nested path(`App::user.address`) is composed one segment at a time
+pub fn field<A:?Sized, B:?Sized, C:?Sized, L:Lens<A,B>>(lens:L, get:fn(&B) -> &C, get_mut:fn(&mut B) -> &mut C) -> impl Lens<A,C> {
+    lens.then( Field::new(get, get_mut) )
+}
+
+/// `f64` ratio as percent. `0.5` <=> `50`
+pub fn percent() -> impl Lens<f64, f64> {
+    Map::new( |v:&f64| *v * 100., |v:&mut f64, x:f64| *v = x / 100. )
+}
+
+/// Negated `bool`
+pub fn not() -> impl Lens<bool, bool> {
+    Map::new( |v:&bool| !*v, |v:&mut bool, x:bool| *v = !x )
+}
+
+/// Text of the value. Unparsable text is ignored
+pub fn string<T:ToString+std::str::FromStr>() -> impl Lens<T, String> {
+    Map::new( |v:&T| v.to_string(), |v:&mut T, x:String| if let Ok(x) = x.parse() { *v = x } )
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum Segment {
+    Field(String),
+    Index(usize),
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub struct LensPath {
+    /// `App::user` or `user`
+    pub root : String,
+    /// `.address`, `[2]`
+    pub segments : Vec<Segment>,
+    /// `percent`, `crate::conv::celsius`
+    pub conversions : Vec<String>,
+}
+
+impl LensPath {
+    /// `None` if it's not the lens path
+    pub fn parse(v:&str) -> Option<Self> {
+        let mut pipes = v.split('|');
+        let path = pipes.next()?.trim();
+        let conversions = pipes.map( |e| e.trim().to_owned() ).collect::<Vec<_>>();
+        if !conversions.iter().all( |e| is_path(e) ) {
+            return None
+        }
+
+        let root_end = path.find( |c| c == '.' || c == '[' ).unwrap_or(path.len());
+        let root = &path[..root_end];
+        if !is_path(root) {
+            return None
+        }
+        let mut segments = vec![];
+        let mut rest = &path[root_end..];
+        while !rest.is_empty() {
+            if let Some(field) = rest.strip_prefix('.') {
+                let end = field.find( |c| c == '.' || c == '[' ).unwrap_or(field.len());
+                if !is_ident(&field[..end]) {
+                    return None
+                }
+                segments.push( Segment::Field(field[..end].to_owned()) );
+                rest = &field[end..];
+            } else if let Some(index) = rest.strip_prefix('[') {
+                let end = index.find(']')?;
+                segments.push( Segment::Index(index[..end].trim().parse().ok()?) );
+                rest = &index[end+1..];
+            } else {
+                return None
+            }
+        }
+        Some( Self { root:root.to_owned(), segments, conversions } )
+    }
+
+    /// Not the plain `Type::field`
+    pub fn is_compound(&self) -> bool {
+        !self.segments.is_empty() || !self.conversions.is_empty()
+    }
+
+    /// Rust lens expression. `field` is the field of the `data_type`
+    pub(crate) fn to_rust(&self, data_type:Option<&str>, pos:usize) -> Result<String, Error> {
+        let mut lens = match (self.root.contains("::"), data_type) {
+            (true, _) => self.root.clone(),
+            (false, Some(ty)) => format!("{ty}::{}", self.root),
+            (false, None) => return Err(Error::AttributeRequired( (pos, "lens") ))
+        };
+
+        //`App::user.address[2]`. the type of the field is inferred from the previous lens
+        for segment in self.segments.iter() {
+            lens = match segment {
+                Segment::Field(name) => format!("druid_xml::lens::field({lens}, |data| &data.{name}, |data| &mut data.{name})"),
+                Segment::Index(i) => format!("druid::LensExt::index({lens}, {i})"),
+            };
+        }
+
+        for conversion in self.conversions.iter() {
+            let conversion = match conversion.as_str() {
+                "percent" | "not" | "string" => format!("druid_xml::lens::{conversion}()"),
+                _ => conversion.clone()
+            };
+            lens = format!("druid::LensExt::then({lens}, {conversion})");
+        }
+        Ok( lens )
+    }
+
+    /// Value of the JSON data. The type name of the root is ignored(`App::user` is `user`)
+    pub fn get_json(&self, data:&Value) -> Option<Value> {
+        let mut v = data.get( self.root_field() )?;
+        for segment in self.segments.iter() {
+            v = match segment {
+                Segment::Field(name) => v.get(name.as_str())?,
+                Segment::Index(i) => v.get(*i)?,
+            };
+        }
+        let mut v = v.clone();
+        for conversion in self.conversions.iter() {
+            v = convert_json(conversion, v, false);
+        }
+        Some(v)
+    }
+
+    /// Set the value of the JSON data. Missing field(and `null` object) is inserted
+    pub fn set_json(&self, data:&mut Value, value:Value) -> bool {
+        let mut value = value;
+        for conversion in self.conversions.iter().rev() {
+            value = convert_json(conversion, value, true);
+        }
+        if data.is_null() {
+            *data = Value::Object(Default::default());
+        }
+        let mut target = match data.as_object_mut() {
+            Some(obj) => obj.entry( self.root_field() ).or_insert(Value::Null),
+            None => return false
+        };
+        for segment in self.segments.iter() {
+            target = match segment {
+                Segment::Field(name) => match object_mut(target) {
+                    Some(obj) => obj.entry( name.as_str() ).or_insert(Value::Null),
+                    None => return false
+                },
+                Segment::Index(i) => match target.get_mut(*i) {
+                    Some(v) => v,
+                    None => return false
+                }
+            };
+        }
+        //`string` of the number or bool keeps the type
+        if let (Value::String(s), false) = (&value, target.is_string() || target.is_null()) {
+            match serde_json::from_str::<Value>(s) {
+                Ok(parsed) if std::mem::discriminant(&parsed) == std::mem::discriminant(target) => value = parsed,
+                _ => return false
+            }
+        }
+        *target = value;
+        true
+    }
+
+    fn root_field(&self) -> &str {
+        &self.root[ self.root.rfind("::").map( |e| e+2 ).unwrap_or(0) .. ]
+    }
+}
+
+fn object_mut(v:&mut Value) -> Option<&mut serde_json::Map<String, Value>> {
+    if v.is_null() {
+        *v = Value::Object(Default::default());
+    }
+    v.as_object_mut()
+}
+
+/// Built-in conversion of the JSON value. Unknown conversion is the identity
+fn convert_json(conversion:&str, v:Value, inverse:bool) -> Value {
+    match (conversion, v) {
+        ("percent", Value::Number(n)) => {
+            let n = n.as_f64().unwrap_or(0.);
+            serde_json::json!( if inverse { n / 100. } else { n * 100. } )
+        },
+        ("not", Value::Bool(b)) => Value::Bool(!b),
+        ("string", v) if !inverse => match v {
+            Value::String(s) => Value::String(s),
+            v => Value::String(v.to_string())
+        },
+        (_, v) => v
+    }
+}
+
+fn is_ident(v:&str) -> bool {
+    let mut chars = v.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_') && chars.all( |c| c.is_alphanumeric() || c == '_' )
+}
+
+/// `Ident(::Ident)*`
+pub(crate) fn is_path(v:&str) -> bool {
+    !v.is_empty() && v.split("::").all(is_ident)
+}
@@ -1,51 +1,42 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
 
-use quick_xml::events::attributes::Attributes;
-use quick_xml::reader::Reader;
-use quick_xml::events::{Event, BytesStart, BytesText};
-use quick_xml::name::QName;
 use simplecss::{StyleSheet};
 
 pub mod writer;
 mod named_color;
 use writer::{SourceGenerator, DruidGenerator};
 
-pub mod qwidget;
-pub mod widget;
-pub mod simple_style;
-mod curve;
-
+pub mod diagnostic;
+pub use diagnostic::Diagnostic;
 
-#[derive(Default)]
-struct DummyLens<T,A> {
-    o : PhantomData<T>,
-    a : A
-}
+pub mod document;
+pub use document::{Document, Node, Element, Attributes, Attribute, Text, Span};
 
-impl <T,A> DummyLens<T,A> {
-    fn new(a : A) -> Self {
-        Self { o : PhantomData, a : a }
-    }
-}
+pub mod schema;
+use schema::Children;
 
-impl <T:druid::Data,U> druid::Lens<T, U> for DummyLens<T,U> {
-    fn with<V, F: FnOnce(&U) -> V>(&self, data: &T, f: F) -> V {
-        f(&self.a)
-    }
+mod import;
+mod template;
+use template::Template;
+mod spec;
+mod bind;
+pub mod lens;
+mod model;
+pub mod resource;
 
-    fn with_mut<V, F: FnOnce(&mut U) -> V>(&self, data: &mut T, f: F) -> V {
-        #[allow(mutable_transmutes)]
-        f( unsafe { std::mem::transmute::<&U,&mut U>(&self.a) } )
-    }
-}
+pub mod qwidget;
+pub mod widget;
+pub mod simple_style;
+mod curve;
+mod rich_text;
 
 //#[cfg(feature="dynamic")]
 pub mod dynamic;
 
 #[cfg_attr(taret_arch="wasm32", wasm_bindgen)]
-#[derive(Debug)]
+#[derive(Debug,Clone)]
 pub enum Error {
 	///Flex child length at least 1
 	InvalidFlexChildNum( usize ),
@@ -80,11 +71,14 @@ pub enum Error {
 	///That element can't have child
 	ChildlessElement( usize ),
 
-	///Unknown attribute
-	UnknownAttribute( usize ),
+	///Unknown attribute. (position, name, similar attribute)
+	UnknownAttribute( (usize, String, Option<String>) ),
+
+	//Unknwon localname. (position, tag, similar tag)
+	UnknownTag( (usize, String, Option<String>) ),
 
-	//Unknwon localname
-	UnknownTag( (usize,String) ),
+	///Value is not one of the allowed values. (position, attribute, value, allowed values)
+	InvalidEnumValue( (usize, &'static str, String, &'static [&'static str]) ),
 
 	///Not available as top elment
 	InvalidTopElement( usize ),
@@ -93,11 +87,68 @@ pub enum Error {
 	CSSSyntaxError( (usize,simplecss::Error) ),
 
 	///XML syntax error
-	XMLSyntaxError( (usize,quick_xml::Error) )
+	XMLSyntaxError( (usize,quick_xml::Error) ),
+
+	///There is no top element which has `fn` name contains "main"
+	MainWidgetNotFound( usize ),
+
+	///Imported file not exist. (position, src)
+	ImportNotFound( (usize, String) ),
+
+	///File imports itself directly or indirectly. (position, src)
+	ImportCycle( (usize, String) ),
+
+	///Error in the imported file. (position, src, error in the imported file)
+	ImportError( (usize, String, Box<Diagnostic>) ),
+
+	///Template parameter without default value is not given. (position, name)
+	ParamRequired( (usize, String) ),
+
+	///Template parameter value is not the declared type. (position, name, type)
+	InvalidParamValue( (usize, String, &'static str) ),
+
+	///`<param>` is available only as the direct child of the template
+	MisplacedParam( usize ),
+
+	///If needs one child and optional `<else>`
+	InvalidIfChildNum( usize ),
+
+	///`<else>` is available only as the child of `<if>`
+	MisplacedElse( usize ),
+
+	///Template not exist. (position, name, similar template)
+	UnknownTemplate( (usize, String, Option<String>) ),
+
+	///Field not declared in the `<model>`. (position, model, field, similar field)
+	UnknownField( (usize, String, String, Option<String>) ),
+
+	///`<model>` is available only as the top element and has only `<field>` children
+	MisplacedModel( usize ),
+
+	///`<field>` is available only as the child of `<model>`
+	MisplacedField( usize ),
+
+	///Resource of the `src` not exist. (position, src)
+	ResourceNotFound( (usize, String) ),
+
+	///Unsupported or broken image. (position, src)
+	InvalidImage( (usize, String) ),
+
+	///Shapes(`rect`, `circle`, `ellipse`, `arc`, `line`) are available only as the children of `canvas` which has only shapes
+	MisplacedShape( usize ),
+
+	///Command target(`target="#id"`) in the template which is used more than once or the list item. (position, id)
+	ReusedTarget( (usize, String) ),
+
+	///Event handler or command of the runtime widget which is not registered to the `dynamic::WidgetRegistry`. (position, attribute, path)
+	UnregisteredHandler( (usize, &'static str, String) ),
+
+	///Format spec of the data-bound text which is not `[[fill]align][width][.precision]`. (position, spec)
+	InvalidFormatSpec( (usize, String) ),
 }
 
 impl Error {
-	fn error_at(&self) -> usize {
+	pub fn error_at(&self) -> usize {
 		match self {
 			Error::InvalidFlexChildNum( s ) => *s,
 			Error::InvalidSplitChildNum( s ) => *s,
@@ -110,52 +161,221 @@ impl Error {
 			Error::InvalidSizeAttributeValue( s ) => *s,
 			Error::InvalidBorderAttributeValue( s ) => *s,
 			Error::ChildlessElement(s) => *s,
-			Error::UnknownAttribute(s) => *s,
-			Error::UnknownTag( (s,_) ) => *s,
+			Error::UnknownAttribute( (s,_,_) ) => *s,
+			Error::UnknownTag( (s,_,_) ) => *s,
+			Error::InvalidEnumValue( (s,_,_,_) ) => *s,
 			Error::InvalidTopElement(s) => *s,
 			Error::CSSSyntaxError( (s,_) ) => *s,
 			Error::XMLSyntaxError( (s, _) ) => *s,
+			Error::MainWidgetNotFound( s ) => *s,
+			Error::ImportNotFound( (s,_) ) => *s,
+			Error::ImportCycle( (s,_) ) => *s,
+			Error::ImportError( (s,_,_) ) => *s,
+			Error::ParamRequired( (s,_) ) => *s,
+			Error::InvalidParamValue( (s,_,_) ) => *s,
+			Error::MisplacedParam( s ) => *s,
+			Error::InvalidIfChildNum( s ) => *s,
+			Error::MisplacedElse( s ) => *s,
+			Error::UnknownTemplate( (s,_,_) ) => *s,
+			Error::UnknownField( (s,_,_,_) ) => *s,
+			Error::MisplacedModel( s ) => *s,
+			Error::MisplacedField( s ) => *s,
+			Error::ResourceNotFound( (s,_) ) => *s,
+			Error::InvalidImage( (s,_) ) => *s,
+			Error::MisplacedShape( s ) => *s,
+			Error::ReusedTarget( (s,_) ) => *s,
+			Error::UnregisteredHandler( (s,_,_) ) => *s,
+			Error::InvalidFormatSpec( (s,_) ) => *s,
 		}
 	}
-}
 
+	///variant name
+	pub fn name(&self) -> &'static str {
+		match self {
+			Error::InvalidFlexChildNum( _ ) => "InvalidFlexChildNum",
+			Error::InvalidSplitChildNum( _ ) => "InvalidSplitChildNum",
+			Error::InvalidContainerChildNum( _ ) => "InvalidContainerChildNum",
+			Error::InvalidScrollChildNum( _ ) => "InvalidScrollChildNum",
+			Error::CloseWithoutStart( _ ) => "CloseWithoutStart",
+			Error::InvalidCloseTag( _ ) => "InvalidCloseTag",
+			Error::AttributeRequired( _ ) => "AttributeRequired",
+			Error::InvalidAttributeValue( _ ) => "InvalidAttributeValue",
+			Error::InvalidSizeAttributeValue( _ ) => "InvalidSizeAttributeValue",
+			Error::InvalidBorderAttributeValue( _ ) => "InvalidBorderAttributeValue",
+			Error::ChildlessElement( _ ) => "ChildlessElement",
+			Error::UnknownAttribute( _ ) => "UnknownAttribute",
+			Error::UnknownTag( _ ) => "UnknownTag",
+			Error::InvalidEnumValue( _ ) => "InvalidEnumValue",
+			Error::InvalidTopElement( _ ) => "InvalidTopElement",
+			Error::CSSSyntaxError( _ ) => "CSSSyntaxError",
+			Error::XMLSyntaxError( _ ) => "XMLSyntaxError",
+			Error::MainWidgetNotFound( _ ) => "MainWidgetNotFound",
+			Error::ImportNotFound( _ ) => "ImportNotFound",
+			Error::ImportCycle( _ ) => "ImportCycle",
+			Error::ImportError( _ ) => "ImportError",
+			Error::ParamRequired( _ ) => "ParamRequired",
+			Error::InvalidParamValue( _ ) => "InvalidParamValue",
+			Error::MisplacedParam( _ ) => "MisplacedParam",
+			Error::InvalidIfChildNum( _ ) => "InvalidIfChildNum",
+			Error::MisplacedElse( _ ) => "MisplacedElse",
+			Error::UnknownTemplate( _ ) => "UnknownTemplate",
+			Error::UnknownField( _ ) => "UnknownField",
+			Error::MisplacedModel( _ ) => "MisplacedModel",
+			Error::MisplacedField( _ ) => "MisplacedField",
+			Error::ResourceNotFound( _ ) => "ResourceNotFound",
+			Error::InvalidImage( _ ) => "InvalidImage",
+			Error::MisplacedShape( _ ) => "MisplacedShape",
+			Error::ReusedTarget( _ ) => "ReusedTarget",
+			Error::UnregisteredHandler( _ ) => "UnregisteredHandler",
+			Error::InvalidFormatSpec( _ ) => "InvalidFormatSpec",
+		}
+	}
 
-#[derive(Debug,Clone)]
-pub(crate) struct Element<'a> {
-	src_pos : usize,
-	src_pos_end : usize,
-	bs : BytesStart<'a>, //Originally, I tried to save the QName, but since BytesStart has a Cow, it took a lifecycle problem. (Cow type is always do that)
-	text : Option<quick_xml::events::BytesText<'a>>,
-	childs : Vec<Element<'a>>
+	///related attribute name if exist
+	pub fn attribute_name(&self) -> Option<&str> {
+		match self {
+			Error::AttributeRequired( (_, n) ) => Some(n),
+			Error::InvalidAttributeValue( (_, n) ) => Some(n),
+			Error::UnknownAttribute( (_, n, _) ) => Some(n.as_str()),
+			Error::InvalidEnumValue( (_, n, _, _) ) => Some(n),
+			Error::ImportNotFound( _ ) | Error::ImportCycle( _ ) | Error::ImportError( _ ) => Some("src"),
+			Error::ResourceNotFound( _ ) | Error::InvalidImage( _ ) => Some("src"),
+			Error::InvalidParamValue( (_, n, _) ) => Some(n.as_str()),
+			Error::UnknownField( _ ) => Some("lens"),
+			Error::ReusedTarget( _ ) => Some("id"),
+			Error::UnregisteredHandler( (_, n, _) ) => Some(n),
+			_ => None
+		}
+	}
+
+	///Convert to [`Diagnostic`] with line/column and source snippet
+	pub fn diagnostic(self, src:&str) -> Diagnostic {
+		Diagnostic::new(src, self)
+	}
+
+	///Some errors are raised without position(css value parser). Locate it at `pos`
+	pub(crate) fn or_at(self, pos:usize) -> Self {
+		if self.error_at() != 0 {
+			return self
+		}
+		match self {
+			Error::AttributeRequired( (_, n) ) => Error::AttributeRequired( (pos, n) ),
+			Error::InvalidAttributeValue( (_, n) ) => Error::InvalidAttributeValue( (pos, n) ),
+			Error::InvalidSizeAttributeValue( _ ) => Error::InvalidSizeAttributeValue( pos ),
+			Error::InvalidBorderAttributeValue( _ ) => Error::InvalidBorderAttributeValue( pos ),
+			Error::MisplacedShape( _ ) => Error::MisplacedShape( pos ),
+			e @ _ => e
+		}
+	}
+}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Error::InvalidFlexChildNum( _ ) => write!(f, "flex needs at least one child"),
+			Error::InvalidSplitChildNum( _ ) => write!(f, "split needs exactly two children"),
+			Error::InvalidContainerChildNum( _ ) => write!(f, "container needs exactly one child"),
+			Error::InvalidScrollChildNum( _ ) => write!(f, "scroll needs exactly one child"),
+			Error::CloseWithoutStart( _ ) => write!(f, "close tag without start tag"),
+			Error::InvalidCloseTag( _ ) => write!(f, "close tag does not match the start tag"),
+			Error::AttributeRequired( (_, n) ) => write!(f, "attribute `{n}` is required"),
+			Error::InvalidAttributeValue( (_, n) ) => write!(f, "invalid value for `{n}`"),
+			Error::InvalidSizeAttributeValue( _ ) => write!(f, "invalid size value"),
+			Error::InvalidBorderAttributeValue( _ ) => write!(f, "invalid border value (only `solid` is supported)"),
+			Error::ChildlessElement( _ ) => write!(f, "this element can't have children"),
+			Error::UnknownAttribute( (_, n, similar) ) => {
+				write!(f, "unknown attribute `{n}`")?;
+				did_you_mean(f, similar.as_deref())
+			},
+			Error::UnknownTag( (_, tag, similar) ) => {
+				write!(f, "unknown tag `{tag}`")?;
+				did_you_mean(f, similar.as_deref())
+			},
+			Error::InvalidEnumValue( (_, n, v, values) ) => {
+				write!(f, "invalid value `{v}` for `{n}` (expected one of {})", values.iter().map( |e| format!("`{e}`") ).collect::<Vec<_>>().join(", "))?;
+				did_you_mean(f, schema::suggest(v, values.iter().copied()))
+			},
+			Error::InvalidTopElement( _ ) => write!(f, "not available as top element"),
+			Error::CSSSyntaxError( (_, e) ) => write!(f, "css syntax error : {e}"),
+			Error::XMLSyntaxError( (_, e) ) => write!(f, "xml syntax error : {e}"),
+			Error::MainWidgetNotFound( _ ) => write!(f, "can't find main widget (top element which `fn` contains \"main\")"),
+			Error::ImportNotFound( (_, src) ) => write!(f, "can't read imported file `{src}`"),
+			Error::ImportCycle( (_, src) ) => write!(f, "import cycle detected at `{src}`"),
+			Error::ImportError( (_, src, d) ) => write!(f, "in `{src}` line {}, col {}: {}", d.line, d.column, d.message),
+			Error::ParamRequired( (_, n) ) => write!(f, "template parameter `{n}` is required"),
+			Error::InvalidParamValue( (_, n, ty) ) => write!(f, "template parameter `{n}` must be `{ty}`"),
+			Error::MisplacedParam( _ ) => write!(f, "`param` is available only as the direct child of the template"),
+			Error::InvalidIfChildNum( _ ) => write!(f, "if needs exactly one child and optional else"),
+			Error::MisplacedElse( _ ) => write!(f, "`else` is available only as the child of `if`"),
+			Error::UnknownTemplate( (_, n, similar) ) => {
+				write!(f, "unknown template `{n}`")?;
+				did_you_mean(f, similar.as_deref())
+			},
+			Error::UnknownField( (_, model, n, similar) ) => {
+				write!(f, "model `{model}` has no field `{n}`")?;
+				did_you_mean(f, similar.as_deref())
+			},
+			Error::MisplacedModel( _ ) => write!(f, "`model` is available only as the top element and has only `field` children"),
+			Error::MisplacedField( _ ) => write!(f, "`field` is available only as the child of `model`"),
+			Error::ResourceNotFound( (_, src) ) => write!(f, "can't find resource `{src}`"),
+			Error::InvalidImage( (_, src) ) => write!(f, "can't decode image `{src}`"),
+			Error::MisplacedShape( _ ) => write!(f, "shapes(`rect`, `circle`, `ellipse`, `arc`, `line`) are available only as the children of `canvas`"),
+			Error::ReusedTarget( (_, id) ) => write!(f, "command target `#{id}` must be a single widget. it is in the list item or the template used more than once"),
+			Error::UnregisteredHandler( (_, "command", path) ) => write!(f, "command `{path}` is not registered. the runtime widget needs `WidgetRegistry::register_command`"),
+			Error::UnregisteredHandler( (_, n, path) ) => write!(f, "handler `{path}` of `{n}` is not registered. the runtime widget needs `WidgetRegistry::register_handler`"),
+			Error::InvalidFormatSpec( (_, spec) ) => write!(f, "invalid format spec `{spec}`. `[[fill]align][width][.precision]` is available"),
+		}
+	}
 }
 
+impl std::error::Error for Error {}
 
-impl <'a> Element<'a> {
-	pub fn tag(&'a self) -> QName<'a> {
-		self.bs.name()
+fn did_you_mean(f: &mut std::fmt::Formatter<'_>, similar:Option<&str>) -> std::fmt::Result {
+	match similar {
+		Some(similar) => write!(f, ", did you mean `{similar}`?"),
+		None => Ok(())
 	}
+}
+
 
-	pub fn attributes(&'a self, rel_param:Option<&'a AttributesWrapper>) -> AttributesWrapper<'a> {
-		AttributesWrapper { pos:self.src_pos, attrs:self.bs.attributes(), rel_attrs:rel_param }
+impl Element {
+	pub(crate) fn attrs<'a>(&'a self, rel_param:Option<&'a AttributesWrapper>) -> AttributesWrapper<'a> {
+		AttributesWrapper { pos:self.span.start, attrs:&self.attributes, rel_attrs:rel_param, resolved:false }
 	}
 }
 
 #[derive(Clone)]
 pub struct AttributesWrapper<'a> {
 	pos : usize,
-	attrs : Attributes<'a>,
-	rel_attrs : Option<&'a AttributesWrapper<'a>>
+	attrs : &'a Attributes,
+	rel_attrs : Option<&'a AttributesWrapper<'a>>,
+	/// Values are interpolated already(template parameters)
+	resolved : bool,
 }
 
 impl <'a> AttributesWrapper<'a> {
+	/// Resolved template parameters
+	pub(crate) fn params(pos:usize, attrs:&'a Attributes) -> Self {
+		Self { pos, attrs, rel_attrs:None, resolved:true }
+	}
+
+	/// Attributes of which template parameters are interpolated
+	pub(crate) fn resolved(&self) -> Attributes {
+		let mut resolved = self.attrs.clone();
+		for attr in self.attrs.iter() {
+			if let Some(v) = self.get(attr.name.as_bytes()) {
+				resolved.set( &attr.name, &String::from_utf8_lossy(&v) );
+			}
+		}
+		resolved
+	}
+
 	fn tuples(&self) -> String {
 		use std::fmt::Write;
 		let mut r = "&[".to_owned();
-		self.attrs.clone().map( |e| {
-			if let Ok(e) = e {
-				write!(&mut r,"({},{}),", String::from_utf8_lossy(e.key.as_ref()), String::from_utf8_lossy(e.value.as_ref())).unwrap();
-			}
-		});
+		for e in self.attrs.iter() {
+			write!(&mut r,"({},{}),", e.name, e.value).unwrap();
+		}
 		r.push(']');
 		r
 	}
@@ -192,37 +412,24 @@ pub(crate) trait AttributeGetter {
 	fn get_size(&self, name:&'static str) -> Result<f64, Error> {
 		let e = self.get_result(name)?;
 		let se = String::from_utf8_lossy(&e);
-		if se.as_ref().ends_with("px") {
-			se[..se.len()-2].parse::<f64>().map_err( |_| Error::InvalidAttributeValue( (self.pos(),name) ) )
-		} else {
-			se.parse::<f64>().map_err( |_| Error::InvalidAttributeValue( (self.pos(),name) ) )
-		}
+		let v = se.strip_suffix("px").unwrap_or(&se);
+		//`inf` and `NaN` are not the rust literal
+		v.parse::<f64>().ok().filter( |e| e.is_finite() ).ok_or( Error::InvalidAttributeValue( (self.pos(),name) ) )
 	}
 }
 
 impl <'a> AttributeGetter for AttributesWrapper<'a> {
     fn get(&self, name:&[u8]) -> Option<Cow<'a, [u8]>> {
-        self.attrs.clone()
-		.find( |e| 
-			e.is_ok() && e.as_ref().unwrap().key.as_ref() == name
-		).map( |e|  {
-			let value = e.unwrap().value;
-			let ck_value = String::from_utf8_lossy(&value);
-			if ck_value.starts_with("${") && ck_value.ends_with("}") {
-				if let Some(rel) = self.rel_attrs {
-					let key = &ck_value[2..ck_value.len()-1];
-					if let Some(alter_value) = rel.get(key.as_bytes() ) {
-						return alter_value
-					}
-				}
-				// let key = &ck_value[2..ck_value.len()-1];
-				// if let Some(alter) = self.rel_attrs.clone().unwrap().find( |e|
-				// 	e.is_ok() && e.as_ref().unwrap().key.as_ref() == key.as_bytes()
-				// ) {
-				// 	return alter.unwrap().value
-				// }
+        self.attrs.iter()
+		.find( |e| e.name.as_bytes() == name )
+		.map( |e|  {
+			if self.resolved {
+				return Cow::Borrowed(e.value.as_bytes())
+			}
+			match template::interpolate(e.value.as_str(), self.rel_attrs) {
+				Cow::Borrowed(v) => Cow::Borrowed(v.as_bytes()),
+				Cow::Owned(v) => Cow::Owned(v.into_bytes())
 			}
-			value
 		})
     }
 
@@ -233,228 +440,216 @@ impl <'a> AttributeGetter for AttributesWrapper<'a> {
 
 }
 
+/// Styles and templates from the top level(or `<druid-xml>` document root) and the imported documents
+pub(crate) struct Templates<'a> {
+	pub style : StyleSheet<'a>,
+	pub elem_map : HashMap<String,Template>,
+	/// Exported `fn` names in document order
+	pub exports : Vec<String>,
+	expected_main_widget : Option<String>,
+	last_widget : Option<String>,
+}
 
-pub fn compile(xml:&str, wrappers:&HashMap<String,String>) -> Result<String,Error> {
-	let mut writer = DruidGenerator::new();
-	let mut style = StyleSheet::new();
-	let mut reader = Reader::from_str(xml);
-	let mut elem_map = HashMap::new();
-	let mut expected_main_widget = None;
-	let mut last_widget = None;
-	loop {
-		let pos = reader.buffer_position();
-		match reader.read_event() {
-			Ok(Event::Start(e)) => {
-				match e.name().as_ref() {
-					b"style" => {
-						let start_pos = reader.buffer_position();
-						match reader.read_to_end(e.name()) {
-							Ok(span) => {
-								let css_impl = &xml[span];
-								style.parse_more( css_impl );
-							},
-							_ => return Err(Error::InvalidCloseTag(start_pos))
-						}
-					},
-					_ => {						
-						if let Some(elem) = parse_element(pos, Some(Event::Start(e)), &mut reader )? {
-							let fnname = elem.attributes( None ).get_as_result::<String>("fn")?;
-							last_widget = Some(fnname.clone());
-                            if fnname.find("main").is_some() {
-                                expected_main_widget = Some(fnname);
-                            }
-							elem_map.insert( elem.attributes( None ).get_as_result::<String>("fn")?, elem);
-						} else {
-							break
-						}
+impl <'a> Templates<'a> {
+	/// `styles` : [`Templates::styles`] of the documents
+	pub fn collect(doc:&'a Document, imports:&'a [Document], styles:&'a [String]) -> Result<Self,Error> {
+		let mut templates = Self { style:StyleSheet::new(), elem_map:HashMap::new(), exports:vec![], expected_main_widget:None, last_widget:None };
+		for css in styles {
+			templates.style.parse_more( css );
+		}
+		//imported first so the importing document overrides the styles and templates
+		for imported in imports {
+			templates.merge(imported, false)?;
+		}
+		templates.merge(doc, true)?;
+		Ok( templates )
+	}
+
+	/// Contents of the `<style>` in the order of override(imported first).
+	/// Pseudo classes which simplecss doesn't support are replaced(`writer::pseudo_compat`)
+	pub fn styles(doc:&Document, imports:&[Document]) -> Vec<String> {
+		let mut styles = vec![];
+		for doc in imports.iter().chain( std::iter::once(doc) ) {
+			for top in doc.elements() {
+				let elems:Vec<&Element> = if top.tag() == "druid-xml" { top.elements().collect() } else { vec![top] };
+				for css in elems.into_iter().filter( |e| e.tag() == "style" ).flat_map( |e| e.children.iter() ) {
+					if let Node::Text(css) = css {
+						styles.push( writer::pseudo_compat(&css.content).into_owned() );
 					}
 				}
-			},
+			}
+		}
+		styles
+	}
 
-			Err(e) => return Err(Error::XMLSyntaxError( (reader.buffer_position(),e) )),
-			// exits the loop when reaching end of file
-			Ok(Event::Eof) => break,
-			Ok(Event::Comment(_)) => (),
-			Ok(Event::CData(_)) => (),
-			Ok(Event::Empty(_)) => (),
-			Ok(Event::Decl(_)) => (),
-			Ok(Event::PI(_)) => (),
-			Ok(Event::DocType(_)) => (),
-			Ok(Event::Text(_)) => (), //ignore text from root node
-			Ok(Event::End(_)) => (), //return Err(Error::CloseWithoutStart(reader.buffer_position())),
-		}
-	}
-
-
-	if let Some(main) = expected_main_widget.and( last_widget ) {
-        if let Some(elem ) = elem_map.get(&main) {
-			let attrs = elem.attributes(None);
-			let fn_name = attrs.get_result("fn")?;
-			let fn_name = String::from_utf8_lossy( fn_name.as_ref() );
-			let lens = attrs.get_result("lens")?;
-			let lens = String::from_utf8_lossy( lens.as_ref() );
-			writer.write_raw(&format!("fn {fn_name}() -> impl druid::Widget<{lens}> {{\n") ).unwrap();
-			writer.write(&elem_map, &elem, &style, wrappers).unwrap();
-			writer.write_raw("}\n").unwrap();
-        } else {
-            panic!();
-        }
-    } else {
-        panic!();
-    }
+	/// Only the `entry` document has the main and exported templates
+	fn merge(&mut self, doc:&'a Document, entry:bool) -> Result<(),Error> {
+		for top in doc.elements() {
+			//templates are the children of the document root. every template is exported by default
+			let document_root = top.tag() == "druid-xml";
+			let elems:Vec<&Element> = if document_root { top.elements().collect() } else { vec![top] };
+			for elem in elems {
+				if elem.tag() == "style" || elem.tag() == "import" || elem.tag() == "model" {
+					continue
+				}
+				let template = Template::new(elem)?;
+				check_children(&template.elem)?;
+				let fnname = elem.attrs( None ).get_as_result::<String>("fn")?;
+				if entry {
+					self.last_widget = Some(fnname.clone());
+					if fnname.find("main").is_some() {
+						self.expected_main_widget = Some(fnname.clone());
+					}
+					if is_exported(elem, document_root)? && !self.exports.contains(&fnname) {
+						self.exports.push( fnname.clone() );
+					}
+				}
+				self.elem_map.insert( fnname, template );
+			}
+		}
+		Ok(())
+	}
 
-	Ok( writer.into() )
+	/// The last template if there is a template which `fn` contains "main"
+	pub fn main(&self) -> Option<&str> {
+		self.expected_main_widget.as_ref().and( self.last_widget.as_deref() )
+	}
 }
 
-#[allow(unused)]
-fn custom_ui<'a, IA, IS>(text:&'a str, attrs:IA, styles:IA)
-where IA:Iterator<Item=(&'a [u8],Cow<'a,[u8]>)>, IS:Iterator<Item=(&'a [u8], IA)> {
+/// Child element rules of the [`schema`]. Templates and custom widgets have no child
+fn check_children(elem:&Element) -> Result<(),Error> {
+	let count = elem.elements().count();
+	match schema::find_tag(elem.tag()).map( |e| e.children ).unwrap_or(Children::None) {
+		Children::Any => (),
+		Children::Inline => if elem.elements().any( |e| !rich_text::is_inline(e.tag()) ) {
+			return Err(Error::ChildlessElement(elem.span.start))
+		},
+		Children::Two => if count > 2 {
+			return Err(Error::InvalidSplitChildNum(elem.span.start))
+		},
+		Children::One => if count > 1 {
+			return Err(Error::InvalidContainerChildNum(elem.span.start))
+		},
+		Children::Shapes => if let Some(child) = elem.elements().find( |e| !qwidget::drawable::SHAPES.contains(&e.tag()) ) {
+			return Err(Error::MisplacedShape(child.span.start))
+		},
+		Children::Branch => {
+			let (otherwise, then):(Vec<_>,Vec<_>) = elem.elements().partition( |e| e.tag() == "else" );
+			if then.len() != 1 || otherwise.len() > 1 {
+				return Err(Error::InvalidIfChildNum(elem.span.start))
+			}
+		},
+		Children::None => if count > 0 {
+			return Err(Error::ChildlessElement(elem.span.start))
+		}
+	}
+	for child in elem.elements() {
+		check_children(child)?;
+	}
+	Ok(())
+}
+
+
+/// Generate rust source code from xml.
+/// The error is reported as [`Diagnostic`] which has line, column and source snippet.
+/// `<import src="..."/>` is relative to the current directory.
+pub fn compile(xml:&str, wrappers:&HashMap<String,String>) -> Result<String,Diagnostic> {
+	compile_with_dir(xml, Path::new("."), wrappers).map( |e| e.code )
+}
 
+/// Same as [`compile`] but `<import src="..."/>` is relative to `dir`
+pub fn compile_with_dir(xml:&str, dir:&Path, wrappers:&HashMap<String,String>) -> Result<Compiled,Diagnostic> {
+	compile_source(xml, dir, wrappers).map_err( |e| Diagnostic::new(xml, e) )
 }
 
-struct AttributeIter<'a> {
-	attrs : Attributes<'a>
+/// Result of [`compile_with_dir`]. The macros make cargo track the imports and locate the handler errors
+#[derive(Debug,Clone)]
+pub struct Compiled {
+	/// Generated rust source
+	pub code : String,
+	/// Resolved paths of the imported files
+	pub imports : Vec<PathBuf>,
+	/// Function path of the event handler attribute(`onclick` ...) and the span of the attribute.
+	/// Only of the compiled document, not the imports
+	pub handlers : Vec<(String,Span)>,
 }
 
-impl <'a> Iterator for AttributeIter<'a> {
-	type Item = (&'a [u8], Cow<'a,[u8]>);
+fn compile_source(xml:&str, dir:&Path, wrappers:&HashMap<String,String>) -> Result<Compiled,Error> {
+	let doc = Document::parse(xml)?;
+	let (imports, import_paths) = import::load(&doc, dir, None)?;
+	//unknown tag is the native widget function
+	if let Some(e) = schema::validate(&doc, &imports, None).into_iter().next() {
+		return Err(e)
+	}
+	let models = model::collect( imports.iter().chain( std::iter::once(&doc) ) )?;
+	model::check(&doc, &models)?;
+	let styles = Templates::styles(&doc, &imports);
+	let templates = Templates::collect(&doc, &imports, &styles)?;
+	let mut writer = DruidGenerator::new().with_resources( resource::IncludeResolver::new(dir) );
+
+	//models of the imported document are declared where the document is compiled(shared types)
+	for model in models.iter().skip( models.len() - doc.models().count() ) {
+		writer.write_raw( &model.to_rust() )?;
+	}
 
-	fn next(&mut self) -> Option<Self::Item> {
-		while let Some(e) = self.attrs.next() {
-			if let Ok(attr) = e {
-				return Some( (
-					attr.key.into_inner()
-					, attr.value
-				) )
-			}
+	if doc.templates().next().is_none() && doc.models().next().is_some() {
+		//models only
+	} else if !templates.exports.is_empty() {
+		//every exported template is generated as `pub fn` with it's own lens type
+		for fn_name in templates.exports.iter() {
+			write_template(&mut writer, &templates, fn_name, "pub ", wrappers)?;
 		}
-		None
-    }
-}
+		//the main template is still the entry point of the document
+		if let Some(main) = templates.main().filter( |e| !templates.exports.iter().any( |export| export == e ) ) {
+			write_template(&mut writer, &templates, main, "", wrappers)?;
+		}
+	} else if let Some(main) = templates.main() {
+		write_template(&mut writer, &templates, main, "", wrappers)?;
+	} else {
+		return Err(Error::MainWidgetNotFound(0))
+	}
 
+	let mut handlers = vec![];
+	for template in doc.templates() {
+		collect_handlers(template, &mut handlers);
+	}
+	Ok( Compiled { code:writer.into(), imports:import_paths, handlers } )
+}
 
-fn parse_element<'a>(mut src_pos:usize, mut backward:Option<Event<'a>>, reader:&mut Reader<&'a [u8]>) 
--> Result< Option<Element<'a>> , Error> {
-	let mut elem:Option<Element> = None;
-	let mut last_text = None;
-	
-	loop {
-		let event = if let Some(e) = backward.take() {
-			Ok(e)
-		} else {
-			src_pos = reader.buffer_position();
-			reader.read_event()
-		};
-		//println!("{:?}", event);
-		match event {
-			Ok(Event::Start(e)) => {
-				//check parentable element
-				if let Some( el) = elem.as_mut() {
-					let tag = el.tag();
-					let tag = tag.as_ref();
-					if tag == b"flex" {
-						//just ok
-					} else if tag == b"split" {
-						//must be two
-						if el.childs.len() == 2 {
-							return Err(Error::InvalidSplitChildNum(el.src_pos))
-						}
-					} else if tag == b"container" || tag == b"scroll" {
-						//must be one
-						if el.childs.len() == 1 {
-							return Err(Error::InvalidContainerChildNum(el.src_pos))
-						}
-					} else {
-						return Err(Error::ChildlessElement(el.src_pos))
-					}
+/// Handler attributes of the element and the descendants. Template parameter(`${action}`) is substituted later
+fn collect_handlers(elem:&Element, handlers:&mut Vec<(String,Span)>) {
+	for attr in elem.attributes.iter().filter( |e| spec::HANDLERS.contains(&e.name.as_str()) && !e.value.contains("${") ) {
+		handlers.push( (attr.value.trim().to_owned(), attr.span) );
+	}
+	for child in elem.elements() {
+		collect_handlers(child, handlers);
+	}
+}
 
-					if let Some(child) = parse_element(src_pos, Some(Event::Start(e)),reader)? {
-						el.childs.push( child );
-					}
-				} else {
-					let el = Element {
-						src_pos,
-						src_pos_end : reader.buffer_position(),
-						bs : e,
-						text : last_text.take(),
-						childs : vec![]
-					};
-					elem = Some(el);
-				}
-			}
-			Ok(Event::End(e)) => {
-				if let Some(mut el) = elem.as_mut() {
-					//println!("############ {} {}", String::from_utf8_lossy(e.name().as_ref()), String::from_utf8_lossy(el.tag().as_ref()));
-					//check matching tag start and end
-					if e.name().as_ref() != el.tag().as_ref() {
-						return Err(Error::InvalidCloseTag(src_pos))
-					} 					
-					el.src_pos_end = reader.buffer_position();
-					el.text = last_text.take();			
-				} else {
-					return Err(Error::InvalidCloseTag(src_pos))
-				}
-				break
-			}
-			Ok(Event::Text(t)) => {
-				last_text = Some(t);				
-			}
-			Ok(Event::Comment(_)) => (), //ignore
-			Ok(Event::Empty(e)) => {
-				//like <tag name=value .. />
-
-				//check parentable element
-				if let Some( el) = elem.as_mut() {
-					let tag = el.tag();
-					let tag = tag.as_ref();
-					if tag == b"flex" {
-						//just ok
-					} else if tag == b"split" {
-						//must be two
-						if el.childs.len() == 2 {
-							return Err(Error::InvalidSplitChildNum(el.src_pos))
-						}
-					} else if tag == b"container" || tag == b"scroll" {
-						//must be one
-						if el.childs.len() == 1 {
-							return Err(Error::InvalidContainerChildNum(el.src_pos))
-						}
-					} else {
-						return Err(Error::ChildlessElement(el.src_pos))
-					}
+/// `{vis}fn {fn_name}() -> impl druid::Widget<{lens}>`
+fn write_template(writer:&mut DruidGenerator, templates:&Templates, fn_name:&str, vis:&str, wrappers:&HashMap<String,String>) -> Result<(),Error> {
+	let template = templates.elem_map.get(fn_name).ok_or( Error::MainWidgetNotFound(0) )?;
+	let lens = template.elem.attrs(None).get_result("lens")?;
+	let lens = String::from_utf8_lossy( lens.as_ref() );
+	writer.write_raw(&format!("{vis}fn {fn_name}() -> impl druid::Widget<{lens}> {{\n") )?;
+	writer.write(&templates.elem_map, template, &templates.style, wrappers)?;
+	writer.write_raw("}\n")
+}
 
-					if let Some(child) = parse_element(src_pos, Some(Event::Empty(e)),reader)? {
-						el.childs.push( child );
-					}
-				} else {
-					let el = Element {
-						src_pos,
-						src_pos_end : reader.buffer_position(),
-						bs : e,
-						text : last_text.take(),
-						childs : vec![]
-					};
-					elem = Some(el);
-
-					//different to `Event::Start`
-					break
-				}
-			}, //ignore
-			Ok(Event::Eof) => {
-				if elem.is_some() {
-					return Err(Error::InvalidCloseTag(src_pos))
-				} else {
-					break
-				}
-			},
-			Err(e) => return Err(Error::XMLSyntaxError( (src_pos,e) )),
-			_etc@ _ => {
-				unimplemented!()
-			}
-		}
+/// `export="true"` or any template under `<druid-xml>` document root(unless `export="false"`)
+pub(crate) fn is_exported(elem:&Element, document_root:bool) -> Result<bool, Error> {
+	match elem.attributes.get("export") {
+		None => Ok(document_root),
+		Some("true") => Ok(true),
+		Some("false") => Ok(false),
+		Some(_) => Err(Error::InvalidAttributeValue( (elem.span.start, "export") ))
 	}
-	
-	Ok( elem )
+}
+
+#[allow(unused)]
+fn custom_ui<'a, IA, IS>(text:&'a str, attrs:IA, styles:IA)
+where IA:Iterator<Item=(&'a [u8],Cow<'a,[u8]>)>, IS:Iterator<Item=(&'a [u8], IA)> {
+
 }
 
 #[cfg(target_arch="wasm32")]
@@ -494,30 +689,13 @@ pub fn show_preview() {
 						let src = get_xml_src();
 						if !src.is_empty() {
 							match dynamic::generate_widget( &src ) {
-								Ok(widget) => {
+								Ok( (widget, _) ) => {
 									self.child = Some( widget );
 									xml_error("",0,"");
 									ctx.children_changed();
 								},
-								Err(e) => {
-									match e {
-										Error::InvalidFlexChildNum( s ) => xml_error("InvalidFlexChildNum", s, ""),
-										Error::InvalidSplitChildNum( s ) => xml_error("InvalidSplitChildNum", s, ""),
-										Error::InvalidContainerChildNum( s ) => xml_error("InvalidContainerChildNum", s, ""),
-										Error::InvalidScrollChildNum( s ) => xml_error("InvalidScrollChildNum", s, ""),
-										Error::CloseWithoutStart( s ) => xml_error("CloseWithoutStart", s, ""),
-										Error::InvalidCloseTag(s) => xml_error("InvalidCloseTag", s, ""),
-										Error::AttributeRequired( (s, n)) => xml_error("AttributeRequired", s, n),
-										Error::InvalidAttributeValue( (s, n) ) => xml_error("InvalidAttributeValue", s, n),
-										Error::InvalidSizeAttributeValue( s ) => xml_error("InvalidSizeAttributeValue", s, ""),
-										Error::InvalidBorderAttributeValue( s ) => xml_error("InvalidBorderAttributeValue", s, ""),
-										Error::ChildlessElement(s) => xml_error("ChildlessElement", s, ""),
-										Error::UnknownAttribute(s) => xml_error("UnknownAttribute", s, ""),
-										Error::UnknownTag( (s,e) ) => xml_error("UnknownTag", s, &e),
-										Error::InvalidTopElement(s) => xml_error("InvalidTopElement", s, ""),
-										Error::CSSSyntaxError( (s, e) ) => xml_error("CSSSyntaxError", s, &format!("{:?}",e) ),
-										Error::XMLSyntaxError( (s, e) ) => xml_error("XMLSyntaxError", s, &format!("{:?}",e) ),
-									}
+								Err(diagnostic) => {
+									xml_error(diagnostic.error.name(), diagnostic.start, &diagnostic.to_string());
 								}
 							}
 						}
@@ -623,7 +801,7 @@ mod test {
 		let result = super::compile( src, &HashMap::new() );
 		match result {
 			Ok(compiled) => println!("{}", compiled),
-			Err(e) => { println!("Error : {:?} : {}", e, &src[e.error_at() .. ])}
+			Err(e) => { println!("Error : {}", e)}
 		}
 	}
 
@@ -638,10 +816,82 @@ mod test {
 		let result = super::compile( src, &HashMap::new() );
 		match result {
 			Ok(compiled) => println!("{}", compiled),
-			Err(e) => { println!("Error : {:?} : {}", e, &src[e.error_at() .. ])}
+			Err(e) => { println!("Error : {}", e)}
 		}
 	}
 
+	#[test]
+	fn validator() {
+		let src = r#"
+		<style>
+		label { color:#333333 }
+		button {color:black; background-color:white}
+		textbox {color:black; background-color:gray}
+		#pwd {color:white; background-color:black}
+		</style>
+
+		<!-- you can remove direction="column" attribute because that default value is "row" -->
+		<flex fn="build_icon" direction="row">
+			<label style="font-size:25px">${icon_text}</label>
+			<label style="font-size:10px">${title}</label>
+		</flex>
+
+		<flex fn="build_main" lens="Login">
+			<label style="color:black; font-size:12em">Login..</label>
+
+			<widget name="native_custom_widget" title="GO"/>
+			<widget name="native_custom_widget" title="MAIN"/>
+			<widget name="native_custom_widget" title="NO"/>
+			<build_icon title="Exit" icon_text="★" onclick="exit"/>
+	
+			<flex direction="column" cross_axis_alignment="start" axis_alignment="center" must_fill_main_axis="true">
+				<label>ID</label><textbox class="normal" lens="Login::id" value="Default Value" placeholder="Input here"/>
+				<label>PWD</label><textbox id="pwd" lens="Login::pwd" placeholder="Your password"/>
+			</flex>
+
+			<flex>
+				<button onclick="login">OK</button>
+				<button style="background-color:red; color:white">CANCEL</button>
+			</flex>
+		</flex>
+		"#;
+		let compiled = super::compile( src, &HashMap::new() ).unwrap();
+		println!("{}", compiled);
+		assert!( compiled.contains("flex.set_cross_axis_alignment(druid::widget::CrossAxisAlignment::Start);") );
+		assert!( compiled.contains("flex.set_main_axis_alignment(druid::widget::MainAxisAlignment::Center);") );
+		assert!( compiled.contains("flex = flex.must_fill_main_axis(true);") );
+		assert!( compiled.contains("textbox.set_placeholder(\"Input here\");") );
+
+		//misspelled attributes of the `test` fixture
+		let old = src.replace("cross_axis_alignment=", "cross_alignment=");
+		assert_eq!( super::compile(&old, &HashMap::new()).unwrap_err().error.name(), "UnknownAttribute" );
+		let old = src.replace("must_fill_main_axis=", "fill_major_axis=");
+		assert_eq!( super::compile(&old, &HashMap::new()).unwrap_err().error.name(), "UnknownAttribute" );
+
+		let src = r#"
+		<flex fn="build_main" lens="()">
+			<label>Hello Druid!</label>
+			<button>OK</button>
+		</flex>
+        "#;
+		let compiled = super::compile( src, &HashMap::new() ).unwrap();
+		assert!( compiled.contains("fn build_main() -> impl druid::Widget<()>") );
+		assert!( compiled.contains("druid::widget::Label::new(\"Hello Druid!\")") );
+		assert!( compiled.contains("druid::widget::Button::from_label(label)") );
+	}
+
+	#[test]
+	fn diagnostic() {
+		let src = "<flex fn=\"build_main\" lens=\"()\">\n\t<split>\n\t\t<label>1</label>\n\t\t<label>2</label>\n\t\t<label>3</label>\n\t</split>\n</flex>";
+		let diagnostic = super::compile( src, &HashMap::new() ).unwrap_err();
+		println!("{}", diagnostic);
+		assert!( matches!(diagnostic.error, super::Error::InvalidSplitChildNum(_)) );
+		assert_eq!( (diagnostic.line, diagnostic.column), (2, 2) );
+		assert_eq!( diagnostic.path, vec!["flex[fn=build_main]", "split"] );
+		assert_eq!( &src[diagnostic.start .. diagnostic.end], "<split>" );
+		assert!( diagnostic.to_string().starts_with("line 2, col 2: split needs exactly two children") );
+	}
+
 	#[test]
 	fn transition() {
 		let src = r#"
@@ -680,7 +930,7 @@ mod test {
 		let result = super::compile( src, &HashMap::new() );
 		match result {
 			Ok(compiled) => println!("{}", compiled),
-			Err(e) => { println!("Error : {:?} : {}", e, &src[e.error_at() .. ])}
+			Err(e) => { println!("Error : {}", e)}
 		}
 	}
 
@@ -715,7 +965,7 @@ mod test {
 		let result = super::compile( src, &HashMap::new() );
 		match result {
 			Ok(compiled) => println!("{}", compiled),
-			Err(e) => { println!("Error : {:?} : {}", e, &src[e.error_at() .. ])}
+			Err(e) => { println!("Error : {}", e)}
 		}
 	}
 
@@ -834,4 +1084,932 @@ mod test {
 		println!("{}",result.unwrap());
 	}
 
-}
\ No newline at end of file
+	#[test]
+	fn export() {
+		let src = r#"
+		<druid-xml>
+			<style>
+			label { color:black }
+			</style>
+
+			<label fn="title" export="false">${text}</label>
+
+			<flex fn="build_login" lens="LoginInfo">
+				<title text="Login"/>
+				<textbox lens="LoginInfo::id"/>
+			</flex>
+
+			<flex fn="build_about" lens="()">
+				<title text="About"/>
+			</flex>
+		</druid-xml>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		println!("{}", compiled);
+		assert!( compiled.contains("pub fn build_login() -> impl druid::Widget<LoginInfo>") );
+		assert!( compiled.contains("pub fn build_about() -> impl druid::Widget<()>") );
+		assert!( !compiled.contains("fn title()") );
+
+		//`export` attribute without document root
+		let src = r#"
+		<flex fn="build_side" lens="Side" export="true"><label>Side</label></flex>
+		<flex fn="build_main" lens="()"><label>Main</label></flex>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		assert!( compiled.contains("pub fn build_side() -> impl druid::Widget<Side>") );
+		//exporting a template keeps the entry point
+		assert!( compiled.contains("\nfn build_main() -> impl druid::Widget<()>") );
+		assert!( !compiled.contains("pub fn build_main()") );
+		//the entry point is the last template as without `export`
+		let last = src.replace(r#"<flex fn="build_side" lens="Side" export="true"><label>Side</label></flex>"#, "") + r#"<flex fn="build_side" lens="Side" export="true"><label>Side</label></flex>"#;
+		let compiled = super::compile(&last, &HashMap::new()).unwrap();
+		assert!( compiled.contains("pub fn build_side() -> impl druid::Widget<Side>") );
+		assert!( !compiled.contains("fn build_main()") );
+	}
+
+	#[test]
+	fn document() {
+		let src = r#"<?xml version="1.0"?>
+<!-- login -->
+<style>
+label > b { color:black }
+</style>
+<flex fn="build_main" lens="()" direction="column">
+	<label id="title">Tom &amp; Jerry</label>
+	<textbox placeholder="&quot;id&quot;"/>
+	<label><![CDATA[<raw>]]></label>
+</flex>
+"#;
+		let mut doc = super::Document::parse(src).unwrap();
+		assert_eq!( doc.to_xml(), src );
+
+		let main = doc.template("build_main").unwrap();
+		assert_eq!( main.attributes.iter().map( |e| e.name.as_str() ).collect::<Vec<_>>(), vec!["fn","lens","direction"] );
+		let title = main.elements().next().unwrap();
+		assert_eq!( title.text(), "Tom & Jerry" );
+		assert_eq!( &src[title.span.start .. title.span.end], r#"<label id="title">Tom &amp; Jerry</label>"# );
+		assert_eq!( &src[title.attributes.find("id").unwrap().span.start .. title.attributes.find("id").unwrap().span.end], r#"id="title""# );
+		assert_eq!( doc.element_at( src.find("<textbox").unwrap() + 3 ).unwrap().tag(), "textbox" );
+
+		//edit and save
+		let main = doc.template_mut("build_main").unwrap();
+		main.attributes.set("direction", "row");
+		main.attributes.remove("lens");
+		let mut button = super::Element::new("button");
+		button.children.push( super::Node::Text( super::Text::new("OK") ) );
+		main.children.push( super::Node::Element(button) );
+		let saved = doc.to_xml();
+		println!("{}", saved);
+		assert!( saved.contains(r#"<flex fn="build_main" direction="row">"#) );
+		assert!( saved.contains("<button>OK</button></flex>") );
+		assert_eq!( super::Document::parse(&saved).unwrap().to_xml(), saved );
+
+		//close tag
+		match super::Document::parse("<flex><label>A</flex>") {
+			Err(super::Error::InvalidCloseTag(pos)) => assert_eq!(pos, 14),
+			e @ _ => panic!("{:?}", e)
+		}
+	}
+
+	#[test]
+	fn rich_text() {
+		let src = r#"
+		<flex fn="build_main" lens="()">
+			<label>
+				Hello <b>world</b><br/>
+				<span style="color:red; font-size:20px">!</span>
+			</label>
+			<button><i>OK</i></button>
+		</flex>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		println!("{}", compiled);
+		assert!( compiled.contains(r#"druid::text::RichText::new("Hello world\n!".into())"#) );
+		assert!( compiled.contains(".with_attribute(6..11, druid::text::Attribute::weight(druid::FontWeight::BOLD))") );
+		assert!( compiled.contains(".with_attribute(12..13, druid::text::Attribute::text_color(druid::Color::rgba8(255,0,0,255)))") );
+		assert!( compiled.contains(".with_attribute(12..13, druid::text::Attribute::size(20.0f64))") );
+		assert!( compiled.contains("druid_xml::widget::DXButton::from_label( druid_xml::widget::DXLabel::rich(rich) )") );
+
+		let invalid = src.replace("font-size:20px", "font-size:big");
+		assert_eq!( super::compile(&invalid, &HashMap::new()).unwrap_err().line, 5 );
+		assert!( super::dynamic::generate_widget(&invalid).is_err() );
+
+		//block element is not available in the label
+		let src = r#"<flex fn="build_main" lens="()"><label>A<flex><label>B</label></flex></label></flex>"#;
+		match super::compile(src, &HashMap::new()) {
+			Err(e) => assert_eq!(e.error.name(), "ChildlessElement"),
+			Ok(_) => panic!("must be failed")
+		}
+	}
+
+	#[test]
+	fn schema() {
+		let src = r#"<flex fn="build_main" lens="()" cross_alignment="center"><label>A</label></flex>"#;
+		match super::compile(src, &HashMap::new()) {
+			Err(e) => {
+				println!("{}", e);
+				assert_eq!( e.error.to_string(), "unknown attribute `cross_alignment`, did you mean `cross_axis_alignment`?" );
+				assert_eq!( &src[e.start .. e.end], r#"cross_alignment="center""# );
+			},
+			Ok(_) => panic!("must be failed")
+		}
+
+		let src = r#"<flex fn="build_main" lens="()" direction="colum"><label>A</label></flex>"#;
+		match super::compile(src, &HashMap::new()) {
+			Err(e) => assert_eq!( e.error.to_string(), "invalid value `colum` for `direction` (expected one of `row`, `column`), did you mean `column`?" ),
+			Ok(_) => panic!("must be failed")
+		}
+
+		//typo of the built-in tag. other unknown tag is the native widget function
+		let src = r#"<flex fn="build_main" lens="()"><lable>A</lable><my_widget/></flex>"#;
+		let errors = super::schema::validate(&super::Document::parse(src).unwrap(), &[], None);
+		assert_eq!( errors.len(), 1 );
+		assert_eq!( errors[0].to_string(), "unknown tag `lable`, did you mean `label`?" );
+		let errors = super::schema::validate(&super::Document::parse(src).unwrap(), &[], Some(&[]));
+		assert_eq!( errors.len(), 2 );
+
+		//template parameter is not checked
+		let src = r#"<label fn="title" line-break="${wrap}">T</label><flex fn="build_main" lens="()"><title wrap="clip"/></flex>"#;
+		assert!( super::compile(src, &HashMap::new()).is_ok() );
+
+		//size is the same as the style size
+		let src = r#"<flex fn="build_main" lens="()" width="10em"><label>A</label></flex>"#;
+		assert!( super::compile(src, &HashMap::new()).unwrap().contains(", 160.0f64);") );
+		let src = r#"<flex fn="build_main" lens="()"><container><label>A</label></container></flex>"#;
+		assert!( super::schema::validate(&super::Document::parse(src).unwrap(), &[], Some(&[]))[0].to_string().starts_with("unknown tag `container`") );
+	}
+
+	#[test]
+	fn import() {
+		let dir = std::env::temp_dir().join( format!("druid-xml-import-{}", std::process::id()) );
+		std::fs::create_dir_all(dir.join("common")).unwrap();
+		std::fs::write(dir.join("common/widgets.xml"), r#"
+		<import src="../colors.xml"/>
+		<label fn="title" class="title">${text}</label>
+		"#).unwrap();
+		std::fs::write(dir.join("colors.xml"), "<style> .title { color:#ff0000 } </style>").unwrap();
+
+		let src = r#"
+		<import src="common/widgets.xml"/>
+		<flex fn="build_main" lens="()">
+			<title text="Imported"/>
+		</flex>
+		"#;
+		let compiled = super::compile_with_dir(src, &dir, &HashMap::new()).unwrap();
+		println!("{}", compiled.code);
+		assert!( compiled.code.contains("Imported") );
+		assert!( compiled.code.contains("druid::Color::rgba8(255,0,0,255)") );
+		//dependencies first
+		assert_eq!( compiled.imports, vec![dir.join("colors.xml").canonicalize().unwrap(), dir.join("common/widgets.xml").canonicalize().unwrap()] );
+
+		//cycle
+		std::fs::write(dir.join("a.xml"), r#"<import src="b.xml"/>"#).unwrap();
+		std::fs::write(dir.join("b.xml"), r#"<import src="a.xml"/>"#).unwrap();
+		let src = r#"<import src="a.xml"/><flex fn="build_main" lens="()"/>"#;
+		let e = super::compile_with_dir(src, &dir, &HashMap::new()).unwrap_err();
+		println!("{}", e);
+		assert_eq!( e.error.name(), "ImportError" );
+		assert_eq!( e.message, "in `a.xml` line 1, col 1: in `b.xml` line 1, col 1: import cycle detected at `a.xml`" );
+
+		let src = r#"<import src="none.xml"/><flex fn="build_main" lens="()"/>"#;
+		let e = super::compile_with_dir(src, &dir, &HashMap::new()).unwrap_err();
+		assert_eq!( e.error.name(), "ImportNotFound" );
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn param() {
+		let src = r#"
+		<label fn="title">
+			<param name="text" default="Untitled"/>
+			<param name="size" type="f64" default="12"/>
+			${text}
+		</label>
+		<textbox fn="field" placeholder="${hint:Input here}"/>
+		<flex fn="build_main" lens="()">
+			<title/>
+			<title text="Hello" size="20"/>
+			<field/>
+		</flex>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		println!("{}", compiled);
+		assert!( compiled.contains("Untitled") );
+		assert!( compiled.contains("Hello") );
+		assert!( compiled.contains("Input here") );
+		assert!( !compiled.contains("${") );
+
+		let fails = [
+			(r#"<label fn="title"><param name="text"/>${text}</label><flex fn="build_main" lens="()"><title/></flex>"#, "template parameter `text` is required"),
+			(r#"<label fn="title">${text}</label><flex fn="build_main" lens="()"><title/></flex>"#, "template parameter `text` is required"),
+			(r#"<label fn="title"><param name="size" type="f64"/>T</label><flex fn="build_main" lens="()"><title size="big"/></flex>"#, "template parameter `size` must be `f64`"),
+			(r#"<label fn="title"><param name="on" type="bool" default="yes"/>T</label><flex fn="build_main" lens="()"><title/></flex>"#, "template parameter `on` must be `bool`"),
+			(r#"<flex fn="build_main" lens="()"><flex><param name="text"/></flex></flex>"#, "`param` is available only as the direct child of the template"),
+		];
+		for (src, message) in fails {
+			match super::compile(src, &HashMap::new()) {
+				Err(e) => assert_eq!( e.message, message ),
+				Ok(_) => panic!("must be failed : {}", src)
+			}
+		}
+	}
+
+	#[test]
+	fn interpolation() {
+		use super::template::{segments, Segment};
+		assert_eq!( segments("Hello ${name}, $${literal} ${n:1}"), vec![
+			Segment::Text("Hello "), Segment::Param("name", None), Segment::Text(", "),
+			Segment::Text("${"), Segment::Text("literal} "), Segment::Param("n", Some("1"))
+		]);
+
+		let src = r#"
+		<flex fn="row">
+			<label>Hello ${name}, you have ${count:0} items</label>
+			<textbox placeholder="Enter ${field}"/>
+			<label>Price : $${price}</label>
+		</flex>
+		<flex fn="build_main" lens="()">
+			<row name="Tom" count="3" field="id"/>
+		</flex>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		println!("{}", compiled);
+		assert!( compiled.contains(r#"Label::new("Hello Tom, you have 3 items")"#) );
+		assert!( compiled.contains(r#"set_placeholder("Enter id")"#) );
+		assert!( compiled.contains(r#"Label::new("Price : ${price}")"#) );
+		assert!( super::dynamic::generate_widget(src).is_ok() );
+
+		let src = r#"<label fn="title">Hello ${name}</label><flex fn="build_main" lens="()"><title/></flex>"#;
+		assert_eq!( super::compile(src, &HashMap::new()).unwrap_err().message, "template parameter `name` is required" );
+	}
+
+	#[test]
+	fn binding() {
+		use super::bind::{parse, Part, Binding};
+		assert_eq!( parse("Count: {AppState::count}"), Some(vec![Part::Text("Count: ".to_owned()), Part::Bind(Binding { path:"AppState::count", spec:None })]) );
+		assert_eq!( parse("{price:.2} {{x}}"), Some(vec![Part::Bind(Binding { path:"price", spec:Some(".2") }), Part::Text(" {x}".to_owned())]) );
+		assert_eq!( parse("{ not binding } ${x}"), None );
+
+		let src = r#"
+		<flex fn="build_main" lens="AppState">
+			<label>Count: {AppState::count}</label>
+			<label style="color:red">{price:.2}</label>
+			<button text-lens="AppState::status"/>
+			<label>{{literal}}</label>
+		</flex>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		println!("{}", compiled);
+		assert!( compiled.contains(r#"druid::widget::Label::dynamic(|data, _env| format!("Count: {}", druid::LensExt::get(&AppState::count, data)))"#) );
+		assert!( compiled.contains(r#"druid_xml::widget::DXLabel::dynamic(|data, _env| format!("{:.2}", druid::LensExt::get(&AppState::price, data)))"#) );
+		assert!( compiled.contains(r#"druid::widget::Label::dynamic(|data, _env| format!("{}", druid::LensExt::get(&AppState::status, data)))"#) );
+		assert!( compiled.contains(r#"druid::widget::Label::new("{literal}")"#) );
+
+		//unqualified field is the field of the template type. `<model>` checks it
+		let total = r#"<flex fn="build_main" lens="App"><label>Total {price:.2}, press {{enter}}</label></flex>"#;
+		assert!( super::compile(total, &HashMap::new()).unwrap().contains(r#"druid::widget::Label::dynamic(|data, _env| format!("Total {:.2}, press {{enter}}", druid::LensExt::get(&App::price, data)))"#) );
+		let model = r#"<model name="App"><field name="price" type="f64"/></model>"#;
+		assert!( super::compile(&format!("{model}{total}"), &HashMap::new()).is_ok() );
+		let typo = format!("{model}{}", total.replace("{price:.2}", "{prise:.2}"));
+		assert_eq!( super::compile(&typo, &HashMap::new()).unwrap_err().error.to_string(), "model `App` has no field `prise`, did you mean `price`?" );
+
+		//format spec goes to `format!`
+		use super::bind::FormatSpec;
+		assert_eq!( FormatSpec::parse("*^8.2"), Some(FormatSpec { fill:'*', align:Some('^'), width:Some(8), precision:Some(2) }) );
+		assert_eq!( FormatSpec::parse(">5"), Some(FormatSpec { fill:' ', align:Some('>'), width:Some(5), precision:None }) );
+		for invalid in ["x", "+", "#?", "05", "{<3", ".", ".x", "5.2e"] {
+			assert_eq!( FormatSpec::parse(invalid), None, "{invalid}" );
+		}
+		assert_eq!( FormatSpec::parse("-^7").unwrap().pad("ab".to_owned(), false), "--ab---" );
+		assert_eq!( FormatSpec::parse("5").unwrap().pad("42".to_owned(), true), "   42" );
+		let invalid = src.replace("{price:.2}", "{price:?}");
+		let e = super::compile(&invalid, &HashMap::new()).unwrap_err();
+		assert_eq!( e.error.to_string(), "invalid format spec `?`. `[[fill]align][width][.precision]` is available" );
+		assert!( super::dynamic::generate_widget(&invalid).is_err() );
+
+		//field of the used template is the field of it's own `lens` type
+		let src = r#"
+		<flex fn="card" lens="User">
+			<if lens="active"><label>Active</label></if>
+		</flex>
+		<flex fn="build_main" lens="App">
+			<card lens="App::user"/>
+		</flex>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		println!("{}", compiled);
+		assert!( compiled.contains("druid::LensExt::get(&User::active, data)") );
+		assert!( compiled.contains("let custom_widget = druid::WidgetExt::lens(custom_widget, App::user);") );
+	}
+
+	#[test]
+	fn conditional() {
+		let src = r#"
+		<flex fn="build_main" lens="App">
+			<if lens="App::logged_in">
+				<label>Welcome</label>
+				<else><button>Login</button></else>
+			</if>
+			<if lens="busy" style="padding:5px">
+				<spinner/>
+			</if>
+		</flex>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		println!("{}", compiled);
+		assert!( compiled.contains("let either = druid::widget::Either::new(|data, _env| druid::LensExt::get(&App::logged_in, data), then, otherwise);") );
+		assert!( compiled.contains("druid::LensExt::get(&App::busy, data)") );
+		assert!( compiled.contains("let otherwise = druid::widget::SizedBox::empty();") );
+		assert!( !compiled.contains("druid::WidgetExt::lens(either") );
+		assert!( super::dynamic::generate_widget(src).is_ok() );
+
+		//`preview` is the branch of the dynamic preview. the generated code ignores it
+		let preview = src.replace(r#"<if lens="App::logged_in">"#, r#"<if lens="App::logged_in" preview="else">"#);
+		assert!( super::compile(&preview, &HashMap::new()).unwrap().contains("let either = druid::widget::Either::new(|data, _env| druid::LensExt::get(&App::logged_in, data), then, otherwise);") );
+		assert!( super::dynamic::generate_widget(&preview).is_ok() );
+		let invalid = src.replace(r#"<if lens="App::logged_in">"#, r#"<if lens="App::logged_in" preview="otherwise">"#);
+		assert_eq!( super::compile(&invalid, &HashMap::new()).unwrap_err().error.name(), "InvalidEnumValue" );
+		assert!( super::dynamic::generate_widget(&invalid).is_err() );
+
+		let two = r#"<flex fn="build_main" lens="App"><if lens="App::a"><label>A</label><label>B</label></if></flex>"#;
+		assert_eq!( super::compile(two, &HashMap::new()).unwrap_err().error.name(), "InvalidIfChildNum" );
+		let misplaced = r#"<flex fn="build_main" lens="App"><else><label>A</label></else></flex>"#;
+		assert_eq!( super::compile(misplaced, &HashMap::new()).unwrap_err().error.name(), "MisplacedElse" );
+	}
+
+	#[test]
+	fn list() {
+		let src = r#"
+		<flex fn="item_row" lens="Item">
+			<label>{name}</label>
+			<checkbox lens="Item::done"/>
+		</flex>
+		<flex fn="build_main" lens="App">
+			<list lens="App::items" item="item_row" direction="horizontal" spacing="4px"/>
+		</flex>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		println!("{}", compiled);
+		assert!( compiled.contains("let mut list = druid::widget::List::new(|| {") );
+		assert!( compiled.contains("druid::LensExt::get(&Item::name, data)") );
+		assert!( compiled.contains("druid::WidgetExt::lens(checkbox, Item::done)") );
+		assert!( compiled.contains("list = list.horizontal();") );
+		assert!( compiled.contains("list = list.with_spacing(4f64);") );
+		assert!( compiled.contains("let list = druid::WidgetExt::lens(list, App::items);") );
+		assert!( super::dynamic::generate_widget(src).is_ok() );
+
+		let unknown = r#"
+		<flex fn="item_row" lens="Item"><label>A</label></flex>
+		<flex fn="build_main" lens="App"><list lens="App::items" item="item_rwo"/></flex>
+		"#;
+		let e = super::compile(unknown, &HashMap::new()).unwrap_err();
+		assert_eq!( e.message, "unknown template `item_rwo`, did you mean `item_row`?" );
+	}
+
+	#[test]
+	fn handler() {
+		let src = r#"
+		<flex fn="build_main" lens="App">
+			<button onclick="login">OK</button>
+			<textbox lens="App::id" onchange="handlers::id_changed" onfocus="focused" onblur="blurred"/>
+			<label ondblclick="reset">Reset</label>
+		</flex>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		println!("{}", compiled);
+		assert!( compiled.contains("let button = druid::WidgetExt::on_click(button, login);") );
+		assert!( compiled.contains("let textbox = druid::WidgetExt::controller(textbox, druid_xml::widget::EventHandler::new().on_change(handlers::id_changed).on_focus(focused).on_blur(blurred));\n\t\tlet textbox = druid::WidgetExt::lens(textbox, App::id);") );
+		assert!( compiled.contains("druid_xml::widget::EventHandler::new().on_dblclick(reset)") );
+
+		//the macro locates the unknown function with the span
+		let handlers = super::compile_with_dir(src, std::path::Path::new("."), &HashMap::new()).unwrap().handlers;
+		let handlers = handlers.iter().map( |(path, span)| (path.as_str(), &src[span.start..span.end]) ).collect::<Vec<_>>();
+		assert_eq!( handlers, vec![("login", r#"onclick="login""#), ("handlers::id_changed", r#"onchange="handlers::id_changed""#), ("focused", r#"onfocus="focused""#), ("blurred", r#"onblur="blurred""#), ("reset", r#"ondblclick="reset""#)] );
+
+		let invalid = r#"<flex fn="build_main" lens="App"><button onclick="login()">OK</button></flex>"#;
+		let e = super::compile(invalid, &HashMap::new()).unwrap_err();
+		assert_eq!( e.message, "invalid value for `onclick`" );
+
+		//the runtime widget calls the registered functions
+		match super::dynamic::generate_widget(src) {
+			Err(e) => assert_eq!( e.message, "handler `login` of `onclick` is not registered. the runtime widget needs `WidgetRegistry::register_handler`" ),
+			Ok(_) => panic!("must be failed")
+		}
+		fn ignore(_:&mut druid::EventCtx, _:&mut super::dynamic::JSValue, _:&druid::Env) {}
+		let mut registry = super::dynamic::WidgetRegistry::new();
+		for path in ["login", "handlers::id_changed", "focused", "blurred", "reset"] {
+			registry.register_handler(path, ignore);
+		}
+		assert!( registry.generate_widget(src).is_ok() );
+	}
+
+	#[test]
+	fn command() {
+		let src = r#"
+		<flex fn="build_main" lens="App">
+			<button command="crate::cmds::SAVE">Save</button>
+			<button command="crate::cmds::OPEN" command-payload="42" target="window">Open</button>
+			<button command="crate::cmds::RESET" target="#editor">Reset</button>
+			<textbox id="editor" lens="App::text"/>
+		</flex>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		println!("{}", compiled);
+		assert!( compiled.contains("druid::WidgetExt::on_click(button, |ctx, _data, _env| ctx.submit_command(druid::Command::from(crate::cmds::SAVE)));") );
+		assert!( compiled.contains("ctx.submit_command(crate::cmds::OPEN.with(42).to(druid::Target::Window(ctx.window_id())))") );
+		assert!( compiled.contains(r#"ctx.submit_command(druid::Command::from(crate::cmds::RESET).to(druid::Target::Widget(druid_xml::widget::widget_id("editor"))))"#) );
+		assert!( compiled.contains(r#"let textbox = druid::WidgetExt::with_id(textbox, druid_xml::widget::widget_id("editor"));"#) );
+
+		let invalid = r#"<flex fn="build_main" lens="App"><button command="crate::cmds::SAVE" target="app">Save</button></flex>"#;
+		assert_eq!( super::compile(invalid, &HashMap::new()).unwrap_err().message, "invalid value for `target`" );
+
+		//every instance would be the same target
+		let editor = r#"<textbox fn="editor" id="editor" lens="App::text"/>"#;
+		let once = format!(r#"{editor}<flex fn="build_main" lens="App"><button command="crate::cmds::RESET" target="#editor">Reset</button><editor/></flex>"#);
+		assert!( super::compile(&once, &HashMap::new()).is_ok() );
+		let twice = once.replace("<editor/>", "<editor/><editor/>");
+		assert_eq!( super::compile(&twice, &HashMap::new()).unwrap_err().error.name(), "ReusedTarget" );
+		let item = format!(r#"<flex fn="row" lens="Item">{editor}</flex><flex fn="build_main" lens="App"><button command="crate::cmds::RESET" target="#editor">Reset</button><list lens="App::items" item="row"/></flex>"#).replace(r#"fn="editor" "#, "");
+		assert_eq!( super::compile(&item, &HashMap::new()).unwrap_err().error.name(), "ReusedTarget" );
+
+		//the runtime widget submits the registered commands
+		match super::dynamic::generate_widget(src) {
+			Err(e) => assert_eq!( e.message, "command `crate::cmds::SAVE` is not registered. the runtime widget needs `WidgetRegistry::register_command`" ),
+			Ok(_) => panic!("must be failed")
+		}
+		let mut registry = super::dynamic::WidgetRegistry::new();
+		for path in ["crate::cmds::SAVE", "crate::cmds::OPEN", "crate::cmds::RESET"] {
+			registry.register_command(path, |_| druid::Command::from(druid::commands::CLOSE_WINDOW));
+		}
+		assert!( registry.generate_widget(src).is_ok() );
+		assert!( registry.generate_widget(&once).is_ok() );
+		match registry.generate_widget(&twice) {
+			Err(e) => assert_eq!( e.error.name(), "ReusedTarget" ),
+			Ok(_) => panic!("must be failed")
+		}
+
+		use super::widget::widget_id;
+		assert_eq!( widget_id("editor"), widget_id("editor") );
+		assert_ne!( widget_id("editor"), widget_id("viewer") );
+	}
+
+	#[test]
+	fn lens_path() {
+		use super::lens::{LensPath, Segment};
+		let path = LensPath::parse("App::user.address[1].city | string").unwrap();
+		assert_eq!( path.root, "App::user" );
+		assert_eq!( path.segments, vec![Segment::Field("address".to_owned()), Segment::Index(1), Segment::Field("city".to_owned())] );
+		assert_eq!( path.conversions, vec!["string".to_owned()] );
+		assert!( LensPath::parse("App::user.").is_none() );
+		assert!( LensPath::parse("App::items[x]").is_none() );
+		assert!( !LensPath::parse("App::name").unwrap().is_compound() );
+
+		let src = r#"
+		<flex fn="build_main" lens="App">
+			<textbox lens="App::user.address.city"/>
+			<checkbox lens="App::flags[2]"/>
+			<slider lens="volume | percent"/>
+			<switch lens="App::muted | not | crate::conv::inverse"/>
+			<label>{App::user.name}</label>
+		</flex>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		println!("{}", compiled);
+		assert!( compiled.contains("druid::WidgetExt::lens(textbox, druid_xml::lens::field(druid_xml::lens::field(App::user, |data| &data.address, |data| &mut data.address), |data| &data.city, |data| &mut data.city));") );
+		assert!( compiled.contains("druid::WidgetExt::lens(checkbox, druid::LensExt::index(App::flags, 2));") );
+		assert!( compiled.contains("druid::WidgetExt::lens(slider, druid::LensExt::then(App::volume, druid_xml::lens::percent()));") );
+		assert!( compiled.contains("druid::LensExt::then(druid::LensExt::then(App::muted, druid_xml::lens::not()), crate::conv::inverse)") );
+		assert!( compiled.contains("druid::LensExt::get(&druid_xml::lens::field(App::user, |data| &data.name, |data| &mut data.name), data)") );
+
+		//mixed path is the lens of each segment like the index path
+		let mixed = src.replace("App::flags[2]", "App::users[2].flags[1]");
+		let compiled = super::compile(&mixed, &HashMap::new()).unwrap();
+		assert!( compiled.contains("druid::LensExt::index(druid_xml::lens::field(druid::LensExt::index(App::users, 2), |data| &data.flags, |data| &mut data.flags), 1)") );
+
+		//json
+		let mut data = serde_json::json!({ "user" : { "address" : [ {"city":"Seoul"}, {"city":"Busan"} ] }, "volume" : 0.5, "count" : 3 });
+		assert_eq!( LensPath::parse("App::user.address[1].city").unwrap().get_json(&data), Some(serde_json::json!("Busan")) );
+		assert_eq!( LensPath::parse("volume | percent").unwrap().get_json(&data), Some(serde_json::json!(50.0)) );
+		assert!( LensPath::parse("volume | percent").unwrap().set_json(&mut data, serde_json::json!(20.0)) );
+		assert_eq!( data["volume"], serde_json::json!(0.2) );
+		assert!( LensPath::parse("count | string").unwrap().set_json(&mut data, serde_json::json!("7")) );
+		assert_eq!( data["count"], serde_json::json!(7) );
+		assert!( !LensPath::parse("count | string").unwrap().set_json(&mut data, serde_json::json!("seven")) );
+		assert!( LensPath::parse("App::user.name").unwrap().set_json(&mut data, serde_json::json!("Tom")) );
+		assert_eq!( data["user"]["name"], serde_json::json!("Tom") );
+		assert_eq!( LensPath::parse("App::none[3]").unwrap().get_json(&data), None );
+	}
+
+	#[test]
+	fn dynamic_data() {
+		use druid::Lens;
+		use serde_json::json;
+		use super::lens::LensPath;
+		use super::dynamic::JSValue;
+		use super::dynamic::data::{JSLens, format, text_parts, truthy};
+
+		let mut data = JSValue::new( json!({ "user" : { "name" : "Tom" }, "count" : 3 }) );
+		let name = JSLens::new(LensPath::parse("App::user.name"), String::new());
+		assert_eq!( name.with(&data, |v| v.clone()), "Tom" );
+		name.with_mut(&mut data, |v| *v = "Jane".to_owned());
+		assert_eq!( data["user"]["name"], json!("Jane") );
+		let count = JSLens::new(LensPath::parse("count"), 0f64);
+		count.with_mut(&mut data, |v| *v += 1.);
+		assert_eq!( data["count"], json!(4) );
+		//missing path is created
+		let dark = JSLens::new(LensPath::parse("App::settings.dark"), false);
+		assert!( !dark.with(&data, |v| *v) );
+		dark.with_mut(&mut data, |v| *v = true);
+		assert_eq!( data["settings"]["dark"], json!(true) );
+		//text of the number
+		let text = JSLens::new(LensPath::parse("count"), String::new());
+		assert_eq!( text.with(&data, |v| v.clone()), "4" );
+		text.with_mut(&mut data, |v| *v = "10".to_owned());
+		assert_eq!( data["count"], json!(10) );
+
+		data["price"] = json!(1.5);
+		let parts = super::bind::parse("Hi {App::user.name}, {price:.2} {missing}").unwrap();
+		let parts = text_parts(&parts);
+		assert_eq!( format(&parts, &data, false), "Hi Jane, 1.50 " );
+		assert_eq!( format(&parts, &data, true), "Hi Jane, 1.50 {missing}" );
+		assert!( truthy(&json!([1])) && !truthy(&json!(0)) && !truthy(&json!(null)) && !truthy(&json!("")) );
+
+		let src = r#"
+		<druid-xml>
+			<flex fn="row" lens="Item" export="false">
+				<checkbox lens="done"/>
+				<label>{title}</label>
+			</flex>
+			<flex fn="build_main" lens="App" direction="column">
+				<textbox lens="App::user.name"/>
+				<input type="number" lens="App::count"/>
+				<if lens="App::logged_in">
+					<label>Welcome {App::user.name}</label>
+					<else><label>Login</label></else>
+				</if>
+				<list lens="App::items" item="row"/>
+			</flex>
+		</druid-xml>
+		"#;
+		assert!( super::dynamic::generate_widget_with_data(src).is_ok() );
+		assert!( super::dynamic::generate_widget(src).is_ok() );
+	}
+
+	#[test]
+	fn local_state() {
+		use serde_json::json;
+		let src = r#"
+		<flex fn="build_main" lens="App">
+			<textbox id="pwd" type="password" lens="App::pwd"/>
+			<checkbox id="remember"/>
+			<slider id="volume" max="10"/>
+		</flex>
+		"#;
+		let (_, ui) = super::dynamic::generate_widget(src).unwrap();
+		let mut ids = ui.ids();
+		ids.sort();
+		assert_eq!( ids, vec!["pwd", "remember", "volume"] );
+		assert_eq!( ui.value("#pwd"), Some(json!("")) );
+		assert!( ui.set_value("#pwd", json!("secret")) );
+		assert_eq!( ui.value("pwd"), Some(json!("secret")) );
+		assert!( ui.set_value("#volume", json!(2.5)) );
+		assert_eq!( ui.value("#volume"), Some(json!(2.5)) );
+		assert!( !ui.set_value("#remember", json!("yes")) );
+		assert_eq!( ui.value("#remember"), Some(json!(false)) );
+		assert_eq!( ui.value("#none"), None );
+	}
+
+	#[test]
+	fn hot_reload() {
+		use super::dynamic::WidgetRegistry;
+		let load = |path:&std::path::Path| super::dynamic::hot_reload::load(path, &WidgetRegistry::new());
+		let dir = std::env::temp_dir().join( format!("druid-xml-hot-reload-{}", std::process::id()) );
+		std::fs::create_dir_all(&dir).unwrap();
+		let path = dir.join("main.xml");
+		assert!( load(&path).unwrap_err().starts_with( &path.display().to_string() ) );
+
+		std::fs::write(dir.join("common.xml"), r#"<druid-xml><label fn="title">Title</label></druid-xml>"#).unwrap();
+		std::fs::write(&path, r#"<import src="common.xml"/><flex fn="build_main" lens="()"><title/><textbox id="name"/></flex>"#).unwrap();
+		let (_, values, imports) = load(&path).unwrap();
+		assert_eq!( values.ids(), vec!["name"] );
+		//the imported file is watched as well
+		assert_eq!( imports, vec![dir.join("common.xml").canonicalize().unwrap()] );
+
+		std::fs::write(&path, r#"<flex fn="build_main" lens="()"><split><label/></split></flex>"#).unwrap();
+		let e = load(&path).unwrap_err();
+		println!("{e}");
+		assert!( e.contains("line 1, col 33: split needs exactly two children") );
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn model() {
+		let src = r#"
+		<model name="LoginInfo" derive="Debug">
+			<field name="id" type="String" default="guest"/>
+			<field name="remember" type="bool" default="true"/>
+			<field name="tries" type="u32"/>
+			<field name="address" type="Address"/>
+		</model>
+		<model name="Address">
+			<field name="city" type="String"/>
+		</model>
+		<flex fn="build_main" lens="LoginInfo">
+			<textbox lens="LoginInfo::id"/>
+			<checkbox lens="remember"/>
+			<label>{LoginInfo::address.city}</label>
+		</flex>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		println!("{}", compiled);
+		assert!( compiled.contains("#[derive(Clone, druid::Data, druid::Lens, Debug)]\npub struct LoginInfo {") );
+		assert!( compiled.contains("    pub address : Address,") );
+		assert!( compiled.contains(r#"id : "guest".into(),"#) );
+		assert!( compiled.contains("remember : true,") );
+		assert!( compiled.contains("tries : Default::default(),") );
+		assert!( compiled.contains("#[derive(Clone, druid::Data, druid::Lens)]\npub struct Address {") );
+		assert!( super::dynamic::generate_widget(src).is_ok() );
+
+		let unknown = src.replace("LoginInfo::address.city", "LoginInfo::address.cty");
+		assert_eq!( super::compile(&unknown, &HashMap::new()).unwrap_err().message, "model `Address` has no field `cty`, did you mean `city`?" );
+		let unknown = src.replace(r#"lens="remember""#, r#"lens="remembr""#);
+		assert_eq!( super::compile(&unknown, &HashMap::new()).unwrap_err().error.name(), "UnknownField" );
+
+		//models only
+		let models = r#"<model name="Point"><field name="x" type="f64" default="0.5"/></model>"#;
+		assert!( super::compile(models, &HashMap::new()).unwrap().contains("x : 0.5,") );
+
+		let misplaced = r#"<flex fn="build_main" lens="App"><field name="x" type="f64"/></flex>"#;
+		assert_eq!( super::compile(misplaced, &HashMap::new()).unwrap_err().error.name(), "MisplacedField" );
+		let misplaced = r#"<model name="App"><label>x</label></model><flex fn="build_main" lens="App"></flex>"#;
+		assert_eq!( super::compile(misplaced, &HashMap::new()).unwrap_err().error.name(), "MisplacedModel" );
+	}
+
+	#[test]
+	fn typed_input() {
+		use super::widget::input::{unmask, NumberError};
+		use super::widget::NumberFormatter;
+		assert_eq!( super::writer::pseudo_compat("textbox:invalid, button:disabled { color:red } a:hover{b:invalid}"), "textbox:lang(invalid), button:lang(disabled) { color:red } a:hover{b:invalid}" );
+		assert_eq!( unmask("abc", "•••x"), "abcx" );
+		assert_eq!( unmask("abc", "x•••"), "xabc" );
+		assert_eq!( unmask("abc", "•y•"), "ayc" );
+		assert_eq!( unmask("abcd", "••"), "ab" );
+		let formatter = NumberFormatter::new().min(0.).max(10.).step(0.5);
+		assert_eq!( formatter.check(1.5), Ok(1.5) );
+		assert_eq!( formatter.check(1.2), Err(NumberError::Step(0.5)) );
+		assert_eq!( formatter.check(-1.), Err(NumberError::Less(0.)) );
+		assert_eq!( formatter.check(11.), Err(NumberError::Greater(10.)) );
+
+		let src = r#"
+		<style>
+		textbox:invalid { background-color:red }
+		</style>
+		<flex fn="build_main" lens="App">
+			<textbox type="password" lens="App::pwd"/>
+			<input type="number" lens="App::age" min="0" max="150" step="1"/>
+			<textbox format="0.00" lens="App::price"/>
+			<textbox type="number" lens="App::count"/>
+		</flex>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		println!("{}", compiled);
+		assert!( compiled.contains("let textbox = druid::WidgetExt::lens(textbox, druid_xml::widget::input::password());") );
+		assert!( compiled.contains("druid::widget::ValueTextBox::new(textbox, druid_xml::widget::NumberFormatter::new().min(0.0f64).max(150.0f64).step(1.0f64)).validate_while_editing(true).delegate(invalid.clone());") );
+		assert!( compiled.contains(r#"druid_xml::widget::NumberFormatter::new().format("0.00")"#) );
+		assert!( compiled.contains("druid::widget::ValueTextBox::new(textbox, druid::text::ParseFormatter::<f64>::new())") );
+		assert!( compiled.contains("Some(druid_xml::simple_style::PseudoStyle::invalid(") );
+		assert!( compiled.contains("druid_xml::widget::SimpleStyleWidget::new(normal_style, pseudo_styles, textbox ).with_invalid(invalid);") );
+		assert!( super::dynamic::generate_widget(src).is_ok() );
+	}
+
+	#[test]
+	fn widget_registry() {
+		use druid::WidgetExt;
+		use std::cell::RefCell;
+		use std::rc::Rc;
+		let src = r#"
+		<flex fn="build_main" lens="()">
+			<gauge max="100" label="cpu">
+				<label>child</label>
+				<textbox id="name"/>
+			</gauge>
+			<demo_custom_widget/>
+		</flex>
+		"#;
+		//unknown without the registry
+		let e = super::dynamic::generate_widget(src).err().unwrap();
+		println!("{e}");
+		assert!( e.message.starts_with("unknown tag `gauge`") );
+
+		let seen = Rc::new( RefCell::new( vec![] ) );
+		let mut registry:super::dynamic::WidgetRegistry = super::dynamic::WidgetRegistry::new();
+		let captured = seen.clone();
+		registry.register("gauge", move |attrs, children| {
+			captured.borrow_mut().push( (attrs.get("label").map( |e| e.to_owned() ), children.len()) );
+			druid::widget::SizedBox::empty().boxed()
+		});
+		//the application registers it's own widgets. a new registry is empty
+		assert!( !registry.contains("demo_custom_widget") );
+		registry.register("demo_custom_widget", |_, _| druid::widget::Label::new("demo").boxed() );
+		assert_eq!( registry.tags().len(), 2 );
+		let (_, ui) = registry.generate_widget(src).unwrap();
+		assert_eq!( *seen.borrow(), vec![(Some("cpu".to_owned()), 2)] );
+		assert_eq!( ui.ids(), vec!["name"] );
+		assert!( registry.generate_widget_with_data(src).is_ok() );
+
+		//template parameters are resolved
+		let src = r#"
+		<flex fn="meter">
+			<param name="max" default="10"/>
+			<gauge label="${max}"/>
+		</flex>
+		<flex fn="build_main" lens="()">
+			<meter max="50"/>
+		</flex>
+		"#;
+		seen.borrow_mut().clear();
+		assert!( registry.generate_widget(src).is_ok() );
+		assert_eq!( *seen.borrow(), vec![(Some("50".to_owned()), 0)] );
+
+		//suggested from the registered tags
+		let e = registry.generate_widget(r#"<flex fn="build_main" lens="()"><gaugee/></flex>"#).err().unwrap();
+		assert!( e.message.contains("did you mean `gauge`?") );
+	}
+
+	#[test]
+	fn image() {
+		use super::resource::MemoryResolver;
+		let src = r#"
+		<style>
+		#logo { object-fit:contain; image-rendering:pixelated }
+		</style>
+		<flex fn="build_main" lens="()">
+			<image id="logo" src="media/sample_login.png"/>
+		</flex>
+		"#;
+		let compiled = super::compile_with_dir(src, std::path::Path::new(".."), &HashMap::new()).unwrap().code;
+		println!("{compiled}");
+		assert!( compiled.contains(r#"druid::ImageBuf::from_data( include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", "../media/sample_login.png")) ).unwrap_or_else( |_| druid::ImageBuf::empty() );"#) );
+		assert!( !compiled.contains(".expect(") );
+		assert!( compiled.contains("image.set_fill_mode(druid::widget::FillStrat::Contain);") );
+		assert!( compiled.contains("image.set_interpolation_mode(druid::piet::InterpolationMode::NearestNeighbor);") );
+		let e = super::compile(src, &HashMap::new()).unwrap_err();
+		assert_eq!( e.error.name(), "ResourceNotFound" );
+		assert_eq!( e.error.attribute_name(), Some("src") );
+		//decoded when it's compiled
+		let e = super::compile_with_dir(&src.replace("media/sample_login.png", "README.md"), std::path::Path::new(".."), &HashMap::new()).unwrap_err();
+		assert_eq!( e.message, "can't decode image `README.md`" );
+
+		let mut registry = super::dynamic::WidgetRegistry::new();
+		assert!( registry.generate_widget(src).is_err() );
+		let png = std::fs::read("../media/sample_login.png").unwrap();
+		registry.set_resources( MemoryResolver::new().insert("media/sample_login.png", png).clone() );
+		assert!( registry.generate_widget(src).is_ok() );
+
+		registry.set_resources( MemoryResolver::new().insert("media/sample_login.png", b"not an image".to_vec()).clone() );
+		let e = registry.generate_widget(src).err().unwrap();
+		println!("{e}");
+		assert_eq!( e.message, "can't decode image `media/sample_login.png`" );
+
+		let invalid = src.replace("contain", "stretch");
+		assert!( super::compile_with_dir(&invalid, std::path::Path::new(".."), &HashMap::new()).is_err() );
+		assert!( super::dynamic::generate_widget_with_dir(&invalid, std::path::Path::new("..")).is_err() );
+	}
+
+	#[test]
+	fn canvas() {
+		let src = r#"
+		<flex fn="gauge">
+			<param name="color" default="teal"/>
+			<canvas style="width:100; height:100">
+				<rect radius="4" fill="#eeeeee" stroke="black"/>
+				<circle r="40%" fill="${color}"/>
+				<ellipse rx="30" ry="10" rotate="45deg" stroke="red" stroke-width="2px"/>
+				<arc sweep="180deg" stroke="blue"/>
+				<line x1="0" y1="0" x2="100%" y2="100%"/>
+				<line x2="calc(50% - 10)" y2="0"/>
+			</canvas>
+		</flex>
+		<flex fn="build_main" lens="()">
+			<gauge color="orange"/>
+		</flex>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		println!("{compiled}");
+		assert!( compiled.contains("druid_xml::qwidget::drawable::Drawable::Circle{ center:druid_xml::qwidget::drawable::QVec2{ x:druid_xml::qwidget::drawable::Number::Rel(0.5f64), y:druid_xml::qwidget::drawable::Number::Rel(0.5f64) }, radius:druid_xml::qwidget::drawable::Number::Rel(0.4f64), border:None, fill:druid_xml::qwidget::drawable::FillMethod::Solid(druid::Color::rgba8(255,165,0,255)) },") );
+		assert!( !compiled.contains("Drawable::parse") );
+		assert!( compiled.contains("druid::widget::Painter::new( move |ctx, _, _| drawables.draw(0., ctx) )") );
+		assert!( super::dynamic::generate_widget(src).is_ok() );
+
+		let invalid = src.replace(r#"sweep="180deg""#, r#"sweep="half""#);
+		let e = super::compile(&invalid, &HashMap::new()).unwrap_err();
+		assert_eq!( e.error.attribute_name(), Some("sweep") );
+		assert_eq!( e.line, 8 );
+		assert!( super::dynamic::generate_widget(&invalid).is_err() );
+		let required = src.replace(r#"<line x2="calc(50% - 10)" y2="0"/>"#, r#"<line x2="10"/>"#);
+		assert_eq!( super::compile(&required, &HashMap::new()).unwrap_err().error.name(), "AttributeRequired" );
+
+		let misplaced = r#"<flex fn="build_main" lens="()"><rect/></flex>"#;
+		assert_eq!( super::compile(misplaced, &HashMap::new()).unwrap_err().error.name(), "MisplacedShape" );
+		let misplaced = r#"<flex fn="build_main" lens="()"><canvas><label/></canvas></flex>"#;
+		assert_eq!( super::compile(misplaced, &HashMap::new()).unwrap_err().error.name(), "MisplacedShape" );
+		assert!( super::dynamic::generate_widget(misplaced).is_err() );
+	}
+
+	#[test]
+	fn widget_spec() {
+		let src = r#"
+		<style>
+		.note { text-align:center; }
+		</style>
+		<flex fn="build_main" lens="App" direction="column">
+			<slider lens="App::volume" min="0" max="100"/>
+			<stepper lens="App::count" max="10"/>
+			<textbox lens="App::memo" multiline="true" style="text-align:right"/>
+			<label class="note" line-break="wordwrap">Long text</label>
+			<spacer flex="2"/>
+			<split min_size="100,50">
+				<label>One</label>
+				<label>Two</label>
+			</split>
+			<label style="transition:300ms color ease-in; color:rgba(255,0,0,128)">Fade</label>
+			<label style="text-align:right; color:blue">Styled</label>
+		</flex>
+		"#;
+		let compiled = super::compile(src, &HashMap::new()).unwrap();
+		println!("{compiled}");
+		assert!( compiled.contains("let slider = druid::widget::Slider::new().with_range(0.0f64, 100.0f64);") );
+		assert!( compiled.contains(".with_range(-1.7976931348623157e308f64, 10.0f64).with_step(1.0f64).with_wraparound(false);") );
+		assert!( compiled.contains("let mut textbox = druid::widget::TextBox::multiline();") );
+		assert!( compiled.contains("textbox.set_text_alignment(druid::TextAlignment::End);") );
+		assert!( compiled.contains("label.set_text_alignment(druid::TextAlignment::Center);") );
+		assert!( compiled.contains("label.set_line_break_mode(druid::widget::LineBreaking::WordWrap);") );
+		assert!( compiled.contains("flex.add_flex_spacer(2.0f64);") );
+		assert!( compiled.contains("split = split.min_size(100.0f64, 50.0f64);") );
+		assert!( compiled.contains("Some(druid::Color::rgba8(255,0,0,128))") );
+		assert!( compiled.contains("duration: 300000000") );
+		assert!( compiled.contains("timing_function: druid_xml::simple_style::TimingFunction::EaseIn") );
+		let step = src.replace("300ms color ease-in", "300ms color step-end");
+		assert!( super::compile(&step, &HashMap::new()).unwrap().contains("TimingFunction::Steps{ n:1.0f64, jumpterm:druid_xml::simple_style::JumpTerm::End }") );
+		use super::simple_style::{JumpTerm, TimingFunction};
+		let steps = |n:f64, jumpterm:JumpTerm| [0., 0.3, 0.5, 1.].map( |t| TimingFunction::Steps { n, jumpterm }.translate(t) );
+		assert_eq!( steps(1., JumpTerm::Start), [1., 1., 1., 1.] );
+		assert_eq!( steps(1., JumpTerm::End), [0., 0., 0., 1.] );
+		assert_eq!( steps(2., JumpTerm::JumpBoth), [1./3., 1./3., 2./3., 1.] );
+		assert_eq!( steps(3., JumpTerm::JumpNone), [0., 0., 0.5, 1.] );
+		assert!( compiled.contains("let mut label = druid_xml::widget::DXLabel::new(\"Styled\");\nlabel.set_text_alignment(druid::TextAlignment::End);") );
+		assert!( super::dynamic::generate_widget(src).is_ok() );
+
+		let invalid = src.replace(r#"min_size="100,50""#, r#"min_size="wide""#);
+		assert_eq!( super::compile(&invalid, &HashMap::new()).unwrap_err().error.attribute_name(), Some("min_size") );
+		assert!( super::dynamic::generate_widget(&invalid).is_err() );
+
+		let split_point = src.replace(r#"min_size="100,50""#, r#"split_point="0.05""#);
+		assert!( super::compile(&split_point, &HashMap::new()).unwrap().contains("split = split.split_point(0.05f64);") );
+		let invalid = src.replace(r#"min_size="100,50""#, r#"split_point="1.5""#);
+		assert_eq!( super::compile(&invalid, &HashMap::new()).unwrap_err().error.attribute_name(), Some("split_point") );
+		assert!( super::dynamic::generate_widget(&invalid).is_err() );
+	}
+
+	#[test]
+	fn non_finite() {
+		//`inf` and `NaN` are parsed by `f64::from_str` but they are not the rust literal
+		let cases = [
+			(r#"<slider min="0" max="inf"/>"#, "InvalidAttributeValue"),
+			(r#"<stepper step="NaN"/>"#, "InvalidAttributeValue"),
+			(r#"<label style="width:inf">A</label>"#, "InvalidSizeAttributeValue"),
+			(r#"<label style="font-size:-infpx">A</label>"#, "InvalidSizeAttributeValue"),
+			(r#"<label flex="NaN">A</label>"#, "InvalidAttributeValue"),
+			(r#"<canvas><circle r="inf"/></canvas>"#, "InvalidAttributeValue"),
+		];
+		for (elem, name) in cases {
+			let src = format!("<flex fn=\"build_main\" lens=\"()\">\n{elem}\n</flex>");
+			let e = super::compile(&src, &HashMap::new()).unwrap_err();
+			println!("{elem} : {e}");
+			assert_eq!( e.error.name(), name );
+			assert_eq!( e.line, 2 );
+			assert!( super::dynamic::generate_widget(&src).is_err() );
+		}
+	}
+
+	#[test]
+	fn color() {
+		use druid::Color;
+		use super::spec::color;
+		assert_eq!( color(" rgb(0, 255, 0) ", "color").unwrap(), Color::rgb8(0,255,0) );
+		assert_eq!( color("rgba(0,255,0,88)", "color").unwrap(), Color::rgba8(0,255,0,88) );
+		assert_eq!( color("#FF33FF22", "color").unwrap(), Color::rgba8(0xff,0x33,0xff,0x22) );
+		assert_eq!( color("teal", "color").unwrap(), Color::rgb8(0,128,128) );
+		for invalid in ["rgb(300,0,0)", "rgb(0,0)", "rgba(0,0,0)", "rgb(100%, 0, 25%)", "rgb(x)", "#GG0000", "tael"] {
+			assert!( color(invalid, "color").is_err(), "{invalid}" );
+		}
+
+		//same grammar in the style and the canvas
+		for elem in [r#"<label style="color:rgb(0,0)">A</label>"#, r#"<canvas><circle fill="rgb(0,0)"/></canvas>"#] {
+			let src = format!("<flex fn=\"build_main\" lens=\"()\">{elem}</flex>");
+			assert_eq!( super::compile(&src, &HashMap::new()).unwrap_err().error.name(), "InvalidAttributeValue" );
+			assert!( super::dynamic::generate_widget(&src).is_err() );
+		}
+	}
+
+}
@@ -0,0 +1,163 @@
+//! Data model declaration.
+//!
+//! ```xml
+//! <model name="LoginInfo" derive="Debug">
+//!     <field name="id" type="String" default=""/>
+//!     <field name="remember" type="bool" default="true"/>
+//! </model>
+//! ```
+//!
+//! Code generation emits the struct with `Clone`, `druid::Data`, `druid::Lens` derives and the `Default` impl.
+//! The `lens` attributes and the data-bound text of the document are checked against the fields.
+
+use std::fmt::Write;
+
+use crate::{bind, Document, Element, Error, Node};
+use crate::lens::{self, LensPath, Segment};
+
+#[derive(Debug,Clone)]
+pub(crate) struct Field {
+    pub name : String,
+    pub ty : String,
+    /// Rust expression. The text for the string types
+    pub default : Option<String>,
+}
+
+#[derive(Debug,Clone)]
+pub(crate) struct Model {
+    pub name : String,
+    /// Additional derives
+    pub derives : Vec<String>,
+    pub fields : Vec<Field>,
+}
+
+impl Model {
+    pub fn parse(elem:&Element) -> Result<Self, Error> {
+        let name = ident(elem, "name")?;
+        let derives = elem.attributes.get("derive").unwrap_or("")
+            .split(',')
+            .map( |e| e.trim() )
+            .filter( |e| !e.is_empty() )
+            .map( |e| if lens::is_path(e) { Ok(e.to_owned()) } else { Err(Error::InvalidAttributeValue( (elem.span.start, "derive") )) } )
+            .collect::<Result<Vec<_>,_>>()?;
+        let mut fields = vec![];
+        for field in elem.elements() {
+            let ty = field.attributes.get("type").ok_or( Error::AttributeRequired( (field.span.start, "type") ) )?;
+            fields.push( Field {
+                name : ident(field, "name")?,
+                ty : ty.trim().to_owned(),
+                default : field.attributes.get("default").map( |e| e.to_owned() ),
+            });
+        }
+        Ok( Self { name, derives, fields } )
+    }
+
+    pub fn field(&self, name:&str) -> Option<&Field> {
+        self.fields.iter().find( |e| e.name == name )
+    }
+
+    /// Struct and the `Default` impl
+    pub fn to_rust(&self) -> String {
+        let mut src = String::new();
+        let derives = ["Clone", "druid::Data", "druid::Lens"].into_iter().chain( self.derives.iter().map( |e| e.as_str() ) ).collect::<Vec<_>>().join(", ");
+        writeln!(src, "#[derive({derives})]").unwrap();
+        writeln!(src, "pub struct {} {{", self.name).unwrap();
+        for field in self.fields.iter() {
+            writeln!(src, "    pub {} : {},", field.name, field.ty).unwrap();
+        }
+        writeln!(src, "}}\n").unwrap();
+        writeln!(src, "impl Default for {} {{", self.name).unwrap();
+        writeln!(src, "    fn default() -> Self {{").unwrap();
+        writeln!(src, "        Self {{").unwrap();
+        for field in self.fields.iter() {
+            let value = match (field.default.as_deref(), field.ty.as_str()) {
+                (None, _) => "Default::default()".to_owned(),
+                (Some(v), "String" | "Arc<str>" | "std::sync::Arc<str>") => format!("{v:?}.into()"),
+                (Some(v), _) => v.to_owned()
+            };
+            writeln!(src, "            {} : {value},", field.name).unwrap();
+        }
+        writeln!(src, "        }}\n    }}\n}}").unwrap();
+        src
+    }
+}
+
+fn ident(elem:&Element, name:&'static str) -> Result<String, Error> {
+    let pos = elem.span.start;
+    match elem.attributes.get(name).map( |e| e.trim() ) {
+        None => Err(Error::AttributeRequired( (pos, name) )),
+        Some(v) if !v.contains("::") && lens::is_path(v) => Ok(v.to_owned()),
+        Some(_) => Err(Error::InvalidAttributeValue( (pos, name) ))
+    }
+}
+
+/// Models of the documents in order
+pub(crate) fn collect<'a>(docs:impl IntoIterator<Item=&'a Document>) -> Result<Vec<Model>, Error> {
+    docs.into_iter().flat_map( |e| e.models() ).map( Model::parse ).collect()
+}
+
+/// Lens paths of the templates refer to the declared fields.
+/// A path of the type which is not the model is not checked
+pub(crate) fn check(doc:&Document, models:&[Model]) -> Result<(), Error> {
+    if models.is_empty() {
+        return Ok(())
+    }
+    for template in doc.templates() {
+        let data_type = template.attributes.get("lens").map( |e| e.trim() );
+        check_element(template, data_type, models, true)?;
+    }
+    Ok(())
+}
+
+/// `lens` of the template root is the data type
+fn check_element(elem:&Element, data_type:Option<&str>, models:&[Model], root:bool) -> Result<(), Error> {
+    let pos = elem.span.start;
+    for name in ["lens", "text-lens"].into_iter().skip( root as usize ) {
+        if let Some(path) = elem.attributes.get(name).filter( |e| !e.contains("${") ).and_then( LensPath::parse ) {
+            check_path(&path, data_type, models, pos)?;
+        }
+    }
+    for node in elem.children.iter() {
+        if let Node::Text(text) = node {
+            for part in bind::parse(&text.content).unwrap_or_default() {
+                if let Some(path) = match part { bind::Part::Bind(b) => LensPath::parse(b.path), _ => None } {
+                    check_path(&path, data_type, models, pos)?;
+                }
+            }
+        }
+    }
+    for child in elem.elements() {
+        check_element(child, data_type, models, false)?;
+    }
+    Ok(())
+}
+
+fn check_path(path:&LensPath, data_type:Option<&str>, models:&[Model], pos:usize) -> Result<(), Error> {
+    let (ty, field) = match path.root.rfind("::") {
+        Some(at) => (&path.root[..at], &path.root[at+2..]),
+        None => match data_type {
+            Some(ty) => (ty, path.root.as_str()),
+            None => return Ok(())
+        }
+    };
+    let mut model = models.iter().find( |e| e.name == ty );
+    let fields = std::iter::once(field).chain( path.segments.iter().map_while( |e| match e {
+        Segment::Field(name) => Some(name.as_str()),
+        //element type is unknown
+        Segment::Index(_) => None
+    }));
+    for name in fields {
+        let current = match model {
+            Some(model) => model,
+            None => break
+        };
+        match current.field(name) {
+            Some(field) => model = models.iter().find( |e| e.name == field.ty ),
+            None => {
+                let suggestion = crate::schema::suggest(name, current.fields.iter().map( |e| e.name.as_str() )).map( |e| e.to_owned() );
+                return Err(Error::UnknownField( (pos, current.name.clone(), name.to_owned(), suggestion) ))
+            }
+        }
+    }
+    Ok(())
+}
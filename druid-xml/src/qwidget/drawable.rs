@@ -8,6 +8,10 @@ use fasteval2::{Instruction, Slab, Evaler, Compiler};
 use std::str::FromStr;
 
 use crate::simple_style::BorderStyle;
+use crate::Error;
+
+/// Child elements of the `canvas`
+pub const SHAPES:&[&str] = &["rect", "circle", "ellipse", "arc", "line"];
 
 
 
@@ -30,6 +34,11 @@ impl Calculator {
         let (inst,slab) = &self.cached;
         inst.eval(&slab, cvar).unwrap()
     }
+
+    /// Compiled expression. Percentages are the `__size_perc_` variables
+    pub fn src(&self) -> &str {
+        &self.src
+    }
 }
 
 impl Clone for Calculator {
@@ -50,6 +59,11 @@ pub enum Number {
 pub struct InvalidNumberError;
 
 impl Number {
+    /// `Calc` of the compiled expression([`Calculator::src`]). `0` if the expression is invalid
+    pub fn expr(src:&str) -> Self {
+        Calculator::new( src.to_owned() ).map( Number::Calc ).unwrap_or( Number::Abs(0.) )
+    }
+
     pub fn calc(&self, vars:&mut CalcVars) -> f64 {
         match self {
             Number::Abs(v) => *v,
@@ -80,16 +94,21 @@ impl FromStr for Number {
             });
             Self::Calc( Calculator::new( expr_str )? )
         } else if s.find('.').is_some() {
-            Self::Rel( s.parse::<f64>().map_err( |_| InvalidNumberError )? )
+            Self::Rel( finite(s)? )
         } else if s.ends_with('%') {
-            Self::Rel( s[..s.len()-1].parse::<f64>().map_err( |_| InvalidNumberError )? / 100f64 )
+            Self::Rel( finite(&s[..s.len()-1])? / 100f64 )
         } else {
-            Self::Abs( s.parse::<f64>().map_err( |_| InvalidNumberError )?  )
+            Self::Abs( finite(s)? )
         };
         Ok( v )
     }
 }
 
+/// `inf` and `NaN` are not available in the generated source
+fn finite(s:&str) -> Result<f64, InvalidNumberError> {
+    s.parse::<f64>().ok().filter( |e| e.is_finite() ).ok_or( InvalidNumberError )
+}
+
 #[derive(Clone)]
 pub enum Round {
     Solid{ radius:f64, width:f64, color:Color },
@@ -106,8 +125,8 @@ pub enum FillMethod {
 
 #[derive(Clone)]
 pub struct QVec2 {
-    x : Number,
-    y : Number
+    pub x : Number,
+    pub y : Number
 }
 
 impl QVec2 {
@@ -115,12 +134,14 @@ impl QVec2 {
         Ok( Self { x:Number::from_str(x)?, y:Number::from_str(y)? } )
     }
 
+    /// Relative `x` is of the width and `y` is of the height
     pub fn calc(&self, map:&mut CalcVars) -> (f64,f64) {
-        ( self.x.calc( map) , self.y.calc( map) )
+        ( self.x.calc( map.width_base() ) , self.y.calc( map.height_base() ) )
     }
 
     pub fn to_vec2(&self, map:&mut CalcVars) -> Vec2 {
-        Vec2::new( self.x.calc( map) , self.y.calc( map) )
+        let (x, y) = self.calc(map);
+        Vec2::new( x, y )
     }
 }
 
@@ -214,6 +235,81 @@ pub enum Drawable {
     Line{ start:Option<QVec2>, end:QVec2, style:Option<BorderStyle> }
 }
 
+impl Drawable {
+    /// Shape element of the `canvas`(one of [`SHAPES`]). Attributes are resolved already.
+    ///
+    /// Position and size are [`Number`]. `10` is absolute, `50%` or `0.5` is relative to the width(x) or height(y)
+    /// and `calc(50% - 10)` is the expression. Angle is the radian or `90deg`.
+    /// `fill` and `stroke` are css colors and `stroke-width` is the width of the stroke.
+    ///
+    /// * `rect` : `left`, `top`(`0`), `right`, `bottom`(`100%`), `radius`
+    /// * `circle` : `cx`, `cy`(`50%`), `r`(`50%` of the height)
+    /// * `ellipse` : `cx`, `cy`(`50%`), `rx`, `ry`(`50%`), `rotate`
+    /// * `arc` : `cx`, `cy`(`50%`), `rx`, `ry`(`50%`), `start`(`0`), `sweep`, `rotate`. Stroke only
+    /// * `line` : `x1`, `y1`(end of the previous line), `x2`, `y2`. Stroke only and the previous stroke if not exist
+    ///
+    /// The error has no position
+    pub fn parse(tag:&str, attrs:&[(&str,&str)]) -> Result<Self, Error> {
+        let get = |name:&str| attrs.iter().find( |e| e.0 == name ).map( |e| e.1 );
+        let number = |name:&'static str, default:Option<&str>| -> Result<Number, Error> {
+            match get(name).or(default) {
+                Some(v) => Number::from_str(v).map_err( |_| Error::InvalidAttributeValue( (0, name) ) ),
+                None => Err(Error::AttributeRequired( (0, name) ))
+            }
+        };
+        let vec2 = |x:&'static str, y:&'static str, default:Option<&str>| -> Result<QVec2, Error> {
+            Ok( QVec2 { x:number(x, default)?, y:number(y, default)? } )
+        };
+        let angle = |name:&'static str, default:Option<&str>| -> Result<Number, Error> {
+            let v = get(name).or(default).ok_or( Error::AttributeRequired( (0, name) ) )?.trim();
+            let radian = match v.strip_suffix("deg") {
+                Some(deg) => finite(deg.trim()).map( |e| e.to_radians() ),
+                None => finite(v.strip_suffix("rad").unwrap_or(v).trim())
+            };
+            radian.map( Number::Abs ).map_err( |_| Error::InvalidAttributeValue( (0, name) ) )
+        };
+        let color = |name:&'static str| -> Result<Option<Color>, Error> {
+            match get(name) {
+                Some(v) => crate::spec::color(v, name).map( Some ),
+                None => Ok(None)
+            }
+        };
+        let fill = match color("fill")? {
+            Some(color) => FillMethod::Solid(color),
+            None => FillMethod::None
+        };
+        let stroke_width = match get("stroke-width") {
+            Some(v) => Some( crate::spec::size(v).map_err( |_| Error::InvalidAttributeValue( (0, "stroke-width") ) )? ),
+            None => None
+        };
+        let border = match (color("stroke")?, stroke_width) {
+            (None, None) => None,
+            (color, width) => Some( BorderStyle::new( width.unwrap_or(1.), 0., color.unwrap_or( Color::BLACK ) ) )
+        };
+        let drawable = match tag {
+            "rect" => {
+                let round = match get("radius") {
+                    Some(v) => Some( RoundedRectRadii::from_single_radius( finite(v.trim()).map_err( |_| Error::InvalidAttributeValue( (0, "radius") ) )? ) ),
+                    None => None
+                };
+                Drawable::Rect { left:number("left", Some("0"))?, top:number("top", Some("0"))?, right:number("right", Some("100%"))?, bottom:number("bottom", Some("100%"))?, border, round, fill }
+            },
+            "circle" => Drawable::Circle { center:vec2("cx", "cy", Some("50%"))?, radius:number("r", Some("50%"))?, border, fill },
+            "ellipse" => Drawable::Ellipse { center:vec2("cx", "cy", Some("50%"))?, radi:vec2("rx", "ry", Some("50%"))?, x_rot:angle("rotate", Some("0"))?, border, fill },
+            "arc" => Drawable::Arc { center:vec2("cx", "cy", Some("50%"))?, radi:vec2("rx", "ry", Some("50%"))?, start_angle:angle("start", Some("0"))?, sweep_angle:angle("sweep", None)?, x_rot:angle("rotate", Some("0"))?, border:border.unwrap_or_default() },
+            "line" => {
+                let start = match (get("x1"), get("y1")) {
+                    (None, None) => None,
+                    _ => Some( vec2("x1", "y1", None)? )
+                };
+                Drawable::Line { start, end:vec2("x2", "y2", None)?, style:border }
+            },
+            _ => return Err(Error::MisplacedShape(0))
+        };
+        Ok( drawable )
+    }
+}
+
 pub struct DrawableStack(Vec<Drawable>);
 
 impl DrawableStack {
@@ -261,15 +357,17 @@ impl DrawableStack {
                 Drawable::Arc{ center, radi, start_angle, sweep_angle, x_rot, border } => { 
                     let mcvar = &mut cvar;
                     let center = center.calc(mcvar);
-                    let arc = Arc { center: Point { x: center.0, y: center.0 }, radii: radi.to_vec2(mcvar), start_angle: start_angle.calc(mcvar), sweep_angle: sweep_angle.calc(mcvar), x_rotation: x_rot.calc(mcvar) };
+                    let arc = Arc { center: Point { x: center.0, y: center.1 }, radii: radi.to_vec2(mcvar), start_angle: start_angle.calc(mcvar), sweep_angle: sweep_angle.calc(mcvar), x_rotation: x_rot.calc(mcvar) };
                     ctx.stroke_styled(arc, &border.color, border.width, &StrokeStyle::default());
                 }
                 Drawable::Line { start, end, style } => {
                     let start = start.as_ref().map( |e| e.calc( &mut cvar ) ).unwrap_or( last_point );
                     let end = end.calc( &mut cvar );
                     last_point = end;
-                    let style = style.as_ref().unwrap_or( &last_style );
-                    //let style = style.as_ref().unwrap_or( &def_stroke );
+                    if let Some(style) = style {
+                        last_style = *style;
+                    }
+                    let style = &last_style;
                     ctx.stroke_styled( Line::new( start, end ), &style.color, style.width, &StrokeStyle::default() );
                 },
             }
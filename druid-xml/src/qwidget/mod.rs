@@ -1,5 +1,5 @@
 
-mod value;
+pub mod value;
 mod flex;
 mod qwidget;
 mod selector;
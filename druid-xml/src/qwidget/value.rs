@@ -1,9 +1,26 @@
 use std::ops::{Deref,DerefMut};
 use serde_json::Value;
 
-#[derive(Clone)]
+/// JSON data of the dynamic widgets
+#[derive(Debug,Clone,Default,PartialEq)]
 pub struct JSValue(Value);
 
+impl JSValue {
+    pub fn new(v:Value) -> Self {
+        Self(v)
+    }
+
+    pub fn into_inner(self) -> Value {
+        self.0
+    }
+}
+
+impl From<Value> for JSValue {
+    fn from(v: Value) -> Self {
+        Self(v)
+    }
+}
+
 
 impl druid::Data for JSValue {
     fn same(&self, other: &Self) -> bool {
@@ -0,0 +1,90 @@
+//! Resources of the `<image src="...">`.
+//!
+//! Code generation resolves the `src` to the `include_bytes!` expression and the bytes to check the image([`IncludeResolver`]).
+//! The dynamic widget loads the bytes from the files([`FileResolver`]) or the memory([`MemoryResolver`]).
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub trait ResourceResolver {
+    /// Rust expression and the bytes(code generation) or the bytes(dynamic)
+    type Output;
+
+    /// `None` is not found
+    fn resolve(&self, src:&str) -> Option<Self::Output>;
+}
+
+/// `include_bytes!` of the file relative to `dir` and the bytes of the file.
+/// Relative `dir` is relative to the `CARGO_MANIFEST_DIR` of the crate which includes the generated code
+pub struct IncludeResolver {
+    dir : PathBuf,
+}
+
+impl IncludeResolver {
+    pub fn new(dir:impl Into<PathBuf>) -> Self {
+        Self { dir:dir.into() }
+    }
+}
+
+impl ResourceResolver for IncludeResolver {
+    type Output = (String, Vec<u8>);
+
+    fn resolve(&self, src:&str) -> Option<(String, Vec<u8>)> {
+        let path = self.dir.join(src);
+        let bytes = std::fs::read(&path).ok()?;
+        let path_str = path.strip_prefix(".").unwrap_or(&path).to_string_lossy().replace('\\', "/");
+        if path.is_absolute() {
+            Some( (format!("include_bytes!({path_str:?})"), bytes) )
+        } else {
+            Some( (format!("include_bytes!(concat!(env!(\"CARGO_MANIFEST_DIR\"), \"/\", {path_str:?}))"), bytes) )
+        }
+    }
+}
+
+/// Files relative to `dir`
+pub struct FileResolver {
+    dir : PathBuf,
+}
+
+impl FileResolver {
+    pub fn new(dir:impl Into<PathBuf>) -> Self {
+        Self { dir:dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl ResourceResolver for FileResolver {
+    type Output = Vec<u8>;
+
+    fn resolve(&self, src:&str) -> Option<Vec<u8>> {
+        std::fs::read( self.dir.join(src) ).ok()
+    }
+}
+
+/// Bytes by the `src`. The designer(wasm) has no file system
+#[derive(Clone,Default)]
+pub struct MemoryResolver {
+    resources : HashMap<String, Vec<u8>>,
+}
+
+impl MemoryResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, src:&str, bytes:impl Into<Vec<u8>>) -> &mut Self {
+        self.resources.insert( src.to_owned(), bytes.into() );
+        self
+    }
+}
+
+impl ResourceResolver for MemoryResolver {
+    type Output = Vec<u8>;
+
+    fn resolve(&self, src:&str) -> Option<Vec<u8>> {
+        self.resources.get(src).cloned()
+    }
+}
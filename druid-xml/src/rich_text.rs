@@ -0,0 +1,116 @@
+//! Inline markup of label and button content.
+//!
+//! `<label>Hello <b>world</b><br/><span style="color:red">!</span></label>`
+//!
+//! Whitespace is collapsed like html and `<br/>` is the line break.
+
+use std::ops::Range;
+
+use simplecss::DeclarationTokenizer;
+
+use druid::Color;
+
+use crate::{Element, Error, Node, AttributesWrapper, template, spec};
+
+/// Tags available in the label and button content
+pub(crate) const INLINE_TAGS:&[&str] = &["b", "i", "u", "span", "br"];
+
+pub(crate) fn is_inline(tag:&str) -> bool {
+    INLINE_TAGS.contains(&tag)
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub(crate) enum InlineStyle {
+    Bold,
+    Italic,
+    Underline,
+    /// css `color`
+    Color(Color),
+    /// css `font-size`
+    FontSize(f64),
+}
+
+/// Flattened text with the byte range of each style
+#[derive(Debug,Default)]
+pub(crate) struct RichSpans {
+    pub text : String,
+    pub spans : Vec<(Range<usize>, InlineStyle)>,
+}
+
+/// The element has inline markup
+pub(crate) fn has_markup(elem:&Element) -> bool {
+    elem.elements().next().is_some()
+}
+
+/// Template parameters in the text are interpolated
+pub(crate) fn flatten(elem:&Element, params:Option<&AttributesWrapper>) -> Result<RichSpans, Error> {
+    let mut rich = RichSpans::default();
+    let mut pending_space = false;
+    flatten_children(elem, params, &mut rich, &mut pending_space)?;
+    Ok( rich )
+}
+
+fn flatten_children(elem:&Element, params:Option<&AttributesWrapper>, rich:&mut RichSpans, pending_space:&mut bool) -> Result<(), Error> {
+    for child in elem.children.iter() {
+        match child {
+            Node::Text(t) | Node::CData(t) => {
+                for c in template::interpolate(&t.content, params).chars() {
+                    if c.is_whitespace() {
+                        *pending_space = true;
+                    } else {
+                        push_space(rich, pending_space);
+                        rich.text.push(c);
+                    }
+                }
+            },
+            Node::Element(e) if e.tag() == "br" => {
+                rich.text.push('\n');
+                *pending_space = false;
+            },
+            Node::Element(e) => {
+                let styles = inline_styles(e)?;
+                push_space(rich, pending_space);
+                let start = rich.text.len();
+                flatten_children(e, params, rich, pending_space)?;
+                let end = rich.text.len();
+                if end > start {
+                    for style in styles {
+                        rich.spans.push( (start .. end, style) );
+                    }
+                }
+            },
+            _ => ()
+        }
+    }
+    Ok(())
+}
+
+fn push_space(rich:&mut RichSpans, pending_space:&mut bool) {
+    if *pending_space && !rich.text.is_empty() && !rich.text.ends_with('\n') {
+        rich.text.push(' ');
+    }
+    *pending_space = false;
+}
+
+fn inline_styles(e:&Element) -> Result<Vec<InlineStyle>, Error> {
+    Ok( match e.tag() {
+        "b" => vec![InlineStyle::Bold],
+        "i" => vec![InlineStyle::Italic],
+        "u" => vec![InlineStyle::Underline],
+        "span" => {
+            let mut styles = vec![];
+            for decl in DeclarationTokenizer::from( e.attributes.get("style").unwrap_or("") ) {
+                match (decl.name, decl.value.trim()) {
+                    ("color", v) => styles.push( InlineStyle::Color( spec::color(v, "color").map_err( |err| err.or_at(e.span.start) )? ) ),
+                    ("font-size", v) => styles.push( InlineStyle::FontSize( spec::font_size(v).map_err( |err| err.or_at(e.span.start) )? ) ),
+                    ("font-weight", "bold" | "bolder" | "600" | "700" | "800" | "900") => styles.push( InlineStyle::Bold ),
+                    ("font-style", "italic" | "oblique") => styles.push( InlineStyle::Italic ),
+                    ("text-decoration", "underline") => styles.push( InlineStyle::Underline ),
+                    _ => ()
+                }
+            }
+            styles
+        },
+        tag @ _ => return Err(Error::UnknownTag( (e.span.start, tag.to_owned(), crate::schema::suggest(tag, INLINE_TAGS.iter().copied()).map( |e| e.to_owned() )) ))
+    })
+}
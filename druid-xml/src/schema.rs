@@ -0,0 +1,386 @@
+//! Built-in tags, their attributes and value types.
+//!
+//! Used by the validation pass before building widgets and available for tools(editor completion, linter).
+//!
+//! ```
+//! use druid_xml::schema;
+//!
+//! let flex = schema::find_tag("flex").unwrap();
+//! assert!( flex.attribute("cross_axis_alignment").is_some() );
+//! assert_eq!( schema::suggest("cross_alignment", flex.attribute_names()), Some("cross_axis_alignment") );
+//! ```
+
+use crate::{Document, Element, Error};
+
+/// Value type of the attribute
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum ValueType {
+    String,
+    Number,
+    Bool,
+    /// `10`, `10px` or `1em`
+    Size,
+    /// css color
+    Color,
+    /// css declarations
+    Style,
+    /// rust lens expression or type
+    Lens,
+    /// rust function path
+    Handler,
+    /// One of the values
+    Enum(&'static [&'static str]),
+}
+
+/// Allowed child elements
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Children {
+    None,
+    One,
+    Two,
+    Any,
+    /// Text and inline markup(`b`, `i`, `u`, `span`, `br`)
+    Inline,
+    /// One child and optional `else`
+    Branch,
+    /// Drawing shapes([`crate::qwidget::drawable::SHAPES`])
+    Shapes,
+}
+
+#[derive(Debug)]
+pub struct AttributeSchema {
+    pub name : &'static str,
+    pub value : ValueType,
+    pub description : &'static str,
+}
+
+#[derive(Debug)]
+pub struct TagSchema {
+    pub tag : &'static str,
+    pub attributes : &'static [AttributeSchema],
+    pub children : Children,
+    pub description : &'static str,
+}
+
+impl TagSchema {
+    /// Own or common attribute
+    pub fn attribute(&self, name:&str) -> Option<&'static AttributeSchema> {
+        self.attributes.iter().chain( COMMON_ATTRIBUTES.iter() ).find( |e| e.name == name )
+    }
+
+    /// Own and common attribute names
+    pub fn attribute_names(&self) -> impl Iterator<Item=&'static str> {
+        self.attributes.iter().chain( COMMON_ATTRIBUTES.iter() ).map( |e| e.name )
+    }
+}
+
+macro_rules! attr {
+    ($name:literal, $value:expr, $desc:literal) => {
+        AttributeSchema { name:$name, value:$value, description:$desc }
+    };
+}
+
+const DIRECTION:ValueType = ValueType::Enum(&["row", "column"]);
+
+/// Attributes available on every widget
+pub static COMMON_ATTRIBUTES:&[AttributeSchema] = &[
+    attr!("id", ValueType::String, "Identifier for the css `#id` selector"),
+    attr!("class", ValueType::String, "Class names for the css `.class` selector"),
+    attr!("style", ValueType::Style, "Inline css declarations"),
+    attr!("flex", ValueType::Number, "Flex factor in the parent `flex`"),
+    attr!("lens", ValueType::Lens, "druid `Lens` of the widget. On the template, the data type"),
+    attr!("fn", ValueType::String, "Generated function name of the template"),
+    attr!("export", ValueType::Bool, "Generate the template as `pub fn`"),
+    attr!("width", ValueType::Size, "Fixed width"),
+    attr!("height", ValueType::Size, "Fixed height"),
+    attr!("onclick", ValueType::Handler, "`fn(&mut EventCtx, &mut T, &Env)` called on click"),
+    attr!("ondblclick", ValueType::Handler, "`fn(&mut EventCtx, &mut T, &Env)` called on double click"),
+    attr!("onchange", ValueType::Handler, "`fn(&mut EventCtx, &mut T, &Env)` called when the data is changed"),
+    attr!("onfocus", ValueType::Handler, "`fn(&mut EventCtx, &mut T, &Env)` called when the widget gets the focus"),
+    attr!("onblur", ValueType::Handler, "`fn(&mut EventCtx, &mut T, &Env)` called when the widget loses the focus"),
+    attr!("command", ValueType::Handler, "`Selector` submitted on click"),
+    attr!("command-payload", ValueType::String, "Payload expression of the `command`"),
+    attr!("target", ValueType::String, "Target of the `command`. `window`, `global` or `#id`"),
+];
+
+pub static TAGS:&[TagSchema] = &[
+    TagSchema { tag:"flex", children:Children::Any, description:"druid `Flex`", attributes:&[
+        attr!("direction", DIRECTION, "Main axis. default is `row`"),
+        attr!("must_fill_main_axis", ValueType::Bool, "Fill the main axis"),
+        attr!("cross_axis_alignment", ValueType::Enum(&["start", "center", "end", "baseline"]), "Cross axis alignment"),
+        attr!("axis_alignment", ValueType::Enum(&["start", "center", "end", "spacebetween", "spaceevenly", "spacearound"]), "Main axis alignment"),
+    ]},
+    TagSchema { tag:"label", children:Children::Inline, description:"druid `Label`", attributes:&[
+        attr!("line-break", ValueType::Enum(&["wordwrap", "clip", "overflow"]), "Line break mode"),
+        attr!("text-lens", ValueType::Lens, "Text from the lens. `Type::field` or `Type::field:.2`"),
+    ]},
+    TagSchema { tag:"button", children:Children::Inline, description:"druid `Button`", attributes:&[
+        attr!("line-break", ValueType::Enum(&["wordwrap", "clip", "overflow"]), "Line break mode"),
+        attr!("text-lens", ValueType::Lens, "Text from the lens. `Type::field` or `Type::field:.2`"),
+    ]},
+    TagSchema { tag:"checkbox", children:Children::None, description:"druid `Checkbox`", attributes:&[] },
+    TagSchema { tag:"textbox", children:Children::None, description:"druid `TextBox`", attributes:&[
+        attr!("type", ValueType::Enum(&["text", "password", "number"]), "Text type. `number` is the `f64` `ValueTextBox`"),
+        attr!("placeholder", ValueType::String, "Placeholder text"),
+        attr!("value", ValueType::String, "Initial text of the preview. The data of the `lens` otherwise"),
+        attr!("multiline", ValueType::Bool, "Multiline textbox"),
+        attr!("min", ValueType::Number, "Minimum value of the `number`"),
+        attr!("max", ValueType::Number, "Maximum value of the `number`"),
+        attr!("step", ValueType::Number, "Valid values are `min + step * n`"),
+        attr!("format", ValueType::String, "Number format. `0.00`"),
+    ]},
+    TagSchema { tag:"input", children:Children::None, description:"`textbox` or `checkbox` by the type", attributes:&[
+        attr!("type", ValueType::Enum(&["text", "password", "number", "checkbox"]), "Input type"),
+        attr!("placeholder", ValueType::String, "Placeholder text"),
+        attr!("value", ValueType::String, "Initial text of the preview. The data of the `lens` otherwise"),
+        attr!("min", ValueType::Number, "Minimum value of the `number`"),
+        attr!("max", ValueType::Number, "Maximum value of the `number`"),
+        attr!("step", ValueType::Number, "Valid values are `min + step * n`"),
+        attr!("format", ValueType::String, "Number format. `0.00`"),
+    ]},
+    TagSchema { tag:"image", children:Children::None, description:"druid `Image`", attributes:&[
+        attr!("src", ValueType::String, "Image path. Relative to the xml file"),
+    ]},
+    TagSchema { tag:"img", children:Children::None, description:"Same as `image`", attributes:&[
+        attr!("src", ValueType::String, "Image path. Relative to the xml file"),
+    ]},
+    TagSchema { tag:"list", children:Children::None, description:"druid `List` of the `im::Vector` lens", attributes:&[
+        attr!("item", ValueType::String, "`fn` name of the item template"),
+        attr!("direction", ValueType::Enum(&["vertical", "horizontal"]), "List direction"),
+        attr!("spacing", ValueType::Size, "Spacing between items"),
+    ]},
+    TagSchema { tag:"scroll", children:Children::One, description:"druid `Scroll`", attributes:&[] },
+    TagSchema { tag:"slider", children:Children::None, description:"druid `Slider`", attributes:&[
+        attr!("min", ValueType::Number, "Minimum value"),
+        attr!("max", ValueType::Number, "Maximum value"),
+    ]},
+    TagSchema { tag:"spinner", children:Children::None, description:"druid `Spinner`", attributes:&[
+        attr!("color", ValueType::Color, "Spinner color"),
+    ]},
+    TagSchema { tag:"split", children:Children::Two, description:"druid `Split`", attributes:&[
+        attr!("direction", DIRECTION, "Split direction"),
+        attr!("split_point", ValueType::Number, "Split point(0.0 ~ 1.0)"),
+        attr!("min_size", ValueType::String, "Minimum size of each side. `first,second` or one value for both"),
+        attr!("bar_size", ValueType::Number, "Bar size"),
+        attr!("min_bar_area", ValueType::Number, "Minimum bar area for the mouse"),
+        attr!("draggable", ValueType::Bool, "Draggable bar"),
+        attr!("solid_bar", ValueType::Bool, "Solid bar"),
+    ]},
+    TagSchema { tag:"stepper", children:Children::None, description:"druid `Stepper`", attributes:&[
+        attr!("min", ValueType::Number, "Minimum value"),
+        attr!("max", ValueType::Number, "Maximum value"),
+        attr!("step", ValueType::Number, "Step"),
+        attr!("wraparound", ValueType::Bool, "Wrap around"),
+    ]},
+    TagSchema { tag:"switch", children:Children::None, description:"druid `Switch`", attributes:&[] },
+    TagSchema { tag:"spacer", children:Children::None, description:"Spacer in `flex`", attributes:&[] },
+    TagSchema { tag:"painter", children:Children::Shapes, description:"druid `Painter` of the shapes", attributes:&[] },
+    TagSchema { tag:"canvas", children:Children::Shapes, description:"Same as `painter`", attributes:&[] },
+
+    //conditional
+    TagSchema { tag:"if", children:Children::Branch, description:"druid `Either`. The child if the `lens` value is true, otherwise `else`", attributes:&[
+        attr!("preview", ValueType::Enum(&["then", "else"]), "Child of the dynamic preview without data"),
+    ]},
+    TagSchema { tag:"else", children:Children::One, description:"The child if the `lens` value of the parent `if` is false", attributes:&[] },
+
+    //inline markup
+    TagSchema { tag:"b", children:Children::Inline, description:"Bold text", attributes:&[] },
+    TagSchema { tag:"i", children:Children::Inline, description:"Italic text", attributes:&[] },
+    TagSchema { tag:"u", children:Children::Inline, description:"Underlined text", attributes:&[] },
+    TagSchema { tag:"span", children:Children::Inline, description:"Styled text", attributes:&[] },
+    TagSchema { tag:"br", children:Children::None, description:"Line break", attributes:&[] },
+
+    //shapes of the canvas. position and size are `10`, `50%`, `0.5` or `calc(50% - 10)`
+    TagSchema { tag:"rect", children:Children::None, description:"Rectangle of the `canvas`", attributes:&[
+        attr!("left", ValueType::String, "Left. default is `0`"),
+        attr!("top", ValueType::String, "Top. default is `0`"),
+        attr!("right", ValueType::String, "Right. default is `100%`"),
+        attr!("bottom", ValueType::String, "Bottom. default is `100%`"),
+        attr!("radius", ValueType::Number, "Corner radius"),
+        attr!("fill", ValueType::Color, "Fill color"),
+        attr!("stroke", ValueType::Color, "Stroke color"),
+        attr!("stroke-width", ValueType::Size, "Stroke width"),
+    ]},
+    TagSchema { tag:"circle", children:Children::None, description:"Circle of the `canvas`", attributes:&[
+        attr!("cx", ValueType::String, "Center x. default is `50%`"),
+        attr!("cy", ValueType::String, "Center y. default is `50%`"),
+        attr!("r", ValueType::String, "Radius. default is `50%` of the height"),
+        attr!("fill", ValueType::Color, "Fill color"),
+        attr!("stroke", ValueType::Color, "Stroke color"),
+        attr!("stroke-width", ValueType::Size, "Stroke width"),
+    ]},
+    TagSchema { tag:"ellipse", children:Children::None, description:"Ellipse of the `canvas`", attributes:&[
+        attr!("cx", ValueType::String, "Center x. default is `50%`"),
+        attr!("cy", ValueType::String, "Center y. default is `50%`"),
+        attr!("rx", ValueType::String, "Horizontal radius. default is `50%`"),
+        attr!("ry", ValueType::String, "Vertical radius. default is `50%`"),
+        attr!("rotate", ValueType::String, "Rotation. radian or `90deg`"),
+        attr!("fill", ValueType::Color, "Fill color"),
+        attr!("stroke", ValueType::Color, "Stroke color"),
+        attr!("stroke-width", ValueType::Size, "Stroke width"),
+    ]},
+    TagSchema { tag:"arc", children:Children::None, description:"Arc of the `canvas`", attributes:&[
+        attr!("cx", ValueType::String, "Center x. default is `50%`"),
+        attr!("cy", ValueType::String, "Center y. default is `50%`"),
+        attr!("rx", ValueType::String, "Horizontal radius. default is `50%`"),
+        attr!("ry", ValueType::String, "Vertical radius. default is `50%`"),
+        attr!("start", ValueType::String, "Start angle. radian or `90deg`"),
+        attr!("sweep", ValueType::String, "Sweep angle. radian or `90deg`"),
+        attr!("rotate", ValueType::String, "Rotation. radian or `90deg`"),
+        attr!("stroke", ValueType::Color, "Stroke color"),
+        attr!("stroke-width", ValueType::Size, "Stroke width"),
+    ]},
+    TagSchema { tag:"line", children:Children::None, description:"Line of the `canvas`", attributes:&[
+        attr!("x1", ValueType::String, "Start x. default is the end of the previous line"),
+        attr!("y1", ValueType::String, "Start y. default is the end of the previous line"),
+        attr!("x2", ValueType::String, "End x"),
+        attr!("y2", ValueType::String, "End y"),
+        attr!("stroke", ValueType::Color, "Stroke color. default is the previous line"),
+        attr!("stroke-width", ValueType::Size, "Stroke width"),
+    ]},
+
+    //template
+    TagSchema { tag:"param", children:Children::None, description:"Template parameter. Direct child of the template", attributes:&[
+        attr!("name", ValueType::String, "Parameter name"),
+        attr!("default", ValueType::String, "Default value. Required parameter if not exist"),
+        attr!("type", ValueType::Enum(&["string", "f64", "bool", "color"]), "Value type. default is `string`"),
+    ]},
+
+    //data model
+    TagSchema { tag:"model", children:Children::Any, description:"`Data`, `Lens` struct with the `Default` impl. Top element", attributes:&[
+        attr!("name", ValueType::String, "Struct name"),
+        attr!("derive", ValueType::String, "Additional derives. `Debug, PartialEq`"),
+    ]},
+    TagSchema { tag:"field", children:Children::None, description:"Field of the `model`", attributes:&[
+        attr!("name", ValueType::String, "Field name"),
+        attr!("type", ValueType::Lens, "Rust type"),
+        attr!("default", ValueType::String, "Default value. Text for `String`, otherwise rust expression"),
+    ]},
+];
+
+pub fn find_tag(tag:&str) -> Option<&'static TagSchema> {
+    TAGS.iter().find( |e| e.tag == tag )
+}
+
+/// The most similar candidate within the edit distance
+pub fn suggest<'a>(word:&str, candidates:impl IntoIterator<Item=&'a str>) -> Option<&'a str> {
+    let max = (word.chars().count() / 3).max(1);
+    candidates.into_iter()
+    .filter( |e| *e != word )
+    .map( |e| (edit_distance(word, e), e) )
+    .filter( |(d, _)| *d <= max )
+    .min_by_key( |(d, _)| *d )
+    .map( |(_, e)| e )
+}
+
+/// Optimal string alignment distance. Transposition is one edit(`lable` -> `label`)
+pub fn edit_distance(a:&str, b:&str) -> usize {
+    let a:Vec<char> = a.chars().collect();
+    let b:Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in 0 ..= a.len() {
+        d[i][0] = i;
+    }
+    for j in 0 ..= b.len() {
+        d[0][j] = j;
+    }
+    for i in 1 ..= a.len() {
+        for j in 1 ..= b.len() {
+            let cost = if a[i-1] == b[j-1] { 0 } else { 1 };
+            d[i][j] = (d[i-1][j] + 1).min( d[i][j-1] + 1 ).min( d[i-1][j-1] + cost );
+            if i > 1 && j > 1 && a[i-1] == b[j-2] && a[i-2] == b[j-1] {
+                d[i][j] = d[i][j].min( d[i-2][j-2] + 1 );
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Validate tags, attributes and values.
+///
+/// Templates of `imports` are available as tags.
+/// `natives` are the tag names of the native widgets.
+/// If `None`, an unknown tag is the rust function which returns the widget(code generation)
+/// and only a tag similar to the known one is reported.
+pub fn validate(doc:&Document, imports:&[Document], natives:Option<&[&str]>) -> Vec<Error> {
+    let template_names:Vec<&str> = imports.iter().chain( std::iter::once(doc) )
+        .flat_map( |e| e.templates() )
+        .filter_map( |e| e.attributes.get("fn") )
+        .collect();
+    let mut errors = vec![];
+    for template in doc.templates().chain( doc.models() ) {
+        validate_element(template, true, &template_names, natives, &mut errors);
+    }
+    errors
+}
+
+fn validate_element(elem:&Element, template_root:bool, template_names:&[&str], natives:Option<&[&str]>, errors:&mut Vec<Error>) {
+    let pos = elem.span.start;
+    if let Some(schema) = find_tag(elem.tag()) {
+        for attr in elem.attributes.iter() {
+            match schema.attribute(&attr.name) {
+                None => {
+                    let suggestion = suggest(&attr.name, schema.attribute_names()).map( |e| e.to_owned() );
+                    errors.push( Error::UnknownAttribute( (pos, attr.name.clone(), suggestion) ) );
+                },
+                //template parameter is checked after substitution
+                Some(_) if attr.value.contains("${") => (),
+                Some(attr_schema) => if let Err(e) = check_value(attr_schema, &attr.value, pos) {
+                    errors.push( e );
+                }
+            }
+        }
+        if let Some(item) = elem.attributes.get("item").filter( |e| elem.tag() == "list" && !e.contains("${") ) {
+            if !template_names.contains(&item) {
+                let suggestion = suggest(item, template_names.iter().copied()).map( |e| e.to_owned() );
+                errors.push( Error::UnknownTemplate( (pos, item.to_owned(), suggestion) ) );
+            }
+        }
+    } else if !template_names.contains(&elem.tag()) && !natives.map( |e| e.contains(&elem.tag()) ).unwrap_or(false) {
+        let known = TAGS.iter().map( |e| e.tag ).chain( template_names.iter().copied() ).chain( natives.unwrap_or(&[]).iter().copied() );
+        let suggestion = suggest(elem.tag(), known).map( |e| e.to_owned() );
+        if natives.is_some() || suggestion.is_some() {
+            errors.push( Error::UnknownTag( (pos, elem.tag().to_owned(), suggestion) ) );
+        }
+    }
+    for child in elem.elements() {
+        if child.tag() == "param" && !template_root {
+            errors.push( Error::MisplacedParam(child.span.start) );
+        }
+        if child.tag() == "else" && elem.tag() != "if" {
+            errors.push( Error::MisplacedElse(child.span.start) );
+        }
+        if child.tag() == "model" || (elem.tag() == "model" && child.tag() != "field") {
+            errors.push( Error::MisplacedModel(child.span.start) );
+        }
+        if child.tag() == "field" && elem.tag() != "model" {
+            errors.push( Error::MisplacedField(child.span.start) );
+        }
+        if crate::qwidget::drawable::SHAPES.contains(&child.tag()) && find_tag(elem.tag()).map( |e| e.children ) != Some(Children::Shapes) {
+            errors.push( Error::MisplacedShape(child.span.start) );
+        }
+        validate_element(child, false, template_names, natives, errors);
+    }
+}
+
+fn check_value(schema:&'static AttributeSchema, value:&str, pos:usize) -> Result<(), Error> {
+    let v = value.trim();
+    let valid = match schema.value {
+        ValueType::String | ValueType::Style | ValueType::Lens => true,
+        ValueType::Number => v.parse::<f64>().map_or(false, |e| e.is_finite()),
+        ValueType::Bool => v == "true" || v == "false",
+        ValueType::Size => crate::spec::size(v).is_ok(),
+        ValueType::Color => crate::spec::color(v, schema.name).is_ok(),
+        ValueType::Handler => crate::lens::is_path(v),
+        ValueType::Enum(values) => if values.contains(&v) {
+            true
+        } else {
+            return Err(Error::InvalidEnumValue( (pos, schema.name, v.to_owned(), values) ))
+        }
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidAttributeValue( (pos, schema.name) ))
+    }
+}
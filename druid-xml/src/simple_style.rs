@@ -42,10 +42,24 @@ impl TimingFunction {
             TimingFunction::EaseOut => AnimationCurve::EASE_OUT.translate(t),
             TimingFunction::EaseInOut => AnimationCurve::EASE_IN_OUT.translate(t),
             TimingFunction::Linear => t,
-            TimingFunction::StepStart => todo!(),
-            TimingFunction::StepEnd => todo!(),
+            TimingFunction::StepStart => TimingFunction::Steps { n:1., jumpterm:JumpTerm::Start }.translate(t),
+            TimingFunction::StepEnd => TimingFunction::Steps { n:1., jumpterm:JumpTerm::End }.translate(t),
             TimingFunction::CubicBezier { p1, p2, p3, p4 } => AnimationCurve::cubic(*p1, *p2, *p3, *p4).translate(t),
-            TimingFunction::Steps { n, jumpterm } => todo!(),
+            TimingFunction::Steps { n, jumpterm } => {
+                //https://drafts.csswg.org/css-easing/#step-easing-algo
+                let n = n.floor();
+                let (step, jumps) = match jumpterm {
+                    JumpTerm::JumpStart | JumpTerm::Start => ((t * n).floor() + 1., n),
+                    JumpTerm::JumpEnd | JumpTerm::End => ((t * n).floor(), n),
+                    JumpTerm::JumpNone => ((t * n).floor(), n - 1.),
+                    JumpTerm::JumpBoth => ((t * n).floor() + 1., n + 1.),
+                };
+                if jumps < 1. {
+                    t
+                } else {
+                    step.clamp(0., jumps) / jumps
+                }
+            },
         }
     }
 }
@@ -85,6 +99,10 @@ impl From<Animation> for AnimationState {
 }
 
 impl AnimationState {
+    pub fn animation(&self) -> &Animation {
+        &self.anim
+    }
+
     pub fn transit<T:Transit>(&mut self,src:T, target:T, interval:i64) -> (bool,T) {
         let old_elapsed = self.elapsed;
         //println!("elapsed interval duration {} {} {}", self.elapsed, interval, self.anim.duration);
@@ -339,7 +357,9 @@ pub enum Pseudo {
 	Focus,
 	Hover,
 	Active,
-	Disabled
+	Disabled,
+	/// validation failure of the input
+	Invalid
 }
 
 pub struct PseudoStyle {
@@ -363,6 +383,10 @@ impl PseudoStyle {
 	pub fn disabled(src:Styler) -> Self {
 		Self {pseudo:Pseudo::Disabled, style:src}
 	}
+
+	pub fn invalid(src:Styler) -> Self {
+		Self {pseudo:Pseudo::Invalid, style:src}
+	}
 }
 
 #[derive(Debug)]
@@ -0,0 +1,846 @@
+//! Resolved widget tree of the template.
+//!
+//! Styles are cascaded, template parameters are substituted and attributes are typed once.
+//! Code generation(`crate::writer`) and the dynamic widget(`crate::dynamic`) lower the same tree,
+//! so every tag behaves the same in both modes.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use druid::{Color, Insets, TextAlignment};
+use druid::piet::InterpolationMode;
+use druid::widget::{CrossAxisAlignment, FillStrat, LineBreaking, MainAxisAlignment};
+use simplecss::{DeclarationTokenizer, StyleSheet};
+
+use crate::bind::{Binding, FormatSpec};
+use crate::lens::LensPath;
+use crate::qwidget::drawable::Drawable;
+use crate::rich_text::{self, RichSpans};
+use crate::simple_style::{Animation, AnimationState, BorderStyle, Direction, JumpTerm, Pseudo, PseudoStyle, Styler, TimingFunction};
+use crate::template::{self, Template};
+use crate::writer::{ElementQueryWrap, PseudoOrderTrapQueryWrap};
+use crate::{AttributeGetter, Attributes, AttributesWrapper, Element, Error, schema};
+
+/// Properties of the `SimpleStyleWidget`
+const STYLER_PROPERTIES:&[&str] = &["padding", "margin", "font-size", "width", "height", "color", "background-color", "border"];
+
+/// Event handler attributes. The value is the path of the rust function
+pub(crate) const HANDLERS:[&str;5] = ["onclick", "onchange", "onfocus", "onblur", "ondblclick"];
+
+/// Templates and styles of the document
+pub(crate) struct Context<'a, 'c> {
+    pub templates : &'a HashMap<String,Template>,
+    pub css : &'c StyleSheet<'c>,
+    /// Registered native widgets. `None` : unknown tag is the native widget function(code generation)
+    pub natives : Option<&'c [&'c str]>,
+    /// `None` : the style sheet is cascaded every time(code generation)
+    pub cache : Option<&'c StyleCache>,
+}
+
+/// Cascaded declarations and the pseudo rules by the element path(addresses of the parents and the element) and the inline style.
+/// The `list` item is built for every item without the style sheet
+pub(crate) type StyleCache = RefCell<HashMap<(Vec<usize>, String), (Declarations, Vec<(Pseudo, Declarations)>)>>;
+
+pub(crate) struct WidgetSpec<'a> {
+    pub elem : &'a Element,
+    /// Ancestors from the template root
+    pub parents : Vec<&'a Element>,
+    pub kind : Kind<'a>,
+    /// Type of the `field` binding. `lens` of the nearest template root(the used template as well) which has it
+    pub data_type : Option<&'a str>,
+    /// Not the condition of the `if` nor the data type of the template root
+    pub lens : Option<String>,
+    pub id : Option<String>,
+    /// (attribute, handler path) of `onclick`, `onchange`, `onfocus`, `onblur` and `ondblclick`
+    pub handlers : Vec<(&'static str, String)>,
+    pub command : Option<Command>,
+    /// Fixed size of the `width`, `height` style(or attribute)
+    pub width : Option<f64>,
+    pub height : Option<f64>,
+    /// `None` if there is no style of the `SimpleStyleWidget`
+    pub style : Option<StyleSpec>,
+}
+
+pub(crate) enum Kind<'a> {
+    Flex { column:bool, must_fill:Option<bool>, cross:Option<CrossAxisAlignment>, main:Option<MainAxisAlignment>, children:Vec<FlexChild<'a>> },
+    /// `label` or `button`
+    Label { text:LabelText, button:bool, line_break:Option<LineBreaking>, align:Option<TextAlignment> },
+    Checkbox { text:String },
+    /// `value` is the initial text of the preview(the data of the `lens` otherwise)
+    TextBox { multiline:bool, placeholder:Option<String>, value:Option<String>, input:Input, color:Option<Color>, font_size:Option<f64>, align:Option<TextAlignment> },
+    Image { src:String, fit:Option<FillStrat>, rendering:Option<InterpolationMode> },
+    /// `item` is the `fn` of the item template
+    List { item:String, template:Box<WidgetSpec<'a>>, horizontal:bool, spacing:Option<f64> },
+    Scroll { child:Box<WidgetSpec<'a>> },
+    Slider { min:f64, max:f64 },
+    Spinner { color:Option<Color> },
+    Split { column:bool, one:Box<WidgetSpec<'a>>, two:Box<WidgetSpec<'a>>, split_point:Option<f64>, min_size:Option<(f64,f64)>, bar_size:Option<f64>, min_bar_area:Option<f64>, draggable:Option<bool>, solid_bar:Option<bool> },
+    /// `lens` is the lens path of the condition. `preview_else` is `preview="else"` which shows `otherwise` on the dynamic preview
+    If { lens:String, then:Box<WidgetSpec<'a>>, otherwise:Option<Box<WidgetSpec<'a>>>, preview_else:bool },
+    Stepper { min:f64, max:f64, step:f64, wraparound:bool },
+    Switch,
+    /// `painter` or `canvas`
+    Canvas { shapes:Vec<Drawable> },
+    /// Native widget of the registry or the function(code generation). `attrs` are resolved
+    Native { tag:&'a str, attrs:Attributes, children:Vec<WidgetSpec<'a>> },
+    /// Usage of the template
+    Template { root:Box<WidgetSpec<'a>> },
+}
+
+pub(crate) enum FlexChild<'a> {
+    /// `flex` factor or the default spacer
+    Spacer(Option<f64>),
+    Child(WidgetSpec<'a>, Option<f64>),
+}
+
+pub(crate) enum LabelText {
+    /// Unescaped text
+    Static(String),
+    /// Text which has `{Type::field}`(`crate::bind::parse`). `text-lens` is `{text-lens}`.
+    /// Braces which are not the binding are escaped
+    Bind(String),
+    /// Inline markup
+    Rich(RichSpans),
+}
+
+pub(crate) enum Input {
+    Text,
+    Password,
+    /// `f64` of the `ValueTextBox`. It has the `:invalid` state
+    Number { min:Option<f64>, max:Option<f64>, step:Option<f64>, format:Option<String> },
+}
+
+/// `command="crate::cmds::SAVE" command-payload="1" target="window"`
+pub(crate) struct Command {
+    pub selector : String,
+    pub payload : Option<String>,
+    pub target : Option<Target>,
+}
+
+#[derive(Clone)]
+pub(crate) enum Target {
+    Window,
+    Global,
+    /// `#id`
+    Widget(String),
+}
+
+/// Normal and pseudo class(in the order of the style sheet, 5 at most) styles
+pub(crate) struct StyleSpec {
+    pub normal : Styler,
+    pub pseudo : Vec<PseudoStyle>,
+}
+
+impl <'a> WidgetSpec<'a> {
+    /// Root of the template(the `fn` or the `list` item)
+    pub fn root(ctx:&Context<'a,'_>, template:&'a Template) -> Result<Self, Error> {
+        let params = template.resolve(None)?;
+        let params = AttributesWrapper::params( template.elem.span.start, &params );
+        Self::build(ctx, Some(&params), &[], &template.elem)
+    }
+
+    /// Error without position is at the element
+    pub fn build(ctx:&Context<'a,'_>, parameter:Option<&AttributesWrapper>, parents:&[&'a Element], elem:&'a Element) -> Result<Self, Error> {
+        Self::resolve(ctx, parameter, parents, elem).map_err( |e| e.or_at(elem.span.start) )
+    }
+
+    fn resolve(ctx:&Context<'a,'_>, parameter:Option<&AttributesWrapper>, parents:&[&'a Element], elem:&'a Element) -> Result<Self, Error> {
+        let tag = elem.tag();
+        let pos = elem.span.start;
+        let attrs = elem.attrs(parameter);
+        let childs:Vec<&'a Element> = elem.elements().collect();
+        let mut stack = parents.to_owned();
+        stack.push(elem);
+
+        let inline = attrs.get(b"style").map( |e| String::from_utf8_lossy(&e).into_owned() ).unwrap_or_default();
+        let (decls, pseudo) = styles(ctx, parents, elem, inline);
+
+        let text = template::interpolate(elem.text().trim(), parameter).into_owned();
+        let input_type = string(&attrs, b"type").unwrap_or_else( || "text".to_owned() );
+        let template_root = ctx.templates.values().any( |e| std::ptr::eq(&e.elem, elem) );
+        let data_type = std::iter::once(elem).chain( parents.iter().rev().copied() )
+            .filter( |e| ctx.templates.values().any( |t| std::ptr::eq(&t.elem, *e) ) )
+            .find_map( |e| e.attributes.get("lens").map( |e| e.trim() ) );
+        let child = |child:&'a Element, stack:&[&'a Element]| Self::build(ctx, parameter, stack, child).map( Box::new );
+
+        let kind = match tag {
+            "flex" => {
+                if childs.is_empty() {
+                    return Err(Error::InvalidFlexChildNum(pos))
+                }
+                let mut children = vec![];
+                for child in childs.iter() {
+                    let child_attrs = child.attrs(parameter);
+                    let flex = if child_attrs.get(b"flex").is_some() { Some( child_attrs.get_size("flex")? ) } else { None };
+                    if child.tag() == "spacer" {
+                        children.push( FlexChild::Spacer(flex) );
+                    } else {
+                        children.push( FlexChild::Child(Self::build(ctx, parameter, &stack, child)?, flex) );
+                    }
+                }
+                Kind::Flex {
+                    column : string(&attrs, b"direction").as_deref() == Some("column"),
+                    must_fill : opt(&attrs, "must_fill_main_axis")?,
+                    cross : match string(&attrs, b"cross_axis_alignment").as_deref() {
+                        None => None,
+                        Some("start") => Some(CrossAxisAlignment::Start),
+                        Some("center") => Some(CrossAxisAlignment::Center),
+                        Some("end") => Some(CrossAxisAlignment::End),
+                        Some("baseline") => Some(CrossAxisAlignment::Baseline),
+                        _ => return Err(Error::InvalidAttributeValue((pos, "cross_axis_alignment")))
+                    },
+                    main : match string(&attrs, b"axis_alignment").as_deref() {
+                        None => None,
+                        Some("start") => Some(MainAxisAlignment::Start),
+                        Some("center") => Some(MainAxisAlignment::Center),
+                        Some("end") => Some(MainAxisAlignment::End),
+                        Some("spacebetween") => Some(MainAxisAlignment::SpaceBetween),
+                        Some("spaceevenly") => Some(MainAxisAlignment::SpaceEvenly),
+                        Some("spacearound") => Some(MainAxisAlignment::SpaceAround),
+                        _ => return Err(Error::InvalidAttributeValue((pos, "axis_alignment")))
+                    },
+                    children
+                }
+            },
+
+            //inline markup : `<label>Hello <b>world</b></label>`
+            "label" | "button" => {
+                let text = if rich_text::has_markup(elem) {
+                    LabelText::Rich( rich_text::flatten(elem, parameter)? )
+                } else if let Some(text_lens) = string(&attrs, b"text-lens") {
+                    let binding = Binding::parse(&text_lens).ok_or( Error::InvalidAttributeValue((pos, "text-lens")) )?;
+                    check_format_spec(&binding, pos)?;
+                    LabelText::Bind( format!("{{{}}}", text_lens.trim()) )
+                } else if let Some(parts) = crate::bind::parse(&text) {
+                    for part in parts.iter() {
+                        if let crate::bind::Part::Bind(binding) = part {
+                            check_format_spec(binding, pos)?;
+                        }
+                    }
+                    LabelText::Bind( crate::bind::to_source(&parts) )
+                } else if text.is_empty() {
+                    LabelText::Static( tag.to_owned() )
+                } else {
+                    LabelText::Static( crate::bind::unescape(&text) )
+                };
+                Kind::Label {
+                    text,
+                    button : tag == "button",
+                    line_break : match string(&attrs, b"line-break").as_deref() {
+                        None => None,
+                        Some("wordwrap") => Some(LineBreaking::WordWrap),
+                        Some("clip") => Some(LineBreaking::Clip),
+                        Some("overflow") => Some(LineBreaking::Overflow),
+                        _ => return Err(Error::InvalidAttributeValue((pos, "line-break")))
+                    },
+                    align : decls.get("text-align").map( text_align ).transpose()?
+                }
+            },
+
+            //WARN : checkbox is none-standard
+            "checkbox" => Kind::Checkbox { text:if text.is_empty() { "Checkbox".to_owned() } else { text } },
+            "input" if input_type == "checkbox" => Kind::Checkbox { text:if text.is_empty() { "Checkbox".to_owned() } else { text } },
+
+            "textbox" | "input" if matches!(input_type.as_str(), "text" | "password" | "number") => Kind::TextBox {
+                multiline : opt(&attrs, "multiline")?.unwrap_or(false),
+                placeholder : string(&attrs, b"placeholder"),
+                value : if input_type == "number" {
+                    opt::<f64>(&attrs, "value")?.map( |e| e.to_string() )
+                } else {
+                    string(&attrs, b"value")
+                },
+                input : if input_type == "password" {
+                    Input::Password
+                } else if input_type == "number" || attrs.get(b"format").is_some() {
+                    Input::Number { min:opt(&attrs, "min")?, max:opt(&attrs, "max")?, step:opt(&attrs, "step")?, format:string(&attrs, b"format") }
+                } else {
+                    Input::Text
+                },
+                color : decls.get("color").map( |v| color(v, "color") ).transpose()?,
+                font_size : decls.get("font-size").map( font_size ).transpose()?,
+                align : decls.get("text-align").map( text_align ).transpose()?
+            },
+
+            //WARN : "image" is none-standard
+            "image" | "img" => Kind::Image {
+                src : attrs.get_as_result::<String>("src")?,
+                fit : decls.get("object-fit").map( object_fit ).transpose()?,
+                rendering : decls.get("image-rendering").map( image_rendering ).transpose()?
+            },
+
+            //WARN : list is none-standard
+            //`<list lens="App::items" item="row">` : `row` template for each item. item template is the root of the item data
+            "list" => {
+                let (item, template) = list_item(&attrs, ctx.templates)?;
+                Kind::List {
+                    template : Box::new( Self::root(ctx, template)? ),
+                    item,
+                    horizontal : string(&attrs, b"direction").as_deref() == Some("horizontal"),
+                    spacing : list_spacing(&attrs)?
+                }
+            },
+
+            "scroll" => {
+                if childs.len() != 1 {
+                    return Err(Error::InvalidScrollChildNum(pos))
+                }
+                Kind::Scroll { child:child(childs[0], &stack)? }
+            },
+
+            "slider" => Kind::Slider { min:opt(&attrs, "min")?.unwrap_or(0.), max:opt(&attrs, "max")?.unwrap_or(1.) },
+
+            "spinner" => Kind::Spinner {
+                color : match string(&attrs, b"color") {
+                    Some(v) => Some( color(&v, "color")? ),
+                    None => decls.get("color").map( |v| color(v, "color") ).transpose()?
+                }
+            },
+
+            "split" => {
+                if childs.len() != 2 {
+                    return Err(Error::InvalidSplitChildNum(pos))
+                }
+                Kind::Split {
+                    column : string(&attrs, b"direction").as_deref() == Some("column"),
+                    one : child(childs[0], &stack)?,
+                    two : child(childs[1], &stack)?,
+                    //druid panics out of the range
+                    split_point : match opt::<f64>(&attrs, "split_point")? {
+                        Some(v) if !(0. ..= 1.).contains(&v) => return Err(Error::InvalidAttributeValue((pos, "split_point"))),
+                        v => v
+                    },
+                    min_size : match string(&attrs, b"min_size") {
+                        Some(v) => Some( min_size(&v).ok_or( Error::InvalidAttributeValue((pos, "min_size")) )? ),
+                        None => None
+                    },
+                    bar_size : opt(&attrs, "bar_size")?,
+                    min_bar_area : opt(&attrs, "min_bar_area")?,
+                    draggable : opt(&attrs, "draggable")?,
+                    solid_bar : opt(&attrs, "solid_bar")?
+                }
+            },
+
+            //`<if lens="App::logged_in"> .. <else> .. </else></if>`
+            "if" => {
+                let (otherwise, then):(Vec<&'a Element>,Vec<&'a Element>) = childs.iter().copied().partition( |e| e.tag() == "else" );
+                if then.len() != 1 || otherwise.len() > 1 {
+                    return Err(Error::InvalidIfChildNum(pos))
+                }
+                let lens = attrs.get_as_result::<String>("lens")?;
+                LensPath::parse(&lens).ok_or( Error::InvalidAttributeValue((pos, "lens")) )?;
+                let otherwise = match otherwise.first().and_then( |e| e.elements().next().map( |child| (*e, child) ) ) {
+                    Some( (otherwise, otherwise_child) ) => {
+                        let mut else_stack = stack.clone();
+                        else_stack.push(otherwise);
+                        Some( child(otherwise_child, &else_stack)? )
+                    },
+                    None => None
+                };
+                let preview_else = match string(&attrs, b"preview").as_deref() {
+                    None | Some("then") => false,
+                    Some("else") => true,
+                    _ => return Err(Error::InvalidAttributeValue((pos, "preview")))
+                };
+                Kind::If { lens, then:child(then[0], &stack)?, otherwise, preview_else }
+            },
+
+            "stepper" => Kind::Stepper {
+                min : opt(&attrs, "min")?.unwrap_or(f64::MIN),
+                max : opt(&attrs, "max")?.unwrap_or(f64::MAX),
+                step : opt(&attrs, "step")?.unwrap_or(1.),
+                wraparound : opt(&attrs, "wraparound")?.unwrap_or(false)
+            },
+
+            "switch" => Kind::Switch,
+
+            //shapes are drawn in order
+            "painter" | "canvas" => {
+                let mut shapes = vec![];
+                for child in childs.iter() {
+                    let attrs:Vec<(String,String)> = child.attrs(parameter).resolved().iter().map( |e| (e.name.clone(), e.value.clone()) ).collect();
+                    let pairs:Vec<(&str,&str)> = attrs.iter().map( |e| (e.0.as_str(), e.1.as_str()) ).collect();
+                    shapes.push( Drawable::parse(child.tag(), &pairs).map_err( |e| e.or_at(child.span.start) )? );
+                }
+                Kind::Canvas { shapes }
+            },
+
+            //registered native widget overrides the template
+            _ if ctx.natives.map( |e| e.contains(&tag) ).unwrap_or(false) => Kind::Native {
+                tag,
+                attrs : attrs.resolved(),
+                children : childs.iter().map( |child| Self::build(ctx, parameter, &stack, child) ).collect::<Result<Vec<_>,_>>()?
+            },
+
+            _ => if let Some(template) = ctx.templates.get(tag) {
+                let params = template.resolve( Some(&attrs) )?;
+                let params = AttributesWrapper::params( pos, &params );
+                Kind::Template { root:Box::new( Self::build(ctx, Some(&params), &stack, &template.elem)? ) }
+            } else if ctx.natives.is_none() {
+                Kind::Native { tag, attrs:attrs.resolved(), children:vec![] }
+            } else {
+                let natives = ctx.natives.unwrap_or(&[]);
+                let known = schema::TAGS.iter().map( |e| e.tag ).chain( ctx.templates.keys().map( |e| e.as_str() ) ).chain( natives.iter().copied() );
+                return Err(Error::UnknownTag( (pos, tag.to_owned(), schema::suggest(tag, known).map( |e| e.to_owned() )) ))
+            }
+        };
+
+        let has_style = decls.has_styler() || decls.get("transition").is_some() || pseudo.iter().any( |(_, e)| e.has_styler() );
+        let style = if has_style {
+            let mut pseudo_styles = vec![];
+            for (pseudo, decls) in pseudo.iter().take(5) {
+                let styler = styler(decls)?;
+                pseudo_styles.push( match pseudo {
+                    Pseudo::Focus => PseudoStyle::focus(styler),
+                    Pseudo::Hover => PseudoStyle::hover(styler),
+                    Pseudo::Active => PseudoStyle::active(styler),
+                    Pseudo::Disabled => PseudoStyle::disabled(styler),
+                    Pseudo::Invalid => PseudoStyle::invalid(styler),
+                });
+            }
+            Some( StyleSpec { normal:styler(&decls)?, pseudo:pseudo_styles } )
+        } else {
+            None
+        };
+
+        let mut handlers = vec![];
+        for name in HANDLERS {
+            if let Some(handler) = string(&attrs, name.as_bytes()) {
+                handlers.push( (name, handler_path(pos, name, &handler)?) );
+            }
+        }
+        let command = match string(&attrs, b"command") {
+            Some(selector) => Some( Command {
+                selector : handler_path(pos, "command", &selector)?,
+                payload : string(&attrs, b"command-payload"),
+                target : match string(&attrs, b"target").as_deref().map( |e| e.trim() ) {
+                    None => None,
+                    Some("window") => Some(Target::Window),
+                    Some("global") => Some(Target::Global),
+                    Some(id) if id.len() > 1 && id.starts_with('#') => Some( Target::Widget(id[1..].to_owned()) ),
+                    _ => return Err(Error::InvalidAttributeValue((pos, "target")))
+                }
+            }),
+            None => None
+        };
+
+        let fixed = |name:&'static str| -> Result<Option<f64>, Error> {
+            match decls.get(name) {
+                Some(v) => size(v).map( Some ),
+                None => match string(&attrs, name.as_bytes()) {
+                    Some(v) => size(&v).map( Some ).map_err( |_| Error::InvalidAttributeValue((pos, name)) ),
+                    None => Ok(None)
+                }
+            }
+        };
+
+        Ok( Self {
+            elem,
+            parents : parents.to_owned(),
+            data_type,
+            lens : if tag != "if" && !template_root { string(&attrs, b"lens") } else { None },
+            id : string(&attrs, b"id"),
+            handlers,
+            command,
+            width : fixed("width")?,
+            height : fixed("height")?,
+            style,
+            kind,
+        })
+    }
+
+    /// `:invalid` state of the `ValueTextBox`
+    pub fn validated(&self) -> bool {
+        matches!(self.kind, Kind::TextBox { input:Input::Number { .. }, .. })
+    }
+}
+
+/// Declarations in the order of the priority
+#[derive(Default,Clone)]
+pub(crate) struct Declarations(Vec<(String,String)>);
+
+impl Declarations {
+    fn get(&self, name:&str) -> Option<&str> {
+        self.0.iter().find( |e| e.0 == name ).map( |e| e.1.as_str() )
+    }
+
+    fn has_styler(&self) -> bool {
+        STYLER_PROPERTIES.iter().any( |e| self.get(e).is_some() )
+    }
+}
+
+/// Cascaded declarations and the pseudo rules of the element
+fn styles(ctx:&Context, parents:&[&Element], elem:&Element, inline:String) -> (Declarations, Vec<(Pseudo, Declarations)>) {
+    let cascaded = |inline:&str| (cascade(ctx.css, parents, elem, inline), pseudo_rules(ctx.css, parents, elem));
+    match ctx.cache {
+        Some(cache) => {
+            let path = parents.iter().copied().chain( std::iter::once(elem) ).map( |e| e as *const Element as usize ).collect();
+            let mut cache = cache.borrow_mut();
+            cache.entry( (path, inline) ).or_insert_with_key( |(_, inline)| cascaded(inline) ).clone()
+        },
+        None => cascaded(&inline)
+    }
+}
+
+/// The inline style, then `#id`, `.class` and the tag rules.
+/// Reference : https://www.w3.org/TR/selectors/#specificity
+fn cascade(css:&StyleSheet, parents:&[&Element], elem:&Element, inline:&str) -> Declarations {
+    let query = ElementQueryWrap { parent_stack:parents, elem };
+    let mut decls:Vec<(String,String)> = DeclarationTokenizer::from(inline).map( |e| (e.name.to_owned(), e.value.to_owned()) ).collect();
+    for rank in 0..3 {
+        for rule in css.rules.iter() {
+            let spec = rule.selector.specificity();
+            let ranked = match rank {
+                0 => spec[0] > 0,
+                1 => spec[0] == 0 && spec[1] > 0,
+                _ => spec[0] == 0 && spec[1] == 0 && spec[2] > 0
+            };
+            if ranked && rule.selector.matches(&query) {
+                decls.extend( rule.declarations.iter().map( |e| (e.name.to_owned(), e.value.to_owned()) ) );
+            }
+        }
+    }
+    Declarations(decls)
+}
+
+/// Pseudo class rules of the element in the order of the style sheet
+fn pseudo_rules(css:&StyleSheet, parents:&[&Element], elem:&Element) -> Vec<(Pseudo, Declarations)> {
+    let mut rules = vec![];
+    for rule in css.rules.iter() {
+        let pseudo_trap_hack = PseudoOrderTrapQueryWrap::new( ElementQueryWrap { parent_stack:parents, elem } );
+        if rule.selector.matches(&pseudo_trap_hack) {
+            //simplecss doesn't support 'disabled' pseudo
+            let selector = rule.selector.to_string();
+            let is_disabled = simplecss::SelectorTokenizer::from( selector.as_str() )
+                .any( |e| matches!(e, Ok(simplecss::SelectorToken::PseudoClass("disabled"))) );
+            let pseudo = if is_disabled {
+                Some(Pseudo::Disabled)
+            } else {
+                pseudo_trap_hack.get_pseudo()
+            };
+            if let Some(pseudo) = pseudo {
+                let decls = rule.declarations.iter().map( |e| (e.name.to_owned(), e.value.to_owned()) ).collect();
+                rules.push( (pseudo, Declarations(decls)) );
+            }
+        }
+    }
+    rules
+}
+
+fn styler(decls:&Declarations) -> Result<Styler, Error> {
+    let transition = decls.get("transition");
+    let anim = |name:&str| transition.and_then( |e| self::transition(e, name) );
+    Ok( Styler {
+        padding : ( decls.get("padding").map( |v| insets(v, "padding") ).transpose()?, anim("padding") ),
+        margin : ( decls.get("margin").map( |v| insets(v, "margin") ).transpose()?, anim("margin") ),
+        font_size : ( decls.get("font-size").map( font_size ).transpose()?, anim("font-size") ),
+        width : ( decls.get("width").map( size ).transpose()?, anim("width") ),
+        height : ( decls.get("height").map( size ).transpose()?, anim("height") ),
+        text_color : ( decls.get("color").map( |v| color(v, "color") ).transpose()?, anim("color") ),
+        background_color : ( decls.get("background-color").map( |v| color(v, "background-color") ).transpose()?, anim("background-color") ),
+        border : ( decls.get("border").map( |v| border(v, decls.get("border-radius")) ).transpose()?, anim("border") ),
+    })
+}
+
+/// Split by the separator which is not in the parenthesis
+fn split_outside(v:&str, sep:impl Fn(char) -> bool) -> Vec<&str> {
+    let mut items = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in v.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            c if depth == 0 && sep(c) => {
+                items.push( &v[start..i] );
+                start = i + c.len_utf8();
+            },
+            _ => ()
+        }
+    }
+    items.push( &v[start..] );
+    items.into_iter().map( |e| e.trim() ).filter( |e| !e.is_empty() ).collect()
+}
+
+/// `0.3s`, `300ms` or `300`(ms) in nanoseconds
+fn time(v:&str) -> Option<i64> {
+    let v = v.trim().to_lowercase();
+    let (v, scale) = match v.strip_suffix("ms") {
+        Some(ms) => (ms, 1_000_000.),
+        None => match v.strip_suffix('s') {
+            Some(s) => (s, 1_000_000_000.),
+            None => (v.as_str(), 1_000_000.)
+        }
+    };
+    finite(&v).map( |e| (e * scale) as i64 )
+}
+
+fn timing_function(v:&str) -> Option<TimingFunction> {
+    let params = |name:&str| v.strip_prefix(name).and_then( |e| e.strip_suffix(')') ).map( |e| e.split(',').map( |e| e.trim() ).collect::<Vec<_>>() );
+    Some( match v {
+        "ease" => TimingFunction::Ease,
+        "ease-in" => TimingFunction::EaseIn,
+        "ease-out" => TimingFunction::EaseOut,
+        "ease-in-out" => TimingFunction::EaseInOut,
+        "linear" => TimingFunction::Linear,
+        "step-start" => TimingFunction::Steps { n:1., jumpterm:JumpTerm::Start },
+        "step-end" => TimingFunction::Steps { n:1., jumpterm:JumpTerm::End },
+        _ => if let Some(p) = params("cubic-bezier(") {
+            let p = |i:usize| p.get(i).and_then( |e| finite(e) ).unwrap_or(0.);
+            TimingFunction::CubicBezier { p1:p(0), p2:p(1), p3:p(2), p4:p(3) }
+        } else if let Some(p) = params("steps(") {
+            let jumpterm = match p.get(1).copied().unwrap_or("jump-start") {
+                "jump-end" => JumpTerm::JumpEnd,
+                "jump-none" => JumpTerm::JumpNone,
+                "jump-both" => JumpTerm::JumpBoth,
+                "start" => JumpTerm::Start,
+                "end" => JumpTerm::End,
+                _ => JumpTerm::JumpStart
+            };
+            TimingFunction::Steps { n:p.first().and_then( |e| finite(e) ).unwrap_or(1.), jumpterm }
+        } else {
+            return None
+        }
+    })
+}
+
+/// Animation of the property. `transition : color 0.3s ease-in 100ms, padding 1s`.
+/// The first time is the duration and the second is the delay
+fn transition(define:&str, property:&str) -> Option<AnimationState> {
+    for item in split_outside(define, |c| c == ',') {
+        let mut name = None;
+        let mut times = vec![];
+        let mut timing = TimingFunction::Linear;
+        for word in split_outside(item, char::is_whitespace) {
+            if word.starts_with( |c:char| c.is_ascii_digit() || c == '.' ) {
+                times.extend( time(word) );
+            } else if let Some(tf) = timing_function(word) {
+                timing = tf;
+            } else {
+                name = Some(word);
+            }
+        }
+        let duration = times.first().copied().unwrap_or(0);
+        if name == Some(property) && duration > 0 {
+            return Some( AnimationState::from( Animation {
+                delay : times.get(1).copied().unwrap_or(0),
+                direction : Direction::Normal,
+                duration,
+                iteration : 1.,
+                name : 1.,
+                timing_function : timing,
+                fill_mode : 0.
+            }))
+        }
+    }
+    None
+}
+
+/// `10px`, `1em` or `10`
+pub(crate) fn size(v:&str) -> Result<f64, Error> {
+    let tv = v.trim();
+    let (number, scale) = match tv.strip_suffix("px") {
+        Some(px) => (px, 1.),
+        None => match tv.strip_suffix("em") {
+            Some(em) => (em, 1. / 0.0625),
+            None => (tv, 1.)
+        }
+    };
+    finite(number).map( |e| e * scale ).ok_or( Error::InvalidSizeAttributeValue(0) )
+}
+
+//Reference : https://simplecss.eu/pxtoems.html or https://websemantics.uk/tools/font-size-conversion-pixel-point-em-rem-percent/
+pub(crate) fn font_size(v:&str) -> Result<f64, Error> {
+    let tv = v.trim();
+    let number = |v:&str, scale:f64| finite(v).map( |e| e * scale ).ok_or( Error::InvalidSizeAttributeValue(0) );
+    match tv {
+        "xx-small" => Ok(9.),
+        "x-small" => Ok(10.),
+        "small" => Ok(13.333),
+        "medium" => Ok(16.),
+        "large" => Ok(18.),
+        "x-large" => Ok(24.),
+        "xx-large" => Ok(32.),
+        _ => if let Some(px) = tv.strip_suffix("px") {
+            number(px, 1.)
+        } else if let Some(em) = tv.strip_suffix("em") {
+            number(em, 1. / 0.0625)
+        } else if let Some(pt) = tv.strip_suffix("pt") {
+            number(pt, 1.333)
+        } else if let Some(percent) = tv.strip_suffix('%') {
+            number(percent, 1. / 100. / 0.0625)
+        } else {
+            number(tv, 1.)
+        }
+    }
+}
+
+/// `10px`, `10px 5px`(vertical, horizontal) or `1 2 3 4`(left, top, right, bottom)
+fn insets(v:&str, name:&'static str) -> Result<Insets, Error> {
+    let values = v.split_whitespace().map( size ).collect::<Result<Vec<_>,_>>().map_err( |_| Error::InvalidAttributeValue((0, name)) )?;
+    match values.as_slice() {
+        [v] => Ok( Insets::uniform(*v) ),
+        [x, y] => Ok( Insets::uniform_xy(*x, *y) ),
+        [x0, y0, x1, y1] => Ok( Insets::new(*x0, *y0, *x1, *y1) ),
+        //The number of padding parameters must be one of 1,2,4
+        _ => Err(Error::InvalidAttributeValue((0, name)))
+    }
+}
+
+/// [O] : rgb(0,255,0), rgba(0,255,0,88), #FF33FF, #FF33FF22, red
+/// [X] : rgb(100%, 0, 25%, 2)
+pub(crate) fn color(v:&str, name:&'static str) -> Result<Color, Error> {
+    let tv = v.trim();
+    let args = |prefix:&str| tv.strip_prefix(prefix).and_then( |e| e.trim_start().strip_prefix('(') ).and_then( |e| e.strip_suffix(')') )
+        .map( |e| e.split(',').map( |e| e.trim().parse::<u8>().ok() ).collect::<Option<Vec<_>>>() );
+    let color = if tv.starts_with('#') {
+        Color::from_hex_str(tv).ok()
+    } else if let Some(rgba) = args("rgba") {
+        match rgba.as_deref() {
+            Some(&[r, g, b, a]) => Some( Color::rgba8(r, g, b, a) ),
+            _ => None
+        }
+    } else if let Some(rgb) = args("rgb") {
+        match rgb.as_deref() {
+            Some(&[r, g, b]) => Some( Color::rgb8(r, g, b) ),
+            _ => None
+        }
+    } else {
+        crate::named_color::named_color(tv).map( |_| crate::dynamic::color::to_color(tv, None) )
+    };
+    color.ok_or( Error::InvalidAttributeValue((0, name)) )
+}
+
+/// `1px solid red`. Only `solid` is supported
+fn border(v:&str, radius:Option<&str>) -> Result<BorderStyle, Error> {
+    let mut splited = v.split_whitespace();
+    let width = match splited.next() {
+        Some(v) => size(v).map_err( |_| Error::InvalidBorderAttributeValue(0) )?,
+        None => 1.
+    };
+    if splited.next().unwrap_or("solid") != "solid" {
+        return Err(Error::InvalidBorderAttributeValue(0))
+    }
+    let color = color(splited.next().unwrap_or("black"), "border")?;
+    let radius = radius.map( size ).transpose()?.unwrap_or(0.);
+    Ok( BorderStyle::new(width, radius, color) )
+}
+
+fn text_align(v:&str) -> Result<TextAlignment, Error> {
+    Ok( match v.trim() {
+        "left" => TextAlignment::Start,
+        "right" => TextAlignment::End,
+        "center" => TextAlignment::Center,
+        "justify" => TextAlignment::Justified,
+        _ => return Err(Error::InvalidAttributeValue((0, "text-align")))
+    })
+}
+
+//https://developer.mozilla.org/en-US/docs/Web/CSS/object-fit
+fn object_fit(v:&str) -> Result<FillStrat, Error> {
+    Ok( match v.trim() {
+        "none" => FillStrat::None, //Do not scale
+        "fill" | "" => FillStrat::Fill, //(default) Fill the widget with no dead space, aspect ratio of widget is used
+        "contain" => FillStrat::Contain, //As large as posible without changing aspect ratio of image and all of image shown
+        "cover" => FillStrat::Cover, //As large as posible with no dead space so that some of the image may be clipped
+        "scale-down" => FillStrat::ScaleDown, //Scale down to fit but do not scale up
+
+        //WARN : None-standard css attribute
+        "fit-width" => FillStrat::FitWidth, //Fill the width with the images aspect ratio, some of the image may be clipped
+        "fit-height" => FillStrat::FitHeight, //Fill the hight with the images aspect ratio, some of the image may be clipped
+        _ => return Err(Error::InvalidAttributeValue((0,"object-fit")))
+    })
+}
+
+//https://developer.mozilla.org/en-US/docs/Web/CSS/image-rendering
+fn image_rendering(v:&str) -> Result<InterpolationMode, Error> {
+    Ok( match v.trim() {
+        "auto" | "smooth" | "high-quality" => InterpolationMode::Bilinear,
+        "crisp-edges" | "pixelated" => InterpolationMode::NearestNeighbor,
+        _ => return Err(Error::InvalidAttributeValue((0,"image-rendering")))
+    })
+}
+
+/// `first,second` or the same size of both
+fn min_size(v:&str) -> Option<(f64,f64)> {
+    let sizes = v.split(',').map( |e| size(e).ok() ).collect::<Option<Vec<_>>>()?;
+    match sizes.as_slice() {
+        [both] => Some( (*both, *both) ),
+        [first, second] => Some( (*first, *second) ),
+        _ => None
+    }
+}
+
+/// Item template of the `list`
+fn list_item<'t>(attrs:&AttributesWrapper, templates:&'t HashMap<String,Template>) -> Result<(String, &'t Template), Error> {
+    let item = attrs.get_as_result::<String>("item")?;
+    match templates.get(&item) {
+        Some(template) => Ok( (item, template) ),
+        None => {
+            let suggestion = schema::suggest(&item, templates.keys().map( |e| e.as_str() )).map( |e| e.to_owned() );
+            Err(Error::UnknownTemplate( (attrs.pos(), item, suggestion) ))
+        }
+    }
+}
+
+/// `spacing` of the `list`. `4` or `4px`
+fn list_spacing(attrs:&AttributesWrapper) -> Result<Option<f64>, Error> {
+    match string(attrs, b"spacing") {
+        Some(v) => size(&v).map( Some ).map_err( |_| Error::InvalidAttributeValue((attrs.pos(), "spacing")) ),
+        None => Ok(None)
+    }
+}
+
+/// Rust function path of the handler attribute
+fn handler_path(pos:usize, name:&'static str, value:&str) -> Result<String, Error> {
+    let value = value.trim();
+    if crate::lens::is_path(value) {
+        Ok( value.to_owned() )
+    } else {
+        Err(Error::InvalidAttributeValue((pos, name)))
+    }
+}
+
+fn string(attrs:&AttributesWrapper, name:&[u8]) -> Option<String> {
+    attrs.get(name).map( |e| String::from_utf8_lossy(&e).into_owned() )
+}
+
+/// The format spec goes to `format!` as it is
+fn check_format_spec(binding:&Binding, pos:usize) -> Result<(), Error> {
+    match binding.spec {
+        Some(spec) if FormatSpec::parse(spec).is_none() => Err(Error::InvalidFormatSpec( (pos, spec.to_owned()) )),
+        _ => Ok(())
+    }
+}
+
+/// Typed value of the optional attribute
+fn opt<T:AttrValue>(attrs:&AttributesWrapper, name:&'static str) -> Result<Option<T>, Error> {
+    match attrs.get(name.as_bytes()) {
+        Some(_) => match attrs.get_as_result::<T>(name)? {
+            v if v.is_valid() => Ok( Some(v) ),
+            _ => Err(Error::InvalidAttributeValue((attrs.pos(), name)))
+        },
+        None => Ok(None)
+    }
+}
+
+/// Value of [`opt`]. It is written to the rust source as is
+trait AttrValue : FromStr {
+    fn is_valid(&self) -> bool {
+        true
+    }
+}
+
+impl AttrValue for bool {}
+
+//`inf` and `NaN` are not the rust literal
+impl AttrValue for f64 {
+    fn is_valid(&self) -> bool {
+        self.is_finite()
+    }
+}
+
+/// Finite number. `inf` and `NaN` are not the rust literal
+fn finite(v:&str) -> Option<f64> {
+    v.trim().parse::<f64>().ok().filter( |e| e.is_finite() )
+}
@@ -0,0 +1,213 @@
+//! Template and it's declared parameters.
+//!
+//! ```xml
+//! <label fn="title">
+//!     <param name="text" default="Untitled"/>
+//!     <param name="size" type="f64"/>
+//!     ${text}
+//! </label>
+//! ```
+//!
+//! `<param>` without `default` is required. `${key:default}` has the inline default value.
+//! Placeholders can be anywhere in the text and attribute values(`Hello ${name}`) and `$${` is the literal `${`.
+
+use std::borrow::Cow;
+
+use crate::{Element, Error, Attributes, AttributesWrapper, AttributeGetter, Node};
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub(crate) enum ParamType {
+    String,
+    F64,
+    Bool,
+    Color,
+}
+
+impl ParamType {
+    fn parse(pos:usize, v:&str) -> Result<Self, Error> {
+        match v {
+            "string" => Ok(ParamType::String),
+            "f64" => Ok(ParamType::F64),
+            "bool" => Ok(ParamType::Bool),
+            "color" => Ok(ParamType::Color),
+            _ => Err(Error::InvalidAttributeValue( (pos, "type") ))
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ParamType::String => "string",
+            ParamType::F64 => "f64",
+            ParamType::Bool => "bool",
+            ParamType::Color => "color",
+        }
+    }
+
+    fn is_valid(&self, v:&str) -> bool {
+        let v = v.trim();
+        match self {
+            ParamType::String => true,
+            ParamType::F64 => v.parse::<f64>().is_ok(),
+            ParamType::Bool => v == "true" || v == "false",
+            ParamType::Color => crate::spec::color(v, "color").is_ok(),
+        }
+    }
+}
+
+#[derive(Debug,Clone)]
+pub(crate) struct Param {
+    pub name : String,
+    pub default : Option<String>,
+    pub ty : ParamType,
+}
+
+/// Template element without `<param>` and it's parameters
+#[derive(Debug,Clone)]
+pub(crate) struct Template {
+    pub elem : Element,
+    pub params : Vec<Param>,
+}
+
+impl Template {
+    pub fn new(elem:&Element) -> Result<Self, Error> {
+        let mut elem = elem.clone();
+        let mut params = vec![];
+        for child in elem.elements().filter( |e| e.tag() == "param" ) {
+            let pos = child.span.start;
+            let name = child.attributes.get("name").ok_or( Error::AttributeRequired((pos, "name")) )?;
+            let ty = ParamType::parse(pos, child.attributes.get("type").unwrap_or("string"))?;
+            let default = child.attributes.get("default").map( |e| e.to_owned() );
+            if let Some(default) = default.as_ref() {
+                if !ty.is_valid(default) {
+                    return Err(Error::InvalidParamValue( (pos, name.to_owned(), ty.name()) ))
+                }
+            }
+            params.push( Param { name:name.to_owned(), default, ty } );
+        }
+        elem.children.retain( |e| !matches!(e, Node::Element(e) if e.tag() == "param") );
+        Ok( Self { elem, params } )
+    }
+
+    /// Parameter values of the template usage(`None` : top level). Declared defaults are applied and checked
+    pub fn resolve(&self, usage:Option<&AttributesWrapper>) -> Result<Attributes, Error> {
+        let pos = usage.map( |e| e.pos() ).unwrap_or( self.elem.span.start );
+        let mut values = Attributes::new();
+        if let Some(usage) = usage {
+            for attr in usage.attrs.iter() {
+                //substituted with the parameter of the outer template
+                if let Some(v) = usage.get( attr.name.as_bytes() ) {
+                    values.set( &attr.name, &String::from_utf8_lossy(&v) );
+                }
+            }
+        }
+
+        for param in self.params.iter() {
+            match (values.get(&param.name), param.default.as_ref()) {
+                (Some(v), _) => if !param.ty.is_valid(v) {
+                    return Err(Error::InvalidParamValue( (pos, param.name.clone(), param.ty.name()) ))
+                },
+                (None, Some(default)) => values.set( &param.name, default ),
+                (None, None) => return Err(Error::ParamRequired( (pos, param.name.clone()) ))
+            }
+        }
+
+        //undeclared parameter without the inline default
+        let mut missing = None;
+        visit_placeholders(&self.elem, &mut |key, default| {
+            if missing.is_none() && default.is_none() && !values.contains(key) {
+                missing = Some(key.to_owned());
+            }
+        });
+        match missing {
+            Some(key) => Err(Error::ParamRequired( (pos, key) )),
+            None => Ok( values )
+        }
+    }
+}
+
+#[derive(Debug,PartialEq)]
+pub(crate) enum Segment<'a> {
+    Text(&'a str),
+    /// `${key}` or `${key:default}`
+    Param(&'a str, Option<&'a str>),
+}
+
+/// `Hello ${name}, $${literal}` => `Hello `, `name`, `, `, `${`, `literal}`.
+/// Unclosed `${` is the text
+pub(crate) fn segments(value:&str) -> Vec<Segment> {
+    let mut segments = vec![];
+    let mut rest = value;
+    while let Some(at) = rest.find("${") {
+        //escaped
+        if at > 0 && rest.as_bytes()[at-1] == b'$' {
+            if at > 1 {
+                segments.push( Segment::Text(&rest[..at-1]) );
+            }
+            segments.push( Segment::Text("${") );
+            rest = &rest[at+2..];
+            continue
+        }
+        let end = match rest[at..].find('}') {
+            Some(end) => at + end,
+            None => break
+        };
+        if at > 0 {
+            segments.push( Segment::Text(&rest[..at]) );
+        }
+        let inner = &rest[at+2 .. end];
+        segments.push( match inner.split_once(':') {
+            Some((key, default)) => Segment::Param(key.trim(), Some(default)),
+            None => Segment::Param(inner.trim(), None)
+        });
+        rest = &rest[end+1..];
+    }
+    if !rest.is_empty() {
+        segments.push( Segment::Text(rest) );
+    }
+    segments
+}
+
+/// Replace the placeholders with the parameters. Unknown key without default is kept
+pub(crate) fn interpolate<'a>(value:&'a str, params:Option<&AttributesWrapper>) -> Cow<'a, str> {
+    if !value.contains("${") {
+        return Cow::Borrowed(value)
+    }
+    let mut r = String::with_capacity(value.len());
+    for segment in segments(value) {
+        match segment {
+            Segment::Text(t) => r.push_str(t),
+            Segment::Param(key, default) => {
+                match params.and_then( |e| e.get(key.as_bytes()) ) {
+                    Some(v) => r.push_str( &String::from_utf8_lossy(&v) ),
+                    None => match default {
+                        Some(default) => r.push_str(default),
+                        None => { r.push_str("${"); r.push_str(key); r.push('}'); }
+                    }
+                }
+            }
+        }
+    }
+    Cow::Owned(r)
+}
+
+fn visit_placeholders<'a>(elem:&'a Element, f:&mut impl FnMut(&'a str, Option<&'a str>)) {
+    let mut visit = |value:&'a str| {
+        for segment in segments(value) {
+            if let Segment::Param(key, default) = segment {
+                f(key, default);
+            }
+        }
+    };
+    for attr in elem.attributes.iter() {
+        visit(&attr.value);
+    }
+    for child in elem.children.iter() {
+        match child {
+            Node::Text(t) | Node::CData(t) => visit(&t.content),
+            _ => ()
+        }
+    }
+    for child in elem.elements() {
+        visit_placeholders(child, f);
+    }
+}
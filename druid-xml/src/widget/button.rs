@@ -15,6 +15,7 @@ const LABEL_INSETS: Insets = Insets::uniform_xy(8., 2.);
 /// A button with a text label.
 pub struct DXButton<T> {
     label: DXLabel<T>,
+    label_size: Size,
 }
 
 impl<T: Data> DXButton<T> {
@@ -39,7 +40,7 @@ impl<T: Data> DXButton<T> {
     }
 
     pub fn from_label(label: DXLabel<T>) -> DXButton<T> {
-        Self { label }
+        Self { label, label_size: Size::ZERO }
     }
 
     pub fn dynamic(text: impl Fn(&T, &Env) -> String + 'static) -> Self {
@@ -70,10 +71,61 @@ impl<T: Data> Widget<T> for DXButton<T> {
     }
 
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, data: &T, env: &Env) -> Size {
-        self.label.layout(ctx, bc, data, env)
+        if is_styled(env) {
+            return self.label.layout(ctx, bc, data, env)
+        }
+        //not wrapped by `SimpleStyleWidget` : same as druid `Button`
+        let padding = Size::new(LABEL_INSETS.x_value(), LABEL_INSETS.y_value());
+        let label_bc = bc.shrink(padding).loosen();
+        self.label_size = self.label.layout(ctx, &label_bc, data, env);
+        let min_height = env.get(druid::theme::BORDERED_WIDGET_HEIGHT);
+        bc.constrain(Size::new(
+            self.label_size.width + padding.width,
+            (self.label_size.height + padding.height).max(min_height),
+        ))
     }
 
     fn paint(&mut self, ctx: &mut PaintCtx, data: &T, env: &Env) {
-        self.label.paint(ctx, data, env);
+        if is_styled(env) {
+            return self.label.paint(ctx, data, env)
+        }
+        let is_active = ctx.is_active() && !ctx.is_disabled();
+        let is_hot = ctx.is_hot();
+        let size = ctx.size();
+        let stroke_width = env.get(druid::theme::BUTTON_BORDER_WIDTH);
+
+        let rounded_rect = size
+            .to_rect()
+            .inset(-stroke_width / 2.0)
+            .to_rounded_rect(env.get(druid::theme::BUTTON_BORDER_RADIUS));
+
+        let (light, dark) = if ctx.is_disabled() {
+            (env.get(druid::theme::DISABLED_BUTTON_LIGHT), env.get(druid::theme::DISABLED_BUTTON_DARK))
+        } else if is_active {
+            (env.get(druid::theme::BUTTON_DARK), env.get(druid::theme::BUTTON_LIGHT))
+        } else {
+            (env.get(druid::theme::BUTTON_LIGHT), env.get(druid::theme::BUTTON_DARK))
+        };
+        let bg_gradient = LinearGradient::new(UnitPoint::TOP, UnitPoint::BOTTOM, (light, dark));
+
+        let border_color = if is_hot && !ctx.is_disabled() {
+            env.get(druid::theme::BORDER_LIGHT)
+        } else {
+            env.get(druid::theme::BORDER_DARK)
+        };
+
+        ctx.stroke(rounded_rect, &border_color, stroke_width);
+        ctx.fill(rounded_rect, &bg_gradient);
+
+        let label_offset = (size.to_vec2() - self.label_size.to_vec2()) / 2.0;
+        ctx.with_save(|ctx| {
+            ctx.transform(Affine::translate(label_offset));
+            self.label.paint(ctx, data, env);
+        });
     }
-}
\ No newline at end of file
+}
+
+/// `SimpleStyleWidget` draws the background and border
+fn is_styled(env:&Env) -> bool {
+    env.try_get(theme::STYLE_UPDATED).is_ok()
+}
@@ -0,0 +1,98 @@
+//!Controller for the event handler attributes(`onchange`, `onfocus`, `onblur`, `ondblclick`)
+//!`onclick` is `WidgetExt::on_click`
+//!
+//!The controller wraps the widget before the `lens`, so the data is the data of the widget
+
+use druid::widget::Controller;
+use druid::{Data, Env, Event, EventCtx, LifeCycle, LifeCycleCtx, Selector, Widget};
+
+/// Named rust function of the handler attribute
+pub type Handler<T> = fn(&mut EventCtx, &mut T, &Env);
+
+/// `lifecycle` has no `EventCtx` and mutable data. Deferred to `event` by the command
+const DEFERRED:Selector<Deferred> = Selector::new("druid-xml.handler.deferred");
+
+#[derive(Debug,Clone,Copy)]
+enum Deferred {
+    Focus,
+    Blur,
+}
+
+pub struct EventHandler<T> {
+    change : Option<Handler<T>>,
+    focus : Option<Handler<T>>,
+    blur : Option<Handler<T>>,
+    dblclick : Option<Handler<T>>,
+}
+
+impl <T> EventHandler<T> {
+    pub fn new() -> Self {
+        Self { change:None, focus:None, blur:None, dblclick:None }
+    }
+
+    /// The wrapped widget changed the data while it handled the event(typing, click, paste ...).
+    /// The data changed by the application or the other widgets is not the change of this widget
+    pub fn on_change(mut self, f:Handler<T>) -> Self {
+        self.change = Some(f);
+        self
+    }
+
+    pub fn on_focus(mut self, f:Handler<T>) -> Self {
+        self.focus = Some(f);
+        self
+    }
+
+    pub fn on_blur(mut self, f:Handler<T>) -> Self {
+        self.blur = Some(f);
+        self
+    }
+
+    pub fn on_dblclick(mut self, f:Handler<T>) -> Self {
+        self.dblclick = Some(f);
+        self
+    }
+}
+
+impl <T:Data, W:Widget<T>> Controller<T, W> for EventHandler<T> {
+    fn event(&mut self, child: &mut W, ctx: &mut EventCtx, event: &Event, data: &mut T, env: &Env) {
+        match event {
+            Event::MouseDown(mouse) if mouse.count == 2 => {
+                if let Some(f) = self.dblclick {
+                    f(ctx, data, env);
+                }
+            },
+            Event::Command(cmd) if cmd.is(DEFERRED) => {
+                let f = match cmd.get_unchecked(DEFERRED) {
+                    Deferred::Focus => self.focus,
+                    Deferred::Blur => self.blur,
+                };
+                if let Some(f) = f {
+                    f(ctx, data, env);
+                }
+                ctx.set_handled();
+                return
+            },
+            _ => ()
+        }
+        match self.change {
+            Some(f) => {
+                let before = data.clone();
+                child.event(ctx, event, data, env);
+                if !before.same(data) {
+                    f(ctx, data, env);
+                }
+            },
+            None => child.event(ctx, event, data, env)
+        }
+    }
+
+    fn lifecycle(&mut self, child: &mut W, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
+        if let LifeCycle::FocusChanged(focus) = event {
+            let (deferred, f) = if *focus { (Deferred::Focus, self.focus) } else { (Deferred::Blur, self.blur) };
+            if f.is_some() {
+                ctx.submit_command( DEFERRED.with(deferred).to(ctx.widget_id()) );
+            }
+        }
+        child.lifecycle(ctx, event, data, env)
+    }
+}
@@ -0,0 +1,170 @@
+//!Typed text input(`password`, `number`, `format`) and the validation state for `:invalid`
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use druid::text::{Formatter, Selection, Validation, ValidationError};
+use druid::widget::{TextBoxEvent, ValidationDelegate};
+use druid::{EventCtx, Lens};
+
+/// Validation state of the `ValueTextBox`. Shared with `SimpleStyleWidget` for `:invalid` style
+#[derive(Debug,Clone,Default)]
+pub struct InvalidState(Rc<Cell<bool>>);
+
+impl InvalidState {
+    pub fn is_invalid(&self) -> bool {
+        self.0.get()
+    }
+}
+
+impl ValidationDelegate for InvalidState {
+    fn event(&mut self, _ctx: &mut EventCtx, event: TextBoxEvent, _current_text: &str) {
+        match event {
+            TextBoxEvent::PartiallyInvalid(_) | TextBoxEvent::Invalid(_) => self.0.set(true),
+            TextBoxEvent::Changed | TextBoxEvent::Complete | TextBoxEvent::Cancel => self.0.set(false),
+            TextBoxEvent::Began => ()
+        }
+    }
+}
+
+#[derive(Debug,Clone,PartialEq)]
+pub enum NumberError {
+    NotNumber,
+    Less(f64),
+    Greater(f64),
+    Step(f64),
+}
+
+impl std::fmt::Display for NumberError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumberError::NotNumber => write!(f, "not a number"),
+            NumberError::Less(min) => write!(f, "must be greater than or equal to {min}"),
+            NumberError::Greater(max) => write!(f, "must be less than or equal to {max}"),
+            NumberError::Step(step) => write!(f, "must be a multiple of {step}"),
+        }
+    }
+}
+
+impl std::error::Error for NumberError {}
+
+/// `f64` formatter with the range, step and `format="0.00"` precision
+#[derive(Debug,Clone,Default)]
+pub struct NumberFormatter {
+    min : Option<f64>,
+    max : Option<f64>,
+    step : Option<f64>,
+    precision : Option<usize>,
+}
+
+impl NumberFormatter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn min(mut self, min:f64) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    pub fn max(mut self, max:f64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Valid values are `min + step * n`
+    pub fn step(mut self, step:f64) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    /// `0`, `0.0`, `0.00` ... Digits after `.` are the precision
+    pub fn format(mut self, format:&str) -> Self {
+        self.precision = Some( format.split_once('.').map( |(_, fraction)| fraction.len() ).unwrap_or(0) );
+        self
+    }
+
+    /// Check the value
+    pub fn check(&self, v:f64) -> Result<f64, NumberError> {
+        if let Some(min) = self.min.filter( |min| v < *min ) {
+            return Err(NumberError::Less(min))
+        }
+        if let Some(max) = self.max.filter( |max| v > *max ) {
+            return Err(NumberError::Greater(max))
+        }
+        if let Some(step) = self.step.filter( |step| *step > 0. ) {
+            let n = (v - self.min.unwrap_or(0.)) / step;
+            if (n - n.round()).abs() > 1e-9 {
+                return Err(NumberError::Step(step))
+            }
+        }
+        Ok(v)
+    }
+}
+
+impl Formatter<f64> for NumberFormatter {
+    fn format(&self, value: &f64) -> String {
+        match self.precision {
+            Some(precision) => format!("{value:.precision$}"),
+            None => value.to_string()
+        }
+    }
+
+    fn validate_partial_input(&self, input: &str, _sel: &Selection) -> Validation {
+        //incomplete number while typing
+        if matches!(input, "" | "-" | "." | "-.") || input.parse::<f64>().is_ok() {
+            Validation::success()
+        } else {
+            Validation::failure( NumberError::NotNumber )
+        }
+    }
+
+    fn value(&self, input: &str) -> Result<f64, ValidationError> {
+        input.trim().parse::<f64>()
+            .map_err( |_| NumberError::NotNumber )
+            .and_then( |v| self.check(v) )
+            .map_err( ValidationError::new )
+    }
+}
+
+const MASK:char = '•';
+
+/// Masked text of the password. Edit of the masked text is applied to the data
+pub fn password() -> impl Lens<String, String> {
+    PasswordLens
+}
+
+struct PasswordLens;
+
+impl Lens<String, String> for PasswordLens {
+    fn with<V, F: FnOnce(&String) -> V>(&self, data: &String, f: F) -> V {
+        f( &mask(data) )
+    }
+
+    fn with_mut<V, F: FnOnce(&mut String) -> V>(&self, data: &mut String, f: F) -> V {
+        let old = mask(data);
+        let mut masked = old.clone();
+        let r = f(&mut masked);
+        if masked != old {
+            *data = unmask(data, &masked);
+        }
+        r
+    }
+}
+
+fn mask(v:&str) -> String {
+    std::iter::repeat(MASK).take( v.chars().count() ).collect()
+}
+
+/// Unchanged head and tail of the masked text are the original characters
+pub(crate) fn unmask(origin:&str, masked:&str) -> String {
+    let origin:Vec<char> = origin.chars().collect();
+    let masked:Vec<char> = masked.chars().collect();
+    let max = origin.len().min(masked.len());
+    let head = masked.iter().take(max).take_while( |c| **c == MASK ).count();
+    let tail = masked.iter().rev().take(max - head).take_while( |c| **c == MASK ).count();
+    origin[..head].iter()
+        .chain( masked[head .. masked.len()-tail].iter() )
+        .chain( origin[origin.len()-tail ..].iter() )
+        .collect()
+}
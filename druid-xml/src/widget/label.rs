@@ -1,22 +1,64 @@
 ///Label wrapper for dynamic style(text-size, color)
 
-use druid::{widget::{Label, LabelText, Axis, LineBreaking}, Data, Widget, EventCtx, Event, Env, LifeCycleCtx, LifeCycle, LayoutCtx, BoxConstraints, PaintCtx, UpdateCtx, Size};
+use druid::{widget::{Label, LabelText, RawLabel, Axis, LineBreaking}, text::RichText, TextAlignment, Data, Widget, EventCtx, Event, Env, LifeCycleCtx, LifeCycle, LayoutCtx, BoxConstraints, PaintCtx, UpdateCtx, Size, Color};
 
 use super::theme;
 
+enum Origin<T> {
+    Plain(Label<T>),
+    /// `RawLabel` and it's constant data
+    Rich(RawLabel<RichText>, RichText),
+}
+
 pub struct DXLabel<T> {
-    origin : Label<T>
+    origin : Origin<T>
 }
 
 impl <T:Data> DXLabel<T> {
     pub fn new(text: impl Into<LabelText<T>>) -> Self {
         Self {
-            origin : Label::new(text)
+            origin : Origin::Plain( Label::new(text) )
+        }
+    }
+
+    /// Text from the data. Same as `Label::dynamic`
+    pub fn dynamic(text: impl Fn(&T, &Env) -> String + 'static) -> Self {
+        Self::new(text)
+    }
+
+    /// Label with the inline styled text(bold, italic, color ...)
+    pub fn rich(text: RichText) -> Self {
+        Self {
+            origin : Origin::Rich( RawLabel::new(), text )
         }
     }
 
     pub fn set_line_break_mode(&mut self, mode:LineBreaking ) {
-        self.origin.set_line_break_mode(mode);
+        match &mut self.origin {
+            Origin::Plain(label) => label.set_line_break_mode(mode),
+            Origin::Rich(label, _) => label.set_line_break_mode(mode),
+        }
+    }
+
+    pub fn set_text_alignment(&mut self, align:TextAlignment) {
+        match &mut self.origin {
+            Origin::Plain(label) => label.set_text_alignment(align),
+            Origin::Rich(label, _) => label.set_text_alignment(align),
+        }
+    }
+
+    fn set_text_size(&mut self, size:f64) {
+        match &mut self.origin {
+            Origin::Plain(label) => label.set_text_size(size),
+            Origin::Rich(label, _) => label.set_text_size(size),
+        }
+    }
+
+    fn set_text_color(&mut self, color:Color) {
+        match &mut self.origin {
+            Origin::Plain(label) => label.set_text_color(color),
+            Origin::Rich(label, _) => label.set_text_color(color),
+        }
     }
 }
 
@@ -25,26 +67,39 @@ impl<T: Data> Widget<T> for DXLabel<T> {
     fn event(&mut self, _ctx: &mut EventCtx, _event: &Event, _data: &mut T, _env: &Env) {}
 
     fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, data: &T, env: &Env) {
-        self.origin.lifecycle(ctx, event, data, env);
+        match &mut self.origin {
+            Origin::Plain(label) => label.lifecycle(ctx, event, data, env),
+            Origin::Rich(label, text) => label.lifecycle(ctx, event, text, env),
+        }
     }
 
     fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &T, data: &T, env: &Env) {
-        self.origin.update(ctx, _old_data, data, env);
+        match &mut self.origin {
+            Origin::Plain(label) => label.update(ctx, _old_data, data, env),
+            Origin::Rich(label, text) => label.update(ctx, text, text, env),
+        }
     }
 
     fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, _data: &T, env: &Env) -> Size {
-        if env.get(theme::STYLE_UPDATED) == theme::STYLE_UPDATED_LAYOUT {
-            self.origin.set_text_size(env.get(theme::FONT_SIZE));
-            self.origin.set_text_color(env.get(theme::COLOR));
+        //without `SimpleStyleWidget` there is no style key
+        if let Ok(theme::STYLE_UPDATED_LAYOUT) = env.try_get(theme::STYLE_UPDATED) {
+            self.set_text_size(env.get(theme::FONT_SIZE));
+            self.set_text_color(env.get(theme::COLOR));
+        }
+        match &mut self.origin {
+            Origin::Plain(label) => label.layout(ctx, bc, _data, env),
+            Origin::Rich(label, text) => label.layout(ctx, bc, text, env),
         }
-        self.origin.layout(ctx, bc, _data, env)
     }
 
     fn paint(&mut self, ctx: &mut PaintCtx, _data: &T, env: &Env) {
-        if env.get(theme::STYLE_UPDATED) == theme::STYLE_UPDATED_PAINT {
-            self.origin.set_text_color(env.get(theme::COLOR));
+        if let Ok(theme::STYLE_UPDATED_PAINT) = env.try_get(theme::STYLE_UPDATED) {
+            self.set_text_color(env.get(theme::COLOR));
+        }
+        match &mut self.origin {
+            Origin::Plain(label) => label.paint(ctx, _data, env),
+            Origin::Rich(label, text) => label.paint(ctx, text, env),
         }
-        self.origin.paint(ctx, _data, env)
     }
 
     fn compute_max_intrinsic(
@@ -55,6 +110,9 @@ impl<T: Data> Widget<T> for DXLabel<T> {
         _data: &T,
         env: &Env,
     ) -> f64 {
-        self.origin.compute_max_intrinsic(axis, ctx, bc, _data, env)
+        match &mut self.origin {
+            Origin::Plain(label) => label.compute_max_intrinsic(axis, ctx, bc, _data, env),
+            Origin::Rich(label, text) => label.compute_max_intrinsic(axis, ctx, bc, text, env),
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,92 @@
+//!Widget which owns its data. The data of the parent is ignored
+//!The value is shared with [`LocalStateHandle`] to read and write it from outside
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use druid::kurbo::Point;
+use druid::{BoxConstraints, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx, Selector, Size, UpdateCtx, Widget, WidgetPod};
+
+/// Apply the values written by [`LocalStateHandle::set`]. Any event applies them too
+pub const REFRESH:Selector = Selector::new("druid-xml.local-state.refresh");
+
+struct Shared<T> {
+    value : T,
+    /// written by the handle
+    changed : bool,
+}
+
+pub struct LocalState<T> {
+    state : T,
+    shared : Rc<RefCell<Shared<T>>>,
+    inner : WidgetPod<T, Box<dyn Widget<T>>>,
+}
+
+impl <T:Data> LocalState<T> {
+    pub fn new(inner:impl Widget<T> + 'static, state:T) -> Self {
+        let shared = Rc::new( RefCell::new( Shared { value:state.clone(), changed:false } ) );
+        Self { state, shared, inner:WidgetPod::new( Box::new(inner) ) }
+    }
+
+    pub fn handle(&self) -> LocalStateHandle<T> {
+        LocalStateHandle( self.shared.clone() )
+    }
+}
+
+#[derive(Clone)]
+pub struct LocalStateHandle<T>(Rc<RefCell<Shared<T>>>);
+
+impl <T:Data> LocalStateHandle<T> {
+    pub fn get(&self) -> T {
+        self.0.borrow().value.clone()
+    }
+
+    /// Applied on the next event of the widget(or [`REFRESH`] command)
+    pub fn set(&self, value:T) {
+        let mut shared = self.0.borrow_mut();
+        shared.value = value;
+        shared.changed = true;
+    }
+}
+
+impl <T:Data, D:Data> Widget<D> for LocalState<T> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, _data: &mut D, env: &Env) {
+        {
+            let mut shared = self.shared.borrow_mut();
+            if shared.changed {
+                shared.changed = false;
+                self.state = shared.value.clone();
+                ctx.request_update();
+            }
+        }
+        if let Event::Command(cmd) = event {
+            if cmd.is(REFRESH) {
+                return
+            }
+        }
+        self.inner.event(ctx, event, &mut self.state, env);
+        let mut shared = self.shared.borrow_mut();
+        if !shared.value.same(&self.state) {
+            shared.value = self.state.clone();
+            ctx.request_update();
+        }
+    }
+
+    fn lifecycle(&mut self, ctx: &mut LifeCycleCtx, event: &LifeCycle, _data: &D, env: &Env) {
+        self.inner.lifecycle(ctx, event, &self.state, env)
+    }
+
+    fn update(&mut self, ctx: &mut UpdateCtx, _old_data: &D, _data: &D, env: &Env) {
+        self.inner.update(ctx, &self.state, env)
+    }
+
+    fn layout(&mut self, ctx: &mut LayoutCtx, bc: &BoxConstraints, _data: &D, env: &Env) -> Size {
+        let size = self.inner.layout(ctx, bc, &self.state, env);
+        self.inner.set_origin(ctx, Point::ORIGIN);
+        size
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, _data: &D, env: &Env) {
+        self.inner.paint(ctx, &self.state, env)
+    }
+}
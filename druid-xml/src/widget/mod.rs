@@ -2,8 +2,29 @@ mod drawable;
 pub mod label;
 pub mod button;
 pub mod style_widget;
+pub mod handler;
+pub mod input;
+pub mod local_state;
 pub mod theme;
 
 pub use label::DXLabel;
 pub use button::DXButton;
-pub use style_widget::SimpleStyleWidget;
\ No newline at end of file
+pub use style_widget::SimpleStyleWidget;
+pub use handler::{EventHandler, Handler};
+pub use input::{InvalidState, NumberFormatter};
+pub use local_state::{LocalState, LocalStateHandle};
+
+/// `WidgetId` of the element `id`. Target of the `target="#id"` command.
+/// Allocated by `WidgetId::next()` at the first call and the same `id` is the same `WidgetId` after that
+pub fn widget_id(id:&str) -> druid::WidgetId {
+    static IDS:std::sync::Mutex<Vec<(String, druid::WidgetId)>> = std::sync::Mutex::new(Vec::new());
+    let mut ids = IDS.lock().unwrap_or_else( |e| e.into_inner() );
+    match ids.iter().find( |(name, _)| name == id ) {
+        Some((_, widget_id)) => *widget_id,
+        None => {
+            let widget_id = druid::WidgetId::next();
+            ids.push( (id.to_owned(), widget_id) );
+            widget_id
+        }
+    }
+}
@@ -4,6 +4,7 @@ use druid::widget::Axis;
 use druid::{BoxConstraints, Color, Data, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx,
     RenderContext, UpdateCtx, Widget, WidgetPod, MouseButton};
 use super::theme;
+use super::input::InvalidState;
 
 /// A StyleWidget for `hover` and `animation` effect
 /// This widget changed `Env` and support `Padding` and `Conatainer`
@@ -11,17 +12,21 @@ use super::theme;
 /// Styles declared later take precedence. (like CSS)
 pub struct SimpleStyleWidget<T,W> {
 	normal_style : Styler,
-	styles : [Option<PseudoStyle>;4],
+	styles : [Option<PseudoStyle>;5],
+	/// validation state of the inner input for `:invalid`
+	invalid : Option<InvalidState>,
 
 	last_focus : bool,
 	last_hover : bool,
 	last_active : bool,
 	last_disabled : bool,
+	last_invalid : bool,
 
 	has_focus_style : bool,
 	has_hover_style : bool,
 	has_active_style : bool,
 	has_disabled_style : bool,
+	has_invalid_style : bool,
 
 	style_updated : u64,
 	start_style : Style,
@@ -34,7 +39,7 @@ pub struct SimpleStyleWidget<T,W> {
 }
 
 impl<T,W:Widget<T>> SimpleStyleWidget<T,W> {
-    pub fn new(normal_style:Styler, styles:[Option<PseudoStyle>;4], inner: W) -> SimpleStyleWidget<T,W> {
+    pub fn new(normal_style:Styler, styles:[Option<PseudoStyle>;5], inner: W) -> SimpleStyleWidget<T,W> {
 		let padding = normal_style.get_padding().unwrap_or_default();
 		let margin = normal_style.get_margin().unwrap_or_default();
 		let font_size = normal_style.get_font_size().unwrap_or( theme::DEFAULT_FONT_SIZE );
@@ -61,6 +66,7 @@ impl<T,W:Widget<T>> SimpleStyleWidget<T,W> {
 		let mut has_hover_style = false;
 		let mut has_active_style = false;
 		let mut has_disabled_style = false;
+		let mut has_invalid_style = false;
 		for s in styles.iter() {
 			if let Some(s) = s {
 				match s.pseudo {
@@ -68,6 +74,7 @@ impl<T,W:Widget<T>> SimpleStyleWidget<T,W> {
 					Pseudo::Hover => has_hover_style = true,
 					Pseudo::Active => has_active_style = true,
 					Pseudo::Disabled => has_disabled_style = true,
+					Pseudo::Invalid => has_invalid_style = true,
     			}
 			}
 		}
@@ -75,14 +82,17 @@ impl<T,W:Widget<T>> SimpleStyleWidget<T,W> {
 		SimpleStyleWidget {
 			normal_style,
 			styles,
+			invalid : None,
 			last_focus : false,
 			last_hover : false,
 			last_active : false,
 			last_disabled : false,
+			last_invalid : false,
 			has_focus_style,
 			has_hover_style,
 			has_active_style,
 			has_disabled_style,
+			has_invalid_style,
 			style_updated : theme::STYLE_UPDATED_LAYOUT,
 			start_style,
 			end_style,
@@ -94,6 +104,12 @@ impl<T,W:Widget<T>> SimpleStyleWidget<T,W> {
         }
     }
 
+	/// `:invalid` style while the state is invalid
+	pub fn with_invalid(mut self, state:InvalidState) -> Self {
+		self.invalid = Some(state);
+		self
+	}
+
 
 }
 
@@ -141,7 +157,8 @@ impl<T:Data, W:Widget<T>> Widget<T> for SimpleStyleWidget<T,W> {
 		let is_hover = ctx.is_hot()  && is_inner;
 		let is_active = ctx.is_active();
 		let is_disabled = ctx.is_disabled();
-		let has_state = has_focus | is_hover | is_active | is_disabled;
+		let is_invalid = self.invalid.as_ref().map( |e| e.is_invalid() ).unwrap_or(false);
+		let has_state = has_focus | is_hover | is_active | is_disabled | is_invalid;
 
 		if self.has_focus_style && self.last_focus != has_focus {
 			self.last_focus = has_focus;
@@ -163,6 +180,11 @@ impl<T:Data, W:Widget<T>> Widget<T> for SimpleStyleWidget<T,W> {
 			on_style_trigger = true;
 		}
 
+		if self.has_invalid_style && self.last_invalid != is_invalid {
+			self.last_invalid = is_invalid;
+			on_style_trigger = true;
+		}
+
 		if on_style_trigger {
 			ctx.request_anim_frame();
 
@@ -179,6 +201,7 @@ impl<T:Data, W:Widget<T>> Widget<T> for SimpleStyleWidget<T,W> {
 							Pseudo::Hover => is_hover,
 							Pseudo::Active => is_active,
 							Pseudo::Disabled => is_disabled,
+							Pseudo::Invalid => is_invalid,
 						};
 						if target {
 							e.style.set_state_from_style(&self.start_style, &self.end_style, &self.base_style);
@@ -196,6 +219,7 @@ impl<T:Data, W:Widget<T>> Widget<T> for SimpleStyleWidget<T,W> {
 							Pseudo::Hover => is_hover,
 							Pseudo::Active => is_active,
 							Pseudo::Disabled => is_disabled,
+							Pseudo::Invalid => is_invalid,
 						}
 					} else {
 						false
@@ -226,6 +250,7 @@ impl<T:Data, W:Widget<T>> Widget<T> for SimpleStyleWidget<T,W> {
 								Pseudo::Hover => is_hover,
 								Pseudo::Active => is_active,
 								Pseudo::Disabled => is_disabled,
+								Pseudo::Invalid => is_invalid,
 							};
 	
 							if !matched {
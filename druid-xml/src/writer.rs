@@ -1,17 +1,21 @@
-
-
 use std::borrow::Cow;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
-use quick_xml::events::BytesStart;
-use quick_xml::name::{self, QName};
-use simplecss::{Declaration, DeclarationTokenizer, StyleSheet, PseudoClass};
+use simplecss::{StyleSheet, PseudoClass};
 use std::fmt::Write;
 
-use crate::simple_style::Pseudo;
-use crate::{named_color, AttributesWrapper};
-use crate::{AttributeGetter, Element, Error};
+use druid::{Color, Insets, RoundedRectRadii};
+
+use crate::qwidget::drawable::{Drawable, FillMethod, Number, QVec2};
+use crate::simple_style::{AnimationState, BorderStyle, Pseudo, Styler, TimingFunction};
+use crate::rich_text::{RichSpans, InlineStyle};
+use crate::{Element, Error};
+use crate::template::Template;
+use crate::bind;
+use crate::lens::LensPath;
+use crate::resource::{ResourceResolver, IncludeResolver};
+use crate::spec::{Context, FlexChild, Input, Kind, LabelText, Target, WidgetSpec};
 
 
 pub(crate) struct PseudoOrderTrapQueryWrap<'a> {
@@ -58,6 +62,9 @@ impl <'a> simplecss::Element for PseudoOrderTrapQueryWrap<'a> {
                 PseudoClass::Hover => { cell.replace( Pseudo::Hover ); true},
                 PseudoClass::Active => { cell.replace( Pseudo::Active ); true},
                 PseudoClass::Focus => { cell.replace( Pseudo::Focus ); true},
+                //not supported by simplecss. see `pseudo_compat`
+                PseudoClass::Lang("disabled") => { cell.replace( Pseudo::Disabled ); true},
+                PseudoClass::Lang("invalid") => { cell.replace( Pseudo::Invalid ); true},
                 _ => false
             }
         } else {
@@ -66,10 +73,40 @@ impl <'a> simplecss::Element for PseudoOrderTrapQueryWrap<'a> {
     }
 }
 
+/// simplecss skips the rule which has the unsupported pseudo class.
+/// `:disabled` and `:invalid` of the selectors are replaced with `:lang(disabled)` and `:lang(invalid)`
+pub(crate) fn pseudo_compat(css:&str) -> Cow<str> {
+    if !css.contains(":disabled") && !css.contains(":invalid") {
+        return Cow::Borrowed(css)
+    }
+    let mut r = String::with_capacity(css.len() + 16);
+    //inside of the declaration block
+    let mut depth = 0;
+    let mut rest = css;
+    while let Some(c) = rest.chars().next() {
+        if depth == 0 && c == ':' {
+            let name = rest[1..].split( |c:char| !(c.is_alphanumeric() || c == '-' || c == '_') ).next().unwrap_or("");
+            if name == "disabled" || name == "invalid" {
+                write!(r, ":lang({name})").unwrap();
+                rest = &rest[1+name.len()..];
+                continue
+            }
+        }
+        match c {
+            '{' => depth += 1,
+            '}' => depth = (depth - 1).max(0),
+            _ => ()
+        }
+        r.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    Cow::Owned(r)
+}
+
 /// stack[parent .. elem]
 pub(crate) struct ElementQueryWrap<'a> {
-	pub parent_stack : &'a [&'a Element<'a>],
-    pub elem : &'a Element<'a>
+	pub parent_stack : &'a [&'a Element],
+    pub elem : &'a Element
 }
 
 impl <'a> simplecss::Element for ElementQueryWrap<'a> {
@@ -88,12 +125,13 @@ impl <'a> simplecss::Element for ElementQueryWrap<'a> {
     fn prev_sibling_element(&self) -> Option<Self> {
         let len = self.parent_stack.len();
         if len > 1 {
-            let parent = &self.parent_stack[len-1];
-            if let Some( (idx,_finded)) = parent.childs.iter().enumerate().find( |(_idx,e)| e.src_pos == self.elem.src_pos ) {
+            let parent:&'a Element = self.parent_stack[len-1];
+            let siblings:Vec<&'a Element> = parent.elements().collect();
+            if let Some(idx) = siblings.iter().position( |e| std::ptr::eq(*e, self.elem) ) {
                 if idx > 0 {
                     return Some( ElementQueryWrap {
                         parent_stack:self.parent_stack,
-                        elem:&parent.childs[idx-1] } )
+                        elem:siblings[idx-1] } )
                 }
             }
 		}
@@ -101,12 +139,12 @@ impl <'a> simplecss::Element for ElementQueryWrap<'a> {
     }
 
     fn has_local_name(&self, name: &str) -> bool {
-        &self.elem.tag().0 == &name.as_bytes()
+        self.elem.tag() == name
     }
 
     fn attribute_matches(&self, local_name: &str, operator: simplecss::AttributeOperator) -> bool {
-		if let Some(v) = self.elem.attributes(None).get(local_name.as_bytes()) {
-			return operator.matches( &String::from_utf8_lossy(&v) )
+		if let Some(v) = self.elem.attributes.get(local_name) {
+			return operator.matches( v )
 		}
 
 		false
@@ -124,20 +162,35 @@ impl <'a> simplecss::Element for ElementQueryWrap<'a> {
 
 pub(crate) trait SourceGenerator {
     fn write_raw(&mut self,code:&str) -> Result<(),Error>;
-    fn write(&mut self, parsed_map:&HashMap<String,Element>, elem:&Element, css:&StyleSheet, wrappers:&HashMap<String,String>) -> Result<(),Error>;
+    fn write(&mut self, parsed_map:&HashMap<String,Template>, template:&Template, css:&StyleSheet, wrappers:&HashMap<String,String>) -> Result<(),Error>;
 }
 
 pub struct DruidGenerator {
-    writer : String
+    writer : String,
+    /// element ids of the `target="#id"`
+    targets : HashSet<String>,
+    /// templates which have more than one instance(`reused_templates`)
+    reused : HashSet<String>,
+    /// `src` of the image
+    resources : Box<dyn ResourceResolver<Output=(String, Vec<u8>)>>,
 }
 
 impl DruidGenerator {
     pub fn new() -> Self {
         Self {
-            writer : String::new()
+            writer : String::new(),
+            targets : HashSet::new(),
+            reused : HashSet::new(),
+            resources : Box::new( IncludeResolver::new(".") ),
         }
     }
 
+    pub fn with_resources(mut self, resources:impl ResourceResolver<Output=(String, Vec<u8>)> + 'static) -> Self {
+        self.resources = Box::new(resources);
+        self
+    }
+
+
     pub fn get_parsed(&self) -> &str {
         self.writer.as_str()
     }
@@ -148,51 +201,10 @@ impl DruidGenerator {
 }
 
 impl DruidGenerator {
-    fn impl_write<'a>(&mut self, parameter:Option<&AttributesWrapper<'a>>, parsed_map:&HashMap<String,Element>, parent_stack:&[&Element], elem:&Element, css:&StyleSheet, wrappers:&HashMap<String,String>) -> Result<(),Error> {
-        let depth = parent_stack.len();
-        let elem_query = ElementQueryWrap { parent_stack, elem };
-
-        //just simplify ordered iteration without vec allocation (#id query first)
-        //Reference : https://www.w3.org/TR/selectors/#specificity
-        let css_iter = 
-        css.rules.iter()
-
-        //id first
-        .filter( |e| { let spec = e.selector.specificity(); spec[0] > 0 && e.selector.matches(&elem_query) } )
-    
-        //class 
-        .chain(
-            css.rules.iter()
-            .filter( |e| { let spec = e.selector.specificity(); spec[0] == 0 && spec[1] > 0 && e.selector.matches(&elem_query) } ) )
-    
-        //global types
-        .chain(
-            css.rules.iter()
-            .filter( |e| { let spec = e.selector.specificity(); spec[0] == 0 && spec[1] == 0 && spec[2] > 0 && e.selector.matches(&elem_query) } ) )
-        .map( |e| &e.declarations );
-
-        let tag_qname = elem.tag();
-        let tag = String::from_utf8_lossy(tag_qname.as_ref());
-        let mut tag_wrap:&str = &tag;
-
-        let attrs = elem.attributes(parameter);
-        let elem_style = attrs.get(b"style").unwrap_or( Cow::Borrowed(b"") );
-        let elem_style_str = &String::from_utf8_lossy(&elem_style) as &str;
-        let specific_style:Vec<Declaration> = DeclarationTokenizer::from( elem_style_str ).collect();
-
-        macro_rules! get_style {
-            ($name:tt) => {
-                specific_style.iter().find( |e| e.name == $name ).map( |e| e.value ).or_else( || {
-                    for global_style in css_iter.clone() {
-                        let find = global_style.iter().find( |e| e.name == $name ).map( |e| e.value );
-                        if find.is_some() {
-                            return find
-                        }
-                    }
-                    None
-                })
-            }
-        }
+    fn impl_write(&mut self, spec:&WidgetSpec, wrappers:&HashMap<String,String>) -> Result<(),Error> {
+        let depth = spec.parents.len();
+        let pos = spec.elem.span.start;
+        let styled = spec.style.is_some();
 
         macro_rules! _src {
             ( $tab_add:expr, $($tts:tt)* ) => {
@@ -207,619 +219,379 @@ impl DruidGenerator {
             } }
         }
 
-        macro_rules! attr_write {
-            ( $name:literal, $value:ident ) => {
-                match $name {
-                    "margin" => CSSAttribute::padding(&mut self.writer, $value).unwrap(),
-                    "padding" => CSSAttribute::padding(&mut self.writer, $value).unwrap(),
-                    "background-color" => CSSAttribute::color(&mut self.writer, $value).unwrap(),
-                    "color" => CSSAttribute::color(&mut self.writer, $value).unwrap(),
-                    "font-size" => CSSAttribute::font_size(&mut self.writer, $value).unwrap(),
-                    "border" => CSSAttribute::border_color_and_width(&mut self.writer, get_style!("border-radius"), $value).unwrap(),
-                    "text-align" => CSSAttribute::text_align(&mut self.writer, $value).unwrap(),
-                    "placeholder" => { write!(self.writer, "{}", $value ).unwrap() },
-                    "object-fit" => CSSAttribute::object_fit(&mut self.writer, $value).unwrap(),
-                    "width" | "height" => CSSAttribute::size(&mut self.writer, $value).unwrap(),
-                    "image-rendering" => CSSAttribute::image_rendering(&mut self.writer, $value).unwrap(),
-                    _ => unimplemented!("unknown css attribute : {}", $name)
-                }
-            }
-        }
-
-        macro_rules! style {
-            ( $start:literal, $name:literal, $end:literal ) => {
-                if $name == "width-height" {
-                    if let (Some(width), Some(height)) = (get_style!("width") , get_style!("height")) {
-                        CSSAttribute::size(&mut self.writer, width).unwrap();
-                        _src!(0,",");
-                        CSSAttribute::size(&mut self.writer, height).unwrap();
-                    }
-                } else if let Some(value) = get_style!($name) {
-                    src!($start);
-                    attr_write!($name, value);
-                    _src!( 0, $end );
-                }
-            }
+        //`let {name} = { .. };`
+        macro_rules! child {
+            ( $name:literal, $child:expr ) => { {
+                src!("let {} = {{\n", $name);
+                self.impl_write($child, wrappers)?;
+                src!("}};\n");
+            } }
         }
 
-        macro_rules! style_opt {
-            ( $name:literal ) => {
-                style_opt!("", $name, "");
-            };
-            ( $start:literal, $name:literal, $end:literal ) => {
-                if let Some(value) = get_style!($name) {
-                    _src!( 0, "Some(");
-                    _src!( 0, $start);
-                    attr_write!($name, value);
-                    _src!( 0, $end );
-                    _src!( 0, ")" );
+        let tag_wrap = match &spec.kind {
+            Kind::Flex { column, must_fill, cross, main, children } => {
+                if *column {
+                    src!("let mut flex = druid::widget::Flex::column();\n");
                 } else {
-                    _src!( 0, "None" );
+                    src!("let mut flex = druid::widget::Flex::row();\n");
                 }
-            }
-        }
-
-        macro_rules! attr {
-            ($start:literal, $attr:literal, $end:literal) => {
-                attr!(attrs, $start, $attr, $end);
-            };
-            ($target:ident, $start:literal, $attr:literal, $end:literal) => {
-                if let Some(attr) = $target.get($attr) {
-                    src!( $start );
-                    _src!( 0, "{}", String::from_utf8_lossy( &attr ) );
-                    _src!( 0, $end );
+                if let Some(v) = must_fill {
+                    src!("flex = flex.must_fill_main_axis({v});\n");
                 }
-            }
-        }
-
-        macro_rules! new_parent_stack {
-            () => {
-                new_parent_stack!( elem )
-            };
-            ( $new:ident ) => { {
-                let mut new_stack = parent_stack.to_owned();
-                new_stack.push( $new );
-                new_stack
-            } }
-        }
-
-        let mut text = elem.text.as_ref().map( |e| String::from_utf8_lossy(&e) ).unwrap_or( std::borrow::Cow::Borrowed("") );
-
-        if text.starts_with("${") && text.ends_with('}') {
-            let key = text[2..text.len()-1].trim();
-            if let Some(parameter) = parameter {
-                if let Some(param_value) = parameter.get( key.as_bytes() ) {
-                    text = Cow::Owned(String::from_utf8_lossy(&param_value).as_ref().to_owned());
-                    //TODO : how to avoid?
-                    // text = String::from_utf8_lossy(param_value);
+                if let Some(v) = cross {
+                    src!("flex.set_cross_axis_alignment(druid::widget::CrossAxisAlignment::{v:?});\n");
                 }
-            } 
-        }
-        
-        let input_type_holder = &attrs.get(b"type").unwrap_or(Cow::Borrowed(b""));
-        let input_type = String::from_utf8_lossy( input_type_holder );
-
-        let normal_transition = get_style!("transition");
-        let has_norm_style = 
-                get_style!("padding").is_some() 
-                | get_style!("margin").is_some() 
-                | get_style!("font-size").is_some() 
-                | get_style!("width").is_some() 
-                | get_style!("height").is_some() 
-                | get_style!("color").is_some() 
-                | get_style!("background-color").is_some() 
-                | get_style!("border").is_some()
-                | normal_transition.is_some();
-        let mut has_pseudo_style = false;
-        for rule in css.rules.iter() {
-            let pseudo_trap_hack = PseudoOrderTrapQueryWrap::new( ElementQueryWrap { parent_stack, elem } );           
-            if rule.selector.matches(&pseudo_trap_hack) {
-                let pseudo = pseudo_trap_hack.get_pseudo();
-
-                /// check disabled. simplecss 'disabled' pseudo not support
-                let selector = rule.selector.to_string();
-                let mut check_disabled = simplecss::SelectorTokenizer::from( selector.as_str() );
-                let is_disabled = check_disabled.find( |e| {
-                    if let Ok(e) = e {
-                        match e {
-                            simplecss::SelectorToken::PseudoClass(p) => *p == "disabled",
-                            _ => false
+                if let Some(v) = main {
+                    src!("flex.set_main_axis_alignment(druid::widget::MainAxisAlignment::{v:?});\n");
+                }
+                for child in children.iter() {
+                    match child {
+                        FlexChild::Spacer(Some(flex)) => src!("flex.add_flex_spacer({});\n", flex.to_source()),
+                        FlexChild::Spacer(None) => src!("flex.add_default_spacer( );\n"),
+                        FlexChild::Child(child, flex) => {
+                            child!("child", child);
+                            match flex {
+                                Some(flex) => src!("flex.add_flex_child(child, {});\n", flex.to_source()),
+                                None => src!("flex.add_child( child );\n")
+                            }
                         }
-                    } else {
-                        false
-                    }
-                }).is_some();
-                
-                let pseudo = if is_disabled {
-                    Some(Pseudo::Disabled)
-                } else {
-                    pseudo
-                };
-
-                if pseudo.is_some() {
-                    has_pseudo_style = rule.declarations.iter().find( |e| e.name == "padding" ).is_some()
-                    | rule.declarations.iter().find( |e| e.name == "margin" ).is_some()
-                    | rule.declarations.iter().find( |e| e.name == "font-size" ).is_some()
-                    | rule.declarations.iter().find( |e| e.name == "width" ).is_some()
-                    | rule.declarations.iter().find( |e| e.name == "height" ).is_some()
-                    | rule.declarations.iter().find( |e| e.name == "color" ).is_some()
-                    | rule.declarations.iter().find( |e| e.name == "background-color" ).is_some()
-                    | rule.declarations.iter().find( |e| e.name == "border" ).is_some();
-                    if has_pseudo_style {
-                        break
                     }
                 }
-            }
-        }
-
-        if tag == "flex" {
-            if let Some( Cow::Borrowed(b"column") ) = attrs.get(b"direction") {
-                src!("let mut flex = druid::widget::Flex::column();\n");
-            } else {
-                src!("let mut flex = druid::widget::Flex::row();\n");
-            }
-            if elem.childs.len() < 1 {
-                return Err(Error::InvalidFlexChildNum((elem.src_pos)))
-            }
-
-            attr!("flex = flex.must_fill_main_axis(", b"must_fill_main_axis", ");\n");
-
-            if let Some(v) = attrs.get(b"cross_axis_alignment") {
-                let v = match v.as_ref() {
-                    b"start" => "druid::widget::CrossAxisAlignment::Start",
-                    b"center" => "druid::widget::CrossAxisAlignment::Center",
-                    b"end" => "druid::widget::CrossAxisAlignment::End",
-                    b"baseline" => "druid::widget::CrossAxisAlignment::Baseline",
-                    _ => return Err(Error::InvalidAttributeValue((elem.src_pos, "cross_axis_alignment")))
-                };
-                src!("flex.set_cross_axis_alignment({});\n", v);
-            }
-
-            if let Some(v) = attrs.get(b"axis_alignment") {
-                let v = match v.as_ref() {
-                    b"start" => "druid::widget::MainAxisAlignment::Start",
-                    b"center" => "druid::widget::MainAxisAlignment::Center",
-                    b"end" => "druid::widget::MainAxisAlignment::End",
-                    b"spacebetween" => "druid::widget::MainAxisAlignment::SpaceBetween",
-                    b"spaceevenly" => "druid::widget::MainAxisAlignment::SpaceEvenly",
-                    b"spacearound" => "druid::widget::MainAxisAlignment::SpaceAround",
-                    _ => return Err(Error::InvalidAttributeValue((elem.src_pos, "axis_alignment")))
+                "flex"
+            },
+
+            Kind::Label { text, button, line_break, align } => {
+                let rich = matches!(text, LabelText::Rich(_));
+                let constructor = match text {
+                    LabelText::Static(text) => format!("new({text:?})"),
+                    LabelText::Bind(source) => {
+                        let parts = bind::parse(source).unwrap_or_default();
+                        format!("dynamic({})", bind::closure(&parts, spec.data_type, pos)?)
+                    },
+                    LabelText::Rich(rich) => {
+                        let mut rich_src = String::new();
+                        write_rich_text(&mut rich_src, rich);
+                        src!("let rich = {rich_src};\n");
+                        "rich(rich)".to_owned()
+                    }
                 };
-                src!("flex.set_main_axis_alignment({});\n", v);
-            }
-            
-
-            let new_stack = new_parent_stack!();
-            for child in elem.childs.iter() {
-                if child.tag().as_ref() == b"spacer" {
-                    if let Some(flex) = child.attributes(None).get(b"flex") {
-                        src!("flex.add_flex_spacer({});\n", String::from_utf8_lossy(&flex));
+                if styled || (rich && *button) {
+                    if !styled && line_break.is_none() && align.is_none() {
+                        src!("let button = druid_xml::widget::DXButton::from_label( druid_xml::widget::DXLabel::rich(rich) );\n");
                     } else {
-                        src!("flex.add_default_spacer( );\n");
+                        src!("let mut label = druid_xml::widget::DXLabel::{constructor};\n");
+                        if let Some(align) = align {
+                            src!("label.set_text_alignment(druid::TextAlignment::{align:?});\n");
+                        }
+                        if let Some(mode) = line_break {
+                            src!("label.set_line_break_mode(druid::widget::LineBreaking::{mode:?});\n");
+                        }
+                        if *button {
+                            src!("let button = druid_xml::widget::DXButton::from_label(label);\n");
+                        }
                     }
                 } else {
-                    src!("let child = {{\n");
-                    self.impl_write(parameter, parsed_map, &new_stack, child, css, wrappers)?;
-                    src!("}};\n");
-                    if let Some(flex) = child.attributes(None).get(b"flex") {
-                        src!("flex.add_flex_child(child, {}f64);\n", String::from_utf8_lossy(&flex));
+                    if rich {
+                        src!("let mut label = druid::widget::RawLabel::<druid::text::RichText>::new();\n");
                     } else {
-                        src!("flex.add_child( child );\n");
+                        src!("let mut label = druid::widget::Label::{constructor};\n");
+                    }
+                    if let Some(align) = align {
+                        src!("label.set_text_alignment(druid::TextAlignment::{align:?});\n");
+                    }
+                    if let Some(mode) = line_break {
+                        src!("label.set_line_break_mode(druid::widget::LineBreaking::{mode:?});\n");
+                    }
+                    if rich {
+                        src!("let label = druid::WidgetExt::lens(label, druid::lens::Constant(rich));\n");
+                    }
+                    if *button {
+                        src!("let button = druid::widget::Button::from_label(label);\n");
                     }
                 }
-            }
-        }
+                if *button { "button" } else { "label" }
+            },
 
-        //WARN : checkbox is none-standard
-        else if tag == "label" || tag == "button" {
-            let label_text = if text == "" {
-                tag.as_ref()
-            } else {
-                &text
-            };
-            
-            if has_norm_style || has_pseudo_style {
-                src!("let mut label = druid_xml::widget::DXLabel::new(\"{label_text}\");\n" );
-            } else {
-                // style!("label.set_text_color(", "color", ");\n");
-                // style!("label.set_text_size(", "font-size", ");\n");
-                src!("let mut label = druid::widget::Label::new(\"{label_text}\");\n" );
-                style!("label.set_text_alignment(\"", "text-align", "\");\n");
-            }
+            Kind::Checkbox { text } => {
+                src!("let checkbox = druid::widget::Checkbox::new({text:?});\n");
+                "checkbox"
+            },
 
-            if tag == "button" {
-                if has_norm_style || has_pseudo_style {
-                    src!("let button = druid_xml::widget::DXButton::from_label(label);\n");
+            Kind::TextBox { multiline, placeholder, input, color, font_size, align, .. } => {
+                if *multiline {
+                    src!("let mut textbox = druid::widget::TextBox::multiline();\n");
                 } else {
-                    src!("let button = druid::widget::Button::from_label(label);\n");
+                    src!("let mut textbox = druid::widget::TextBox::new();\n");
                 }
-            }
-        }
+                if let Some(color) = color {
+                    src!("textbox.set_text_color({});\n", color.to_source());
+                }
+                if let Some(size) = font_size {
+                    src!("textbox.set_text_size({});\n", size.to_source());
+                }
+                if let Some(align) = align {
+                    src!("textbox.set_text_alignment(druid::TextAlignment::{align:?});\n");
+                }
+                if let Some(placeholder) = placeholder {
+                    src!("textbox.set_placeholder({placeholder:?});\n");
+                }
+                match input {
+                    Input::Text => (),
+                    Input::Password => src!("let textbox = druid::WidgetExt::lens(textbox, druid_xml::widget::input::password());\n"),
+                    Input::Number { min, max, step, format } => {
+                        let mut formatter = String::new();
+                        for (name, v) in [("min", min), ("max", max), ("step", step)] {
+                            if let Some(v) = v {
+                                write!(formatter, ".{name}({v:?}f64)").unwrap();
+                            }
+                        }
+                        if let Some(format) = format {
+                            write!(formatter, ".format({format:?})").unwrap();
+                        }
+                        let formatter = if formatter.is_empty() {
+                            "druid::text::ParseFormatter::<f64>::new()".to_owned()
+                        } else {
+                            format!("druid_xml::widget::NumberFormatter::new(){formatter}")
+                        };
+                        src!("let invalid = druid_xml::widget::InvalidState::default();\n");
+                        src!("let textbox = druid::widget::ValueTextBox::new(textbox, {formatter}).validate_while_editing(true).delegate(invalid.clone());\n");
+                    }
+                }
+                "textbox"
+            },
+
+            Kind::Image { src:file_src, fit, rendering } => {
+                let (include, bytes) = self.resources.resolve(file_src).ok_or( Error::ResourceNotFound( (pos, file_src.clone()) ) )?;
+                //the image is checked now, so the same bytes are decoded at runtime without the error
+                druid::ImageBuf::from_data(&bytes).map_err( |_| Error::InvalidImage( (pos, file_src.clone()) ) )?;
+                src!( "let image_buf = druid::ImageBuf::from_data( {include} ).unwrap_or_else( |_| druid::ImageBuf::empty() );\n" );
+                src!( "let mut image = druid::widget::Image::new(image_buf);\n");
+                if let Some(fit) = fit {
+                    src!( "image.set_fill_mode(druid::widget::FillStrat::{fit:?});\n");
+                }
+                if let Some(mode) = rendering {
+                    src!( "image.set_interpolation_mode(druid::piet::InterpolationMode::{mode:?});\n");
+                }
+                "image"
+            },
+
+            //item template is the root of the item data
+            Kind::List { template, horizontal, spacing, .. } => {
+                src!("let mut list = druid::widget::List::new(|| {{\n");
+                self.impl_write(template, wrappers)?;
+                src!("}});\n");
+                if *horizontal {
+                    src!("list = list.horizontal();\n");
+                }
+                if let Some(spacing) = spacing {
+                    src!("list = list.with_spacing({spacing}f64);\n");
+                }
+                "list"
+            },
+
+            Kind::Scroll { child } => {
+                child!("child", child);
+                src!("let scroll = druid::widget::Scroll::new(child);\n");
+                "scroll"
+            },
+
+            Kind::Slider { min, max } => {
+                src!("let slider = druid::widget::Slider::new().with_range({}, {});\n", min.to_source(), max.to_source());
+                "slider"
+            },
+
+            Kind::Spinner { color } => {
+                src!("let mut spinner = druid::widget::Spinner::new();\n");
+                if let Some(color) = color {
+                    src!("spinner.set_color({});\n", color.to_source());
+                }
+                "spinner"
+            },
+
+            Kind::Split { column, one, two, split_point, min_size, bar_size, min_bar_area, draggable, solid_bar } => {
+                child!("one", one);
+                child!("two", two);
+                if *column {
+                    src!("let mut split = druid::widget::Split::columns(one, two);\n");
+                } else {
+                    src!("let mut split = druid::widget::Split::rows(one, two);\n");
+                }
+                if let Some(v) = split_point {
+                    src!("split = split.split_point({});\n", v.to_source());
+                }
+                if let Some((first, second)) = min_size {
+                    src!("split = split.min_size({}, {});\n", first.to_source(), second.to_source());
+                }
+                if let Some(v) = bar_size {
+                    src!("split = split.bar_size({});\n", v.to_source());
+                }
+                if let Some(v) = min_bar_area {
+                    src!("split = split.min_bar_area({});\n", v.to_source());
+                }
+                if let Some(v) = draggable {
+                    src!("split = split.draggable({v});\n");
+                }
+                if let Some(v) = solid_bar {
+                    src!("split = split.solid_bar({v});\n");
+                }
+                "split"
+            },
+
+            //`preview_else` is only for the dynamic preview
+            Kind::If { lens, then, otherwise, .. } => {
+                let lens = LensPath::parse(lens).ok_or( Error::InvalidAttributeValue((pos, "lens")) )?.to_rust(spec.data_type, pos)?;
+                child!("then", then);
+                match otherwise {
+                    Some(otherwise) => child!("otherwise", otherwise),
+                    None => src!("let otherwise = druid::widget::SizedBox::empty();\n")
+                }
+                src!("let either = druid::widget::Either::new(|data, _env| druid::LensExt::get(&{lens}, data), then, otherwise);\n");
+                "either"
+            },
+
+            Kind::Stepper { min, max, step, wraparound } => {
+                src!("let stepper = druid::widget::Stepper::new().with_range({}, {}).with_step({}).with_wraparound({wraparound});\n", min.to_source(), max.to_source(), step.to_source());
+                "stepper"
+            },
+
+            Kind::Switch => {
+                src!("let switch = druid::widget::Switch::new();\n");
+                "switch"
+            },
+
+            //shapes are parsed in the spec, so the constructors are emitted without the runtime parsing
+            Kind::Canvas { shapes } => {
+                src!("let painter = {{\n");
+                src!("\tlet drawables = druid_xml::qwidget::drawable::DrawableStack::new(vec![\n");
+                for shape in shapes.iter() {
+                    src!("\t\t{},\n", shape.to_source());
+                }
+                src!("\t]);\n");
+                src!("\tdruid::widget::Painter::new( move |ctx, _, _| drawables.draw(0., ctx) )\n");
+                src!("}};\n");
+                "painter"
+            },
 
-        else if tag == "checkbox" || (tag == "input" && input_type == "checkbox") {
-            //TODO : checkbox has not label like button. color and text_size
-            tag_wrap = "checkbox";
-            let label_text = if text == "" {
-                "checkbox"
-            } else {
-                &text
+            Kind::Native { tag, .. } => {
+                src!("let custom_widget = {tag}();\n");
+                "custom_widget"
+            },
+
+            Kind::Template { root } => {
+                child!("custom_widget", root);
+                "custom_widget"
+            },
+        };
+
+        //event handler. the controller is inside of the lens so `onchange` compares the data of the widget
+        let mut controller = String::new();
+        let mut on_click = None;
+        for (name, handler) in spec.handlers.iter() {
+            let method = match *name {
+                "onclick" => {
+                    on_click = Some(handler);
+                    continue
+                },
+                "onchange" => "on_change",
+                "onfocus" => "on_focus",
+                "onblur" => "on_blur",
+                _ => "on_dblclick"
             };
-            src!("let checkbox = druid::widget::Checkbox::new(\"{label_text}\");\n");
+            write!(controller, ".{method}({handler})").unwrap();
         }
-
-        //TODO : password type?
-        else if tag == "textbox" || (tag == "input" && input_type == "text") {
-            tag_wrap = "textbox";
-            src!("let mut textbox = druid::widget::TextBox::new();\n" );
-            style!("textbox.set_text_color(", "color", ");\n");
-            style!("textbox.set_text_size(", "font-size", ");\n");
-            style!("textbox.set_text_alignment(\"", "text-align", "\");\n");
-            if let Some(placeholder) = attrs.get_as::<String>(b"placeholder") {
-                src!("textbox.set_placeholder(\"{placeholder}\");\n");
-            }
-        }
-
-        //WARN : "image" is none-standard
-        else if tag == "image" || tag == "img" {
-            tag_wrap = "image";
-            let file_src_holder = &attrs.get_result("src")?;
-            let file_src = String::from_utf8_lossy( file_src_holder );
-            //TODO : more speedup as raw binary data
-            src!( "let image_buf = druid::ImageBuf::from_bytes( inclue_bytes!(\"{}\") ).unwrap();\n", &file_src);
-            src!( "let mut image = druid::widget::Image::new(image_buf);\n");
-            style!( "image.set_fill_mode(\"", "object-fit" ,");\n");
-            style!( "image.set_interpolation_mode(\"", "image-rendering" ,");\n");
-        }
-
-        //WARN : list is none-standard
-        else if tag == "list" {
-            style!( "let mut list = druid::widget::List::new(", "fn" ,");\n");
-            if let Some( Cow::Borrowed(b"horizontal") ) = attrs.get(b"direction") {
-                src!( "list = list.horizontal();\n");
-            }
-            style!( "list.set_spacing(", "spacing", ");\n");
-        }
-
-        else if tag == "scroll" {
-            if elem.childs.len() != 1 {
-                return Err(Error::InvalidScrollChildNum(elem.src_pos))
-            }
-            let new_stack = new_parent_stack!();
-            src!("let child = {{\n");
-            self.impl_write(parameter, parsed_map, &new_stack, &elem.childs[0], css, wrappers)?;
-            src!("}};\n");
-            src!("let mut scroll = druid::widget::Scroll::new(child);\n");
+        if !controller.is_empty() {
+            src!("let {tag_wrap} = druid::WidgetExt::controller({tag_wrap}, druid_xml::widget::EventHandler::new(){controller});\n");
         }
 
-        else if tag == "slider" {
-            let min = attrs.get_as_result::<f64>("min").unwrap_or(0f64);
-            let max = attrs.get_as_result::<f64>("max").unwrap_or(1f64);
-            src!("let mut slider = Slider::new;\n");
-            src!("slider = slider.with_range({min},{max});\n");
+        //wrap Lens
+        if let Some(lens) = spec.lens.as_ref() {
+            //`App::user.name`, `App::items[2]`, `App::volume | percent`. Otherwise the rust expression as it is
+            let lens = match LensPath::parse(lens) {
+                Some(path) if path.is_compound() => path.to_rust(spec.data_type, pos)?,
+                _ => lens.clone()
+            };
+            src!("let {tag_wrap} = druid::WidgetExt::lens({tag_wrap}, {lens});\n");
         }
 
-        else if tag == "spinner" {
-            src!("let mut spinner = druid::widget::Spinner::new();\n");
-            style!("spinner.set_color(", "color", ");\n");
+        //`onclick` takes the data of the parent
+        if let Some(handler) = on_click {
+            src!("let {tag_wrap} = druid::WidgetExt::on_click({tag_wrap}, {handler});\n");
         }
 
-        //TODO : child must be two item
-        else if tag == "split" {
-            if elem.childs.len() != 2 {
-                return Err(Error::InvalidSplitChildNum(elem.src_pos))
-            }
-            let new_stack = new_parent_stack!();
-            src!("let one = {{\n");
-            self.impl_write(parameter, parsed_map, &new_stack, &elem.childs[0], css, wrappers)?;
-            src!("}};\n");
-
-            src!("let two = {{\n");
-            self.impl_write(parameter, parsed_map, &new_stack, &elem.childs[1], css, wrappers)?;
-            src!("}};");
-
-            if let Some( Cow::Borrowed(b"column") ) = attrs.get(b"direction") {
-                src!("let mut split = druid::widget::Split::columns(one, two);\n");
-            } else {
-                src!("let mut split = druid::widget::Split::rows(one, two);\n");
+        if let Some(command) = spec.command.as_ref() {
+            let mut submit = match command.payload.as_ref() {
+                Some(payload) => format!("{}.with({payload})", command.selector),
+                None => format!("druid::Command::from({})", command.selector)
+            };
+            match command.target.as_ref() {
+                Some(Target::Window) => submit.push_str(".to(druid::Target::Window(ctx.window_id()))"),
+                Some(Target::Global) => submit.push_str(".to(druid::Target::Global)"),
+                Some(Target::Widget(id)) => write!(submit, ".to(druid::Target::Widget(druid_xml::widget::widget_id({id:?})))").unwrap(),
+                None => ()
             }
-            
-            attr!("split = split.split_point(", b"split_point", "f64);\n");
-            attr!("split = split.min_size(", b"min_size", "f64);\n");
-            attr!("split = split.bar_size(", b"bar_size", "f64);\n");
-            attr!("split = split.min_bar_area(", b"min_bar_area", "f64);\n");
-            attr!("split = split.draggable(", b"draggable", ");\n");
-            attr!("split = split.solid_bar(", b"solid_bar", ");\n");
-        }
-
-        else if tag == "stepper" {
-            let min = attrs.get_as_result::<f64>("min").unwrap_or(std::f64::MIN);
-            let max = attrs.get_as_result::<f64>("max").unwrap_or(std::f64::MAX);
-            let step = attrs.get_as_result::<f64>("step").unwrap_or(std::f64::MAX);
-            let wrap = attrs.get_as_result::<bool>("wraparound").unwrap_or(false);
-            src!("let mut stepper = Stepper::new();\n");
-            src!("let mut stepper = stepper.with_range({min},{max});\n");
-            src!("stepper = stepper.with_step({step});\n");
-            src!("stepper = stepper.with_wraparound({wrap});\n");
+            src!("let {tag_wrap} = druid::WidgetExt::on_click({tag_wrap}, |ctx, _data, _env| ctx.submit_command({submit}));\n");
         }
 
-        else if tag == "switch" {
-            //TODO : style
-            src!("let mut switch = druid::widget::Switch::new();\n");
+        //wrap `SizedBox` with optimize
+        match (spec.width, spec.height) {
+            (Some(width), Some(height)) => src!("let {tag_wrap} = druid::WidgetExt::fix_size({tag_wrap}, {}, {});\n", width.to_source(), height.to_source()),
+            (Some(width), None) => src!("let {tag_wrap} = druid::WidgetExt::fix_width({tag_wrap}, {});\n", width.to_source()),
+            (None, Some(height)) => src!("let {tag_wrap} = druid::WidgetExt::fix_height({tag_wrap}, {});\n", height.to_source()),
+            (None, None) => ()
         }
 
-        //TODO
-        else if tag == "painter" || tag == "canvas" {
-            tag_wrap = "painter";
+        //wrap `Padding` for Label,Button
+        if styled {
+            src!("let {tag_wrap} = druid::WidgetExt::padding( {tag_wrap}, druid_xml::widget::theme::PADDING );\n");
         }
 
-        //The Container has been replaced by SimpleStyleWidget.
-        // //WARN : container is none-standard
-        // else if tag == "container" {
-        //     if elem.childs.len() != 1 {
-        //         return Err(Error::InvalidContainerChildNum(elem.src_pos))
-        //     }
-        //     let new_stack = new_parent_stack!();
-        //     src!("let child = {{\n");
-        //     self.impl_write(parameter, parsed_map, &new_stack, &elem.childs[0], css, wrappers)?;
-        //     src!("}};\n");
-        //     src!( "let mut container = druid::widget::Container::new(child);\n");
-        //     style!("container.set_background(", "background-color", ");\n");
-        //     style!("container.set_border(", "border", ");\n");
-        // }
-        else {
-            tag_wrap = "custom_widget";
-            if let Some(elem) = parsed_map.get( tag.as_ref() ) {
-                let new_stack = new_parent_stack!();
-                src!("let custom_widget = {{\n");
-                self.impl_write(Some(&attrs), parsed_map, &new_stack, elem, css, wrappers)?;
-                src!("}};\n");
-            } else {
-                src!("let custom_widget = {tag}();\n");
-                //return Err(Error::UnknownTag( (elem.src_pos, tag.as_ref().to_owned() )));
-            }
-        }
-
-        //build styler
-        //TODO : 
-        // - need optimization for duplicated style(use Rc)
-        // - inherit style
-        
-        if has_norm_style || has_pseudo_style {
-            fn parse_time(v:&str) -> u64 {
-                let v = v.to_lowercase();
-                if v.ends_with("s") {
-                    u64::from_str_radix( &v[..v.len()-1], 10 ).unwrap_or(0) * 1000_000_000
-                } else if v.ends_with("ms") {
-                    u64::from_str_radix( &v[..v.len()-2], 10 ).unwrap_or(0) * 1000_000
-                } else {
-                    u64::from_str_radix( v.as_str(), 10 ).unwrap() * 1000_000
-                }
-            }
-
-            fn transition_option(define:&str, item:&str) -> String {
-                for n in define.split(",") {
-                    let mut duration = 0;
-                    let mut delay = 0;
-                    let mut timing_function = Cow::Borrowed("druid_xml::simple_style::TimingFunction::Linear");
-                    //[sec] [name] => duration,item
-                    //[name] [sec] => duration,item
-                    //[sec] [name] [sec] => duration,item,delay
-                    let mut wsplited = n.split_whitespace();
-                    let expect_duration = wsplited.next().unwrap();
-                    let expect_property = if expect_duration.chars().next().unwrap().is_numeric() {
-                        //duration
-                        duration = parse_time(expect_duration);
-                        if duration == 0 {
-                            //ignore
-                            continue;
-                        }
-                        if let Some(s) = wsplited.next() {
-                            s
-                        } else {
-                            continue
-                        }
-                    } else {
-                        //pass
-                        expect_duration
-                    };
-
-                    if expect_property != item {
-                        //not target
-                        continue;
-                    }
-
-                    let expect_delay = if let Some(s) = wsplited.next() {
-                        s
-                    } else {
-                        "0"
-                    };
-
-                    let expect_tf = if expect_delay.chars().next().unwrap().is_numeric() {
-                        //delay
-                        delay = parse_time(expect_delay);
-                        if let Some(s) = wsplited.next() {
-                            s
-                        } else {
-                            "linear"
-                        }
-                    } else {
-                        //timning-funciton
-                        expect_delay
-                    };
-
-                    timing_function = match expect_tf {
-                        "ease" => Cow::Borrowed("druid_xml::simple_style::TimingFunction::Ease"),
-                        "ease-in" => Cow::Borrowed("druid_xml::simple_style::TimingFunction::EaseIn"),
-                        "ease-out" => Cow::Borrowed("druid_xml::simple_style::TimingFunction::EaseOut"),
-                        "ease-in-out" => Cow::Borrowed("druid_xml::simple_style::TimingFunction::EaseInOut"),
-                        "linear" => Cow::Borrowed("druid_xml::simple_style::TimingFunction::Linear"),
-                        "step-start" => Cow::Borrowed("druid_xml::simple_style::TimingFunction::Ease"),
-                        "step-end" => Cow::Borrowed("druid_xml::simple_style::TimingFunction::Ease"),
-                        _ => if expect_tf.starts_with("cubic-bezier(") {
-                            let mut params = expect_tf["cubic-bezier(".len() .. expect_tf.rfind(')').unwrap_or(expect_tf.len()-1)].split(',');
-                            let mut cb = "druid_xml::simple_style::TimingFunction::CubicBezier{".to_string();
-                            cb.push_str("p1:"); params.next().unwrap_or("0"); cb.push_str("f64, ");
-                            cb.push_str("p2:"); params.next().unwrap_or("0"); cb.push_str("f64, ");
-                            cb.push_str("p3:"); params.next().unwrap_or("0"); cb.push_str("f64, ");
-                            cb.push_str("p4:"); params.next().unwrap_or("0"); cb.push_str("f64, ");
-                            cb.push_str("}");
-                            Cow::Owned(cb)
-                        } else if expect_tf.starts_with("steps(") {
-                            let mut params = expect_tf["steps(".len() .. expect_tf.rfind(')').unwrap_or(expect_tf.len()-1)].split(',');
-                            let mut cb = "druid_xml::simple_style::TimingFunction::Steps{".to_string();
-                            cb.push_str("n:"); params.next().unwrap_or("0"); cb.push_str("f64, ");
-                            let jumpterm = match params.next().unwrap_or("jump-start") {
-                                "jump-start" => "druid_xml::simple_style::JumpTerm::JumpStart",
-                                "jump-end" => "druid_xml::simple_style::JumpTerm::JumpEnd",
-                                "jump-none" => "druid_xml::simple_style::JumpTerm::JumpNone",
-                                "jump-both" => "druid_xml::simple_style::JumpTerm::JumpBoth",
-                                "start" => "druid_xml::simple_style::JumpTerm::Start",
-                                "end" => "druid_xml::simple_style::JumpTerm::End",
-                                _ => "druid_xml::simple_style::JumpTerm::JumpStart"
-                            };
-                            cb.push_str("jumpterm:"); params.next().unwrap_or("0"); cb.push_str("f64, ");
-                            cb.push_str("}");
-                            Cow::Owned(cb)
-                        } else {
-                            Cow::Borrowed("druid_xml::simple_style::TimingFunction::Linear")
-                        }
-                    };
-                    return format!("Some(druid_xml::simple_style::AnimationState::from( druid_xml::simple_style::Animation{{ delay: {delay}, direction: druid_xml::simple_style::Direction::Normal, duration: {duration}, iteration: 1., name: 1., timing_function: {timing_function}, fill_mode: 0. }} ))");
+        //custom query wrapper
+        let elem_query = ElementQueryWrap { parent_stack:&spec.parents, elem:spec.elem };
+        for (query, wrapper) in wrappers.iter() {
+            if let Some(selector) = simplecss::Selector::parse(query) {
+                if selector.matches( &elem_query ) {
+                    src!("let widget = {tag_wrap};\n");
+                    src!("let {tag_wrap} = {wrapper};\n");
                 }
-                return "None".to_string()
             }
-
-            src!("let mut normal_style = \n");
-            src!("druid_xml::simple_style::Styler {{\n");
-            src!("     padding : ("); style_opt!("druid::Insets::from(", "padding", ")"); _src!(0, ", {}),\n", normal_transition.map(|e| transition_option(e,"padding")).unwrap_or("None".to_string()) );
-            src!("     margin : ("); style_opt!("druid::Insets::from(", "margin", ")"); _src!(0, ", {}),\n", normal_transition.map(|e| transition_option(e,"margin")).unwrap_or("None".to_string()) );
-            src!("     font_size : ("); style_opt!("( ", "font-size", ")"); _src!(0, ", {}),\n", normal_transition.map(|e| transition_option(e,"font-size")).unwrap_or("None".to_string()) );
-            src!("     width : ("); style_opt!("width"); _src!(0, ", {}),\n", normal_transition.map(|e| transition_option(e,"width")).unwrap_or("None".to_string()) );
-            src!("     height : ("); style_opt!("height"); _src!(0, ", {}),\n", normal_transition.map(|e| transition_option(e,"height")).unwrap_or("None".to_string()) );
-            src!("     text_color : ("); style_opt!("color"); _src!(0, ", {}),\n", normal_transition.map(|e| transition_option(e,"color")).unwrap_or("None".to_string()) );
-            src!("     background_color : ("); style_opt!("background-color");  _src!(0, ", {}),\n", normal_transition.map(|e| transition_option(e,"background-color")).unwrap_or("None".to_string()) );
-            src!("     border : ("); style_opt!("druid_xml::simple_style::BorderStyle::new(", "border", ")"); _src!(0, ", {}),\n", normal_transition.map(|e| transition_option(e,"border")).unwrap_or("None".to_string()) );
-            src!("}};\n");
-
-            src!("let pseudo_styles:[Option<druid_xml::simple_style::PseudoStyle>;4] = [\n");
-            let mut pseudo_count = 0;
-            for rule in css.rules.iter() {
-                let pseudo_trap_hack = PseudoOrderTrapQueryWrap::new( ElementQueryWrap { parent_stack, elem } );
-                
-                if rule.selector.matches(&pseudo_trap_hack) {
-                    let pseudo = pseudo_trap_hack.get_pseudo();
-
-                    /// check disabled. simplecss 'disabled' pseudo not support
-                    let selector = rule.selector.to_string();
-                    let mut check_disabled = simplecss::SelectorTokenizer::from( selector.as_str() );
-                    let is_disabled = check_disabled.find( |e| {
-                        if let Ok(e) = e {
-                            match e {
-                                simplecss::SelectorToken::PseudoClass(p) => *p == "disabled",
-                                _ => false
-                            }
-                        } else {
-                            false
-                        }
-                    }).is_some();
-                    
-                    let pseudo = if is_disabled {
-                        Some(Pseudo::Disabled)
-                    } else {
-                        pseudo
-                    };
-
-                    macro_rules! pseudo_style_opt {
-                        ( $name:literal ) => {
-                            pseudo_style_opt!("", $name, "");
-                        };
-                        ( $start:literal, $name:literal, $end:literal ) => {
-                            if let Some(value) = rule.declarations.iter().find( |e| e.name == $name ).map( |e| e.value ) {
-                                _src!( 0, "Some(");
-                                _src!( 0, $start);
-                                attr_write!($name, value);
-                                _src!( 0, $end );
-                                _src!( 0, ")" );
-                            } else {
-                                _src!( 0, "None" );
-                            }
-                        }
-                    }
-
-                    if let Some(pseudo) = pseudo {
-                        pseudo_count += 1;
-                        let pseudo_transition = rule.declarations.iter().find( |e| e.name == "transition" ).map( |e| e.value );
-                        match pseudo {
-                            Pseudo::Focus => { src!("Some(druid_xml::simple_style::PseudoStyle::focus( druid_xml::simple_style::Styler {{\n"); },
-                            Pseudo::Hover => { src!("Some(druid_xml::simple_style::PseudoStyle::hover( druid_xml::simple_style::Styler {{\n"); },
-                            Pseudo::Active => { src!("Some(druid_xml::simple_style::PseudoStyle::active( druid_xml::simple_style::Styler {{\n"); },
-                            Pseudo::Disabled => { src!("Some(druid_xml::simple_style::PseudoStyle::disabled( druid_xml::simple_style::Styler {{\n"); },
-                        }
-                        
-                        src!("     padding : ("); pseudo_style_opt!("druid::Insets::from(", "padding", ")"); _src!(0, ", {}),\n", pseudo_transition.map(|e| transition_option(e,"padding")).unwrap_or("None".to_string()) );
-                        src!("     margin : ("); pseudo_style_opt!("druid::Insets::from(", "margin", ")"); _src!(0, ", {}),\n", pseudo_transition.map(|e| transition_option(e,"margin")).unwrap_or("None".to_string()) );
-                        src!("     font_size : ("); pseudo_style_opt!("( ", "font-size", ")"); _src!(0, ", {}),\n", pseudo_transition.map(|e| transition_option(e,"font-size")).unwrap_or("None".to_string()) );
-                        src!("     width : ("); pseudo_style_opt!("width"); _src!(0, ", {}),\n", pseudo_transition.map(|e| transition_option(e,"width")).unwrap_or("None".to_string()) );
-                        src!("     height : ("); pseudo_style_opt!("height"); _src!(0, ", {}),\n", pseudo_transition.map(|e| transition_option(e,"height")).unwrap_or("None".to_string()) );
-                        src!("     text_color : ("); pseudo_style_opt!("color"); _src!(0, ", {}),\n", pseudo_transition.map(|e| transition_option(e,"color")).unwrap_or("None".to_string()) );
-                        src!("     background_color : ("); pseudo_style_opt!("background-color");  _src!(0, ", {}),\n", pseudo_transition.map(|e| transition_option(e,"background-color")).unwrap_or("None".to_string()) );
-                        src!("     border : ("); pseudo_style_opt!("druid_xml::simple_style::BorderStyle::new(", "border", ")"); _src!(0, ", {}),\n", pseudo_transition.map(|e| transition_option(e,"border")).unwrap_or("None".to_string()) );
-                        
-                        src!("}}) ), ");
-                    }
-                }
-            }
-
-            //fill 'None' 
-            for i in pseudo_count .. 4 {
-                src!("None,\n");
-            }
-            src!("];\n");
         }
 
-
-        //all component
-        //background, padding, 
-        {
-            //wrap Lens
-            if depth > 0 {
-                if let Some(lens) = attrs.get(b"lens") {
-                    let lens = String::from_utf8_lossy(&lens);
-                    src!("let {tag_wrap} = druid::WidgetExt::lens({tag_wrap}, {lens});\n");
-                }
+        //finally wrapping styler widget
+        //we must have wrapped above 'Padding' widget
+        if let Some(style) = spec.style.as_ref() {
+            //TODO : need optimization for duplicated style(use Rc)
+            src!("let normal_style = {};\n", style.normal.to_source());
+            src!("let pseudo_styles:[Option<druid_xml::simple_style::PseudoStyle>;5] = [\n");
+            for pseudo in style.pseudo.iter() {
+                let name = match pseudo.pseudo {
+                    Pseudo::Focus => "focus",
+                    Pseudo::Hover => "hover",
+                    Pseudo::Active => "active",
+                    Pseudo::Disabled => "disabled",
+                    Pseudo::Invalid => "invalid",
+                };
+                src!("\tSome(druid_xml::simple_style::PseudoStyle::{name}( {} )),\n", pseudo.style.to_source());
             }
-
-            //wrap `SizedBox` with optimize
-            if attrs.get(b"width").is_some() && attrs.get(b"height").is_some() {
-                style!("let {tag_wrap} = druid::WidgetExt::fix_size({tag_wrap}, " , "width-height", ");\n" );
-            } else {
-                style!("let {tag_wrap} = druid::WidgetExt::fix_width({tag_wrap}, " , "width", ");\n" );
-                style!("let {tag_wrap} = druid::WidgetExt::fix_height({tag_wrap}, " , "height", ");\n" );    
+            for _ in style.pseudo.len() .. 5 {
+                src!("\tNone,\n");
             }
-
-            //wrap `Padding` for Label,Button
-            if has_norm_style || has_pseudo_style {
-                src!("let {tag_wrap} = druid::WidgetExt::padding( {tag_wrap}, druid_xml::widget::theme::PADDING );\n");
+            src!("];\n");
+            if spec.validated() {
+                src!("let {tag_wrap} = druid_xml::widget::SimpleStyleWidget::new(normal_style, pseudo_styles, {tag_wrap} ).with_invalid(invalid);\n");
             } else {
-                //style!("let {tag_wrap} = druid::WidgetExt::padding({tag_wrap}, " , "padding", ");\n" );
+                src!("let {tag_wrap} = druid_xml::widget::SimpleStyleWidget::new(normal_style, pseudo_styles, {tag_wrap} );\n");
             }
+            src!("let {tag_wrap} = druid::WidgetExt::boxed({tag_wrap});\n");
+        }
 
-            //custom query wrapper
-            for (query, wrapper) in wrappers.iter() {
-                if let Some(selector) = simplecss::Selector::parse(query) {
-                    if selector.matches( &elem_query ) {
-                        //src!("let {tag_wrap} = ({wrapper})({tag_wrap});\n");
-                        src!("let widget = {tag_wrap};\n");
-                        src!("let {tag_wrap} = {wrapper};\n");
-                    }
+        //target of the command
+        if let Some(id) = spec.id.as_ref() {
+            if self.targets.contains(id) {
+                //every instance would have the same `WidgetId`
+                if spec.parents.iter().copied().chain( std::iter::once(spec.elem) ).any( |e| e.attributes.get("fn").map_or(false, |e| self.reused.contains(e)) ) {
+                    return Err(Error::ReusedTarget( (pos, id.clone()) ))
                 }
-            }
-
-            //finally wrapping styler widget
-            //we must have wrapped above 'Padding' widget
-            if has_norm_style || has_pseudo_style {
-                src!("let {tag_wrap} = druid_xml::widget::SimpleStyleWidget::new(normal_style, pseudo_styles, {tag_wrap} );\n");
-                src!("let {tag_wrap} = druid::WidgetExt::boxed({tag_wrap});\n");
+                src!("let {tag_wrap} = druid::WidgetExt::with_id({tag_wrap}, druid_xml::widget::widget_id({id:?}));\n");
             }
         }
 
@@ -836,138 +608,209 @@ impl SourceGenerator for DruidGenerator {
         Ok(())
     }
 
-    fn write(&mut self, elem_map:&HashMap<String,Element>, elem:&Element, css:&StyleSheet, wrappers:&HashMap<String,String>) -> Result<(),Error> {
-        self.impl_write(None,elem_map, &mut vec![], elem, css, wrappers)
+    fn write(&mut self, elem_map:&HashMap<String,Template>, template:&Template, css:&StyleSheet, wrappers:&HashMap<String,String>) -> Result<(),Error> {
+        self.targets.clear();
+        for template in elem_map.values() {
+            collect_targets(&template.elem, &mut self.targets);
+        }
+        self.reused = reused_templates(elem_map);
+        let ctx = Context { templates:elem_map, css, natives:None, cache:None };
+        let spec = WidgetSpec::root(&ctx, template)?;
+        self.impl_write(&spec, wrappers)
+    }
+}
+
+/// Rust expression of the resolved value
+trait ToSource {
+    fn to_source(&self) -> String;
+}
+
+impl ToSource for f64 {
+    fn to_source(&self) -> String {
+        format!("{self:?}f64")
     }
 }
 
-struct CSSAttribute;
-
-impl CSSAttribute {
-    fn padding(w:&mut String, v:&str) -> Result<(),Error> {
-        let mut splits = v.split_whitespace().map( |s| &s[..s.find("px").unwrap_or(s.len())] );
-        let count = splits.clone().count();
-        if count == 1 {
-            Self::size(w, splits.next().unwrap())?
-        } else if count == 2 || count == 4 {
-            write!(w,"(").unwrap();
-            splits.for_each(|e| write!(w,"{}f64,",e).unwrap() );
-            write!(w,")").unwrap();
-        } else {
-            panic!("The number of padding parameters must be one of 1,2,4. but \"{}\"",v);
-        }
-        Ok(())
+impl ToSource for Color {
+    fn to_source(&self) -> String {
+        let (r,g,b,a) = self.as_rgba8();
+        format!("druid::Color::rgba8({r},{g},{b},{a})")
     }
+}
 
-    //TODO : Error check
-    /// [O] : rgb(0,255,0)
-    /// [O] : rgba(0,255,0,88)
-    /// [O] : #FF33FF
-    /// [O] : #FF33FF22
-    /// [X] : rgb(100%, 0, 25%, 2)
-    fn color(w:&mut String, v:&str) -> Result<(),Error> {
-        let tv = v.trim();
-        if tv.starts_with('#') {
-            write!(w,"druid::Color::from_hex_str({})", tv).unwrap();
-        } else if tv.starts_with("rgba") && tv.ends_with(')') {
-            write!(w,"druid::Color::rgba8({})", &tv[tv.find('(').unwrap()+1 .. tv.rfind(')').unwrap()]).unwrap();
-        } else if tv.starts_with("rgb") && tv.ends_with(')') {
-            write!(w,"druid::Color::rgb8({})", &tv[tv.find('(').unwrap()+1 .. tv.rfind(')').unwrap()]).unwrap();
-        } else {
-            if let Some(rgba) = named_color::named_color(v) {
-                write!(w,"{}", rgba).unwrap();
-            } else {
-                return Err(Error::InvalidAttributeValue((0, "invalid color value")))
-            }
-        }
-        Ok(())
+impl ToSource for Insets {
+    fn to_source(&self) -> String {
+        format!("druid::Insets::new({}, {}, {}, {})", self.x0.to_source(), self.y0.to_source(), self.x1.to_source(), self.y1.to_source())
+    }
+}
+
+impl ToSource for BorderStyle {
+    fn to_source(&self) -> String {
+        format!("druid_xml::simple_style::BorderStyle::new({}, {}, {})", self.width.to_source(), self.radius.to_source(), self.color.to_source())
     }
+}
 
-    fn size(w:&mut String, v:&str) -> Result<(), Error> {
-        let tv = v.trim();
-        match tv.as_bytes() {
-            [val @ .. , b'p', b'x'] => write!(w,"{}f64", String::from_utf8_lossy(val) ).unwrap(),
-            [val @ .. , b'e', b'm'] => write!(w, "{}f64", String::from_utf8_lossy(val).parse::<f64>().map( |v| v / 0.0625).unwrap() ).unwrap(),
-            val @ _ => write!(w, "{}f64", String::from_utf8_lossy(val).parse::<f64>().unwrap() ).unwrap()
+impl ToSource for Number {
+    fn to_source(&self) -> String {
+        match self {
+            Number::Abs(v) => format!("druid_xml::qwidget::drawable::Number::Abs({})", v.to_source()),
+            Number::Rel(v) => format!("druid_xml::qwidget::drawable::Number::Rel({})", v.to_source()),
+            Number::Calc(v) => format!("druid_xml::qwidget::drawable::Number::expr({:?})", v.src())
         }
-        Ok(())
     }
+}
+
+impl ToSource for QVec2 {
+    fn to_source(&self) -> String {
+        format!("druid_xml::qwidget::drawable::QVec2{{ x:{}, y:{} }}", self.x.to_source(), self.y.to_source())
+    }
+}
 
-    //Reference : https://simplecss.eu/pxtoems.html or https://websemantics.uk/tools/font-size-conversion-pixel-point-em-rem-percent/
-    fn font_size(w:&mut String, v:&str) -> Result<(),Error> {
-        let tv = v.trim();
-        match tv.as_bytes() {
-            b"xx-small" => write!(w,"9f64" ).unwrap(),
-            b"x-small" => write!(w,"10f64" ).unwrap(),
-            b"small" => write!(w,"13.333f64" ).unwrap(),
-            b"medium" => write!(w,"16f64" ).unwrap(),
-            b"large" => write!(w,"18f64" ).unwrap(),
-            b"x-large" => write!(w,"24f64" ).unwrap(),
-            b"xx-large" => write!(w,"32f64" ).unwrap(),
-            [val @ .. , b'p', b'x'] => write!(w,"{}f64", String::from_utf8_lossy(val) ).unwrap(),
-            [val @ .. , b'e', b'm'] => write!(w, "{}f64", String::from_utf8_lossy(val).parse::<f64>().map( |v| v / 0.0625).unwrap() ).unwrap(),
-            [val @ .. , b'p', b't'] => write!(w, "{}f64", String::from_utf8_lossy(val).parse::<f64>().map( |v| v * 1.333).unwrap() ).unwrap() ,
-            [val @ .. , b'%'] => write!(w, "{}f64", String::from_utf8_lossy(val).parse::<f64>().map( |v| v / 100f64 / 0.0625 ).unwrap() ).unwrap(),
-            val @ _ => write!(w, "{}f64", String::from_utf8_lossy(val).parse::<f64>().unwrap() ).unwrap()
+impl ToSource for RoundedRectRadii {
+    fn to_source(&self) -> String {
+        format!("druid::RoundedRectRadii::new({}, {}, {}, {})", self.top_left.to_source(), self.top_right.to_source(), self.bottom_right.to_source(), self.bottom_left.to_source())
+    }
+}
+
+impl ToSource for FillMethod {
+    fn to_source(&self) -> String {
+        match self {
+            FillMethod::Solid(color) => format!("druid_xml::qwidget::drawable::FillMethod::Solid({})", color.to_source()),
+            //gradients are not available in the markup
+            _ => "druid_xml::qwidget::drawable::FillMethod::None".to_owned()
         }
-        Ok(())
     }
+}
 
-    fn text_align(w:&mut String, v:&str) -> Result<(), Error> {
-        match v {
-            "left" => write!(w, "druid::TextAlignment::Start").unwrap(),
-            "right" => write!(w, "druid::TextAlignment::End").unwrap(),
-            "center" => write!(w, "druid::TextAlignment::Center").unwrap(),
-            "justify" => write!(w, "druid::TextAlignment::Justified").unwrap(),
-            _ => return Err( Error::InvalidAttributeValue((0,"text-align")) )
+impl ToSource for Drawable {
+    fn to_source(&self) -> String {
+        match self {
+            Drawable::Rect { top, right, bottom, left, border, round, fill } => format!("druid_xml::qwidget::drawable::Drawable::Rect{{ top:{}, right:{}, bottom:{}, left:{}, border:{}, round:{}, fill:{} }}",
+                top.to_source(), right.to_source(), bottom.to_source(), left.to_source(), border.to_source(), round.to_source(), fill.to_source()),
+            Drawable::Circle { center, radius, border, fill } => format!("druid_xml::qwidget::drawable::Drawable::Circle{{ center:{}, radius:{}, border:{}, fill:{} }}",
+                center.to_source(), radius.to_source(), border.to_source(), fill.to_source()),
+            Drawable::Ellipse { center, radi, x_rot, border, fill } => format!("druid_xml::qwidget::drawable::Drawable::Ellipse{{ center:{}, radi:{}, x_rot:{}, border:{}, fill:{} }}",
+                center.to_source(), radi.to_source(), x_rot.to_source(), border.to_source(), fill.to_source()),
+            Drawable::Arc { center, radi, start_angle, sweep_angle, x_rot, border } => format!("druid_xml::qwidget::drawable::Drawable::Arc{{ center:{}, radi:{}, start_angle:{}, sweep_angle:{}, x_rot:{}, border:{} }}",
+                center.to_source(), radi.to_source(), start_angle.to_source(), sweep_angle.to_source(), x_rot.to_source(), border.to_source()),
+            Drawable::Line { start, end, style } => format!("druid_xml::qwidget::drawable::Drawable::Line{{ start:{}, end:{}, style:{} }}",
+                start.to_source(), end.to_source(), style.to_source()),
         }
-        Ok(())
     }
+}
 
-    fn border_color_and_width(w:&mut String, radius:Option<&str>, v:&str) -> Result<(), Error> {
-        let mut splited = v.split_whitespace();
-        let width = splited.next().map( |v| v[..v.find("px").unwrap_or(v.len())].parse::<f64>().unwrap() ).unwrap_or(1f64);
-        //TODO : support other border style?
-        let _border_style = splited.next().unwrap_or("solid");
-        if _border_style != "solid" {
-            Err(Error::InvalidAttributeValue((0,"border")))
-        } else {
-            let color = splited.next().unwrap_or("black");
-            write!(w,"{}f64, ", width).unwrap();
-            Self::size(w, radius.unwrap_or("0")).unwrap();
-            write!(w,",").unwrap();
-            Self::color(w,color).unwrap();
-            Ok(())
+impl ToSource for TimingFunction {
+    fn to_source(&self) -> String {
+        match self {
+            TimingFunction::CubicBezier { p1, p2, p3, p4 } => format!("druid_xml::simple_style::TimingFunction::CubicBezier{{ p1:{}, p2:{}, p3:{}, p4:{} }}", p1.to_source(), p2.to_source(), p3.to_source(), p4.to_source()),
+            TimingFunction::Steps { n, jumpterm } => format!("druid_xml::simple_style::TimingFunction::Steps{{ n:{}, jumpterm:druid_xml::simple_style::JumpTerm::{jumpterm:?} }}", n.to_source()),
+            _ => format!("druid_xml::simple_style::TimingFunction::{self:?}")
         }
     }
+}
 
-    //https://developer.mozilla.org/en-US/docs/Web/CSS/object-fit
-    fn object_fit(w:&mut String, v:&str) -> Result<(), Error> {
-        match v.trim() {
-            "none" => write!(w,"druid::widget::FillStart::None").unwrap(), //Do not scale
-            "fill" | "" => write!(w,"druid::widget::FillStart::Fill").unwrap(), //(default) Fill the widget with no dead space, aspect ratio of widget is used
-            "contain" => write!(w,"druid::widget::illStart::Contain").unwrap(), //As large as posible without changing aspect ratio of image and all of image shown
-            "cover" => write!(w,"druid::widget::FillStart::Cover").unwrap(), //As large as posible with no dead space so that some of the image may be clipped
-            "scale-down" => write!(w,"druid::widget::FillStart::ScaleDown").unwrap(), //Scale down to fit but do not scale up
+impl ToSource for AnimationState {
+    fn to_source(&self) -> String {
+        let anim = self.animation();
+        format!("druid_xml::simple_style::AnimationState::from( druid_xml::simple_style::Animation{{ delay: {}, direction: druid_xml::simple_style::Direction::{:?}, duration: {}, iteration: {}, name: {}, timing_function: {}, fill_mode: {} }} )",
+            anim.delay, anim.direction, anim.duration, anim.iteration.to_source(), anim.name.to_source(), anim.timing_function.to_source(), anim.fill_mode.to_source())
+    }
+}
 
-            //WARN : None-standard css attribute
-            "fit-width" => write!(w,"druid::widget::FillStart::FitWidth").unwrap(), //Fill the width with the images aspect ratio, some of the image may be clipped
-            "fit-height" => write!(w,"druid::widget::FillStart::FitHeight").unwrap(), //Fill the hight with the images aspect ratio, some of the image may be clipped
-            _ => return Err(Error::InvalidAttributeValue((0,"object-fit")))
+impl <T:ToSource> ToSource for Option<T> {
+    fn to_source(&self) -> String {
+        match self {
+            Some(v) => format!("Some({})", v.to_source()),
+            None => "None".to_owned()
         }
-        Ok(())
     }
+}
 
-    //https://developer.mozilla.org/en-US/docs/Web/CSS/image-rendering
-    fn image_rendering(w:&mut String, v:&str) -> Result<(), Error> {
-        match v.trim() {
-            //TODO 
-            "auto" | "smooth" | "high-quality" | "crisp-edges" => write!(w,"InterpolationMode::Bilinear").unwrap(),
+impl <A:ToSource, B:ToSource> ToSource for (A, B) {
+    fn to_source(&self) -> String {
+        format!("({}, {})", self.0.to_source(), self.1.to_source())
+    }
+}
 
-            "pixelated" => write!(w,"druid::piet::InterpolationMode::NearestNeighbor").unwrap(),
+impl ToSource for Styler {
+    fn to_source(&self) -> String {
+        let mut w = "druid_xml::simple_style::Styler {\n".to_owned();
+        write!(w, "\tpadding : {},\n", self.padding.to_source()).unwrap();
+        write!(w, "\tmargin : {},\n", self.margin.to_source()).unwrap();
+        write!(w, "\tfont_size : {},\n", self.font_size.to_source()).unwrap();
+        write!(w, "\twidth : {},\n", self.width.to_source()).unwrap();
+        write!(w, "\theight : {},\n", self.height.to_source()).unwrap();
+        write!(w, "\ttext_color : {},\n", self.text_color.to_source()).unwrap();
+        write!(w, "\tbackground_color : {},\n", self.background_color.to_source()).unwrap();
+        write!(w, "\tborder : {},\n", self.border.to_source()).unwrap();
+        w.push('}');
+        w
+    }
+}
 
-            _ => return Err(Error::InvalidAttributeValue((0,"image_rendering")))
+/// `druid::text::RichText` expression
+fn write_rich_text(w:&mut String, rich:&RichSpans) {
+    write!(w, "druid::text::RichText::new({:?}.into())", rich.text).unwrap();
+    for (range, style) in rich.spans.iter() {
+        write!(w, ".with_attribute({}..{}, ", range.start, range.end).unwrap();
+        match style {
+            InlineStyle::Bold => write!(w, "druid::text::Attribute::weight(druid::FontWeight::BOLD)").unwrap(),
+            InlineStyle::Italic => write!(w, "druid::text::Attribute::style(druid::FontStyle::Italic)").unwrap(),
+            InlineStyle::Underline => write!(w, "druid::text::Attribute::underline(true)").unwrap(),
+            InlineStyle::Color(v) => write!(w, "druid::text::Attribute::text_color({})", v.to_source()).unwrap(),
+            InlineStyle::FontSize(v) => write!(w, "druid::text::Attribute::size({})", v.to_source()).unwrap(),
+        }
+        write!(w, ")").unwrap();
+    }
+}
+
+/// Element ids of the `target="#id"`
+pub(crate) fn collect_targets(elem:&Element, targets:&mut HashSet<String>) {
+    if let Some(id) = elem.attributes.get("target").and_then( |e| e.trim().strip_prefix('#') ) {
+        targets.insert( id.to_owned() );
+    }
+    for child in elem.elements() {
+        collect_targets(child, targets);
+    }
+}
+
+/// Templates which have more than one instance. Used more than once(directly or by the reused template) or the list item
+pub(crate) fn reused_templates(elem_map:&HashMap<String,Template>) -> HashSet<String> {
+    fn uses<'a>(elem:&'a Element, elem_map:&HashMap<String,Template>, used:&mut Vec<&'a str>) {
+        if elem_map.contains_key(elem.tag()) {
+            used.push(elem.tag());
+        }
+        if let Some(item) = elem.attributes.get("item").filter( |_| elem.tag() == "list" ) {
+            used.extend( [item.trim(), item.trim()] );
+        }
+        for child in elem.elements() {
+            uses(child, elem_map, used);
+        }
+    }
+
+    let used_by = elem_map.iter().map( |(name, template)| {
+        let mut used = vec![];
+        uses(&template.elem, elem_map, &mut used);
+        (name, used)
+    }).collect::<Vec<_>>();
+    let mut reused = HashSet::new();
+    for (_, used) in used_by.iter() {
+        for name in used.iter() {
+            if used_by.iter().map( |(_, e)| e.iter().filter( |e| *e == name ).count() ).sum::<usize>() > 1 {
+                reused.insert( name.to_string() );
+            }
         }
-        Ok(())
     }
-}
\ No newline at end of file
+    //templates used by the reused template
+    loop {
+        let before = reused.len();
+        for (name, used) in used_by.iter() {
+            if reused.contains(*name) {
+                reused.extend( used.iter().map( |e| e.to_string() ) );
+            }
+        }
+        if reused.len() == before {
+            return reused
+        }
+    }
+}